@@ -1,9 +1,11 @@
 use wasm_bindgen::prelude::*;
 use js_sys::*;
 use web_sys::*;
-use std::collections::HashMap;
-use nalgebra::{Vector3, Matrix4, UnitQuaternion};
-use serde::{Serialize, Deserialize};
+use std::any::{Any, TypeId};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::iter::Iterator; // js_sys::* also exports an `Iterator`
+use nalgebra::{Vector2, Vector3, Point3, Matrix4, UnitQuaternion};
 
 // Web-Optimized Game Engine for Deplauncher 1.12 - Enhanced Edition (Rust)
 // Specifically optimized for web browsers with modern WebGL and Canvas API
@@ -24,566 +26,5419 @@ macro_rules! console_log {
     ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
 }
 
-// === WEB COLLISION SYSTEM ===
+macro_rules! platform_log {
+    ($logger:expr, $($t:tt)*) => ($logger.log(&format_args!($($t)*).to_string()))
+}
 
-#[derive(Debug)]
-pub struct WebCollisionSystem {
-    spatial_grid: HashMap<(i32, i32), Vec<u32>>,
-    cell_size: f32,
+// === PLATFORM SERVICES ===
+// The simulation core only talks to the outside world through these traits,
+// so it can run natively (tests, tools) as well as inside the browser.
+
+pub trait Clock {
+    /// Current time in milliseconds, same scale as `performance.now()`
+    fn now_ms(&self) -> f64;
 }
 
-impl WebCollisionSystem {
-    pub fn new() -> Self {
+pub trait RandomSource {
+    /// Uniform value in [0, 1), same contract as `Math.random()`
+    fn next_f64(&mut self) -> f64;
+    
+    /// Seed the current stream was started from
+    fn seed(&self) -> u32;
+    
+    /// Restarts the stream; the same seed always replays the same sequence
+    fn set_seed(&mut self, seed: u32);
+}
+
+pub trait Logger {
+    fn log(&self, message: &str);
+}
+
+pub struct Platform {
+    pub clock: Box<dyn Clock>,
+    pub rng: Box<dyn RandomSource>,
+    pub logger: Box<dyn Logger>,
+}
+
+impl Platform {
+    pub fn browser() -> Self {
         Self {
-            spatial_grid: HashMap::new(),
-            cell_size: 64.0,
+            clock: Box::new(BrowserClock),
+            rng: Box::new(XorShiftRandom::new((Math::random() * u32::MAX as f64) as u32)), // Only the initial seed is browser-random
+            logger: Box::new(ConsoleLogger),
         }
     }
     
-    pub fn update(&mut self, entities: &mut HashMap<u32, WebEntity>) -> i32 {
-        // Clear spatial grid
-        self.spatial_grid.clear();
-        
-        // Populate spatial grid
-        for (&id, entity) in entities.iter() {
-            if !entity.active {
-                continue;
-            }
-            
-            let grid_x = (entity.transform.position.x / self.cell_size) as i32;
-            let grid_y = (entity.transform.position.y / self.cell_size) as i32;
-            
-            self.spatial_grid.entry((grid_x, grid_y))
-                .or_insert_with(Vec::new)
-                .push(id);
+    pub fn headless(seed: u32) -> Self {
+        Self {
+            clock: Box::new(FixedClock(0.0)),
+            rng: Box::new(XorShiftRandom::new(seed)),
+            logger: Box::new(NullLogger),
         }
+    }
+}
+
+// Browser implementations (wasm only)
+
+pub struct BrowserClock;
+
+impl Clock for BrowserClock {
+    fn now_ms(&self) -> f64 {
+        now()
+    }
+}
+
+pub struct ConsoleLogger;
+
+impl Logger for ConsoleLogger {
+    fn log(&self, message: &str) {
+        log(message);
+    }
+}
+
+// Engine-owned seeded stream (every platform)
+
+pub struct XorShiftRandom {
+    seed: u32,
+    state: u64,
+}
+
+impl XorShiftRandom {
+    pub fn new(seed: u32) -> Self {
+        let mut rng = Self { seed, state: 0 };
+        rng.set_seed(seed);
+        rng
+    }
+}
+
+impl RandomSource for XorShiftRandom {
+    fn next_f64(&mut self) -> f64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 11) as f64 / (1u64 << 53) as f64
+    }
+    
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+    
+    fn set_seed(&mut self, seed: u32) {
+        // SplitMix64 scramble so neighbouring seeds produce unrelated streams
+        let mut z = (seed as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
         
-        let mut score_increment = 0;
-        let mut collisions = Vec::new();
+        self.seed = seed;
+        self.state = z.max(1); // Zero state would stay zero forever
+    }
+}
+
+// Headless implementations (native builds and tests)
+
+pub struct FixedClock(pub f64);
+
+impl Clock for FixedClock {
+    fn now_ms(&self) -> f64 {
+        self.0
+    }
+}
+
+pub struct NullLogger;
+
+impl Logger for NullLogger {
+    fn log(&self, _message: &str) {}
+}
+
+// === WORLD BOUNDS ===
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundaryMode {
+    Wrap,      // Leave one side, come back on the other
+    Clamp,     // Stop at the edge
+    Bounce,    // Reflect off the edge, losing speed by the body's restitution
+    Destroy,   // Removed once fully outside
+    Unbounded, // Bounds are ignored
+}
+
+impl BoundaryMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BoundaryMode::Wrap => "wrap",
+            BoundaryMode::Clamp => "clamp",
+            BoundaryMode::Bounce => "bounce",
+            BoundaryMode::Destroy => "destroy",
+            BoundaryMode::Unbounded => "unbounded",
+        }
+    }
+    
+    pub fn from_name(name: &str) -> Option<BoundaryMode> {
+        match name {
+            "wrap" => Some(BoundaryMode::Wrap),
+            "clamp" => Some(BoundaryMode::Clamp),
+            "bounce" => Some(BoundaryMode::Bounce),
+            "destroy" => Some(BoundaryMode::Destroy),
+            "unbounded" => Some(BoundaryMode::Unbounded),
+            _ => None,
+        }
+    }
+}
+
+// The playable rectangle in the XY plane; Z is never bounded. Wrap and destroy
+// act `margin` past the edges so sprites can leave the screen first, while
+// clamp and bounce keep the entity's bounding radius inside them.
+#[derive(Debug, Clone, Copy)]
+pub struct WorldBounds {
+    pub min: Vector2<f32>,
+    pub max: Vector2<f32>,
+    pub margin: f32,
+    pub mode: BoundaryMode, // For entities without a `WebBoundary` override
+}
+
+impl Default for WorldBounds {
+    fn default() -> Self {
+        Self {
+            min: Vector2::zeros(),
+            max: Vector2::new(CANVAS_WIDTH, CANVAS_HEIGHT),
+            margin: 0.0,
+            mode: BoundaryMode::Wrap,
+        }
+    }
+}
+
+impl WorldBounds {
+    /// Applies `mode` to one body; false means it left the world and should be removed
+    pub fn apply(&self, mode: BoundaryMode, transform: &mut WebTransform, radius: f32, restitution: f32) -> bool {
+        let before = transform.position;
         
-        // Check collisions within grid cells
-        for entity_ids in self.spatial_grid.values() {
-            for i in 0..entity_ids.len() {
-                for j in (i + 1)..entity_ids.len() {
-                    let id_a = entity_ids[i];
-                    let id_b = entity_ids[j];
-                    
-                    if let (Some(entity_a), Some(entity_b)) = (entities.get(&id_a), entities.get(&id_b)) {
-                        let distance = (entity_a.transform.position - entity_b.transform.position).magnitude();
-                        let collision_radius = entity_a.physics.as_ref().map(|p| p.collision_radius).unwrap_or(16.0) +
-                                             entity_b.physics.as_ref().map(|p| p.collision_radius).unwrap_or(16.0);
-                        
-                        if distance < collision_radius {
-                            collisions.push((id_a, id_b, distance, collision_radius));
-                            
-                            // Score for player collisions
-                            if entity_a.tag == "Player" || entity_b.tag == "Player" {
-                                score_increment += 10;
-                            }
-                        }
+        for axis in 0..2 {
+            let (min, max) = (self.min[axis], self.max[axis]);
+            let position = transform.position[axis];
+            
+            match mode {
+                BoundaryMode::Unbounded => {}
+                BoundaryMode::Wrap => {
+                    if position < min - self.margin {
+                        transform.position[axis] = max + self.margin;
+                    } else if position > max + self.margin {
+                        transform.position[axis] = min - self.margin;
                     }
                 }
-            }
-        }
-        
-        // Resolve collisions
-        for (id_a, id_b, distance, collision_radius) in collisions {
-            if let (Some(entity_a), Some(entity_b)) = (entities.get_mut(&id_a), entities.get_mut(&id_b)) {
-                let direction = (entity_a.transform.position - entity_b.transform.position).normalize();
-                let overlap = collision_radius - distance;
-                
-                // Separate entities
-                entity_a.transform.position += direction * overlap * 0.5;
-                entity_b.transform.position -= direction * overlap * 0.5;
-                
-                // Apply collision response
-                if let (Some(physics_a), Some(physics_b)) = (&entity_a.physics, &entity_b.physics) {
-                    let bounce_force = 100.0 * (physics_a.bounciness + physics_b.bounciness) * 0.5;
+                BoundaryMode::Destroy => {
+                    if position < min - self.margin || position > max + self.margin {
+                        return false;
+                    }
+                }
+                BoundaryMode::Clamp | BoundaryMode::Bounce => {
+                    // A body wider than the world is held at its center
+                    let (low, high) = if max - min > radius * 2.0 {
+                        (min + radius, max - radius)
+                    } else {
+                        ((min + max) * 0.5, (min + max) * 0.5)
+                    };
+                    let rebound = if mode == BoundaryMode::Bounce { restitution } else { 0.0 };
                     
-                    entity_a.transform.velocity += direction * bounce_force;
-                    entity_b.transform.velocity -= direction * bounce_force;
+                    if position < low {
+                        transform.position[axis] = low;
+                        transform.velocity[axis] = transform.velocity[axis].abs() * rebound;
+                    } else if position > high {
+                        transform.position[axis] = high;
+                        transform.velocity[axis] = -transform.velocity[axis].abs() * rebound;
+                    }
                 }
             }
         }
         
-        score_increment
+        // Teleported: don't interpolate across the world
+        if mode == BoundaryMode::Wrap && transform.position != before {
+            transform.previous_position = transform.position;
+        }
+        true
     }
 }
 
-// === MAIN WEB GAME STATE ===
+// === PHYSICS MATERIALS ===
 
-#[wasm_bindgen]
-pub struct WebGameState {
-    // Entity management
-    entities: HashMap<u32, WebEntity>,
-    next_entity_id: u32,
+// How two touching bodies' values mix. When their modes differ the later one
+// here wins, so a single `Max` surface (a trampoline, say) is always bouncy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CombineMode {
+    Average,
+    Min,
+    Multiply,
+    Max,
+}
+
+impl CombineMode {
+    pub fn from_name(name: &str) -> Option<CombineMode> {
+        match name {
+            "average" => Some(CombineMode::Average),
+            "min" => Some(CombineMode::Min),
+            "multiply" => Some(CombineMode::Multiply),
+            "max" => Some(CombineMode::Max),
+            _ => None,
+        }
+    }
     
-    // Systems
-    particle_system: WebParticleSystem,
-    collision_system: WebCollisionSystem,
-    performance: WebPerformanceMonitor,
-    input: WebInputSystem,
+    pub fn combine(self, a: f32, b: f32) -> f32 {
+        match self {
+            CombineMode::Average => (a + b) * 0.5,
+            CombineMode::Min => a.min(b),
+            CombineMode::Multiply => a * b,
+            CombineMode::Max => a.max(b),
+        }
+    }
+}
+
+// Surface properties of a body. Density is relative to a default-sized body:
+// at density 1 an entity with the default collider weighs 1, and mass grows
+// with collider area from there.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhysicsMaterial {
+    pub friction: f32,    // Largest friction impulse as a share of the normal one
+    pub restitution: f32, // Share of the approach speed kept after a bounce
+    pub density: f32,
+    pub friction_combine: CombineMode,
+    pub restitution_combine: CombineMode,
+}
+
+impl Default for PhysicsMaterial {
+    fn default() -> Self {
+        Self {
+            friction: 0.3,
+            restitution: 0.5,
+            density: 1.0,
+            friction_combine: CombineMode::Average,
+            restitution_combine: CombineMode::Average,
+        }
+    }
+}
+
+impl PhysicsMaterial {
+    pub fn ice() -> Self {
+        Self { friction: 0.02, restitution: 0.1, density: 0.9, friction_combine: CombineMode::Min, ..Self::default() }
+    }
     
-    // Browser capabilities
-    capabilities: BrowserCapabilities,
+    pub fn rubber() -> Self {
+        Self { friction: 0.9, restitution: 0.9, density: 1.2, restitution_combine: CombineMode::Max, ..Self::default() }
+    }
     
-    // Camera
-    camera_position: Vector3<f32>,
-    camera_target: Vector3<f32>,
-    camera_fov: f32,
+    pub fn mud() -> Self {
+        Self { friction: 1.0, restitution: 0.0, density: 1.6, friction_combine: CombineMode::Max, restitution_combine: CombineMode::Min }
+    }
     
-    // Physics
-    gravity: Vector3<f32>,
-    physics_enabled: bool,
+    /// Friction and restitution for a contact between the two materials
+    pub fn combine(&self, other: &PhysicsMaterial) -> (f32, f32) {
+        let friction_mode = self.friction_combine.max(other.friction_combine);
+        let restitution_mode = self.restitution_combine.max(other.restitution_combine);
+        (
+            friction_mode.combine(self.friction, other.friction),
+            restitution_mode.combine(self.restitution, other.restitution).clamp(0.0, 1.0),
+        )
+    }
     
-    // Game state
-    score: i32,
-    level: i32,
-    time_scale: f32,
-    paused: bool,
-    debug_mode: bool,
+    /// Mass of a body of this material with the given collider
+    pub fn mass_for(&self, shape: &ColliderShape) -> f32 {
+        let reference_area = ColliderShape::Circle { radius: DEFAULT_COLLISION_RADIUS }.area();
+        self.density * shape.area() / reference_area
+    }
 }
 
-#[wasm_bindgen]
-impl WebGameState {
-    #[wasm_bindgen(constructor)]
+// Named materials entities can be given from JS or scene files. Starts with
+// "default", "ice", "rubber" and "mud"; redefining a name doesn't change
+// entities that already use it.
+#[derive(Debug, Clone)]
+pub struct PhysicsMaterials {
+    materials: HashMap<String, PhysicsMaterial>,
+}
+
+impl PhysicsMaterials {
     pub fn new() -> Self {
-        console_log!("Creating Web Game State for v1.12 Enhanced Edition");
+        let mut materials = HashMap::new();
+        materials.insert("default".to_string(), PhysicsMaterial::default());
+        materials.insert("ice".to_string(), PhysicsMaterial::ice());
+        materials.insert("rubber".to_string(), PhysicsMaterial::rubber());
+        materials.insert("mud".to_string(), PhysicsMaterial::mud());
+        Self { materials }
+    }
+    
+    pub fn define(&mut self, name: &str, material: PhysicsMaterial) {
+        self.materials.insert(name.to_string(), material);
+    }
+    
+    pub fn get(&self, name: &str) -> Option<PhysicsMaterial> {
+        self.materials.get(name).copied()
+    }
+    
+    pub fn len(&self) -> usize {
+        self.materials.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.materials.is_empty()
+    }
+}
+
+impl Default for PhysicsMaterials {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// === COLLIDERS ===
+
+// Planar shapes lie in the XY plane, turn with `WebTransform::rotation` and
+// extend without limit along Z. Sphere and Cuboid are real 3D volumes; a
+// cuboid also turns about Z. Local points are relative to the transform.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColliderShape {
+    Circle { radius: f32 },
+    Aabb { half_extents: Vector2<f32> },         // Ignores rotation
+    Obb { half_extents: Vector2<f32> },
+    Capsule { half_length: f32, radius: f32 },  // Segment along local X
+    Polygon { points: Vec<Vector2<f32>> },      // Convex, either winding
+    Sphere { radius: f32 },
+    Cuboid { half_extents: Vector3<f32> },
+}
+
+#[derive(Debug, Clone)]
+pub struct WebCollider {
+    pub shape: ColliderShape,
+    pub is_trigger: bool, // Reports overlaps as trigger events but is never pushed apart
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    pub normal: Vector3<f32>, // Unit vector from the second shape towards the first
+    pub depth: f32,
+    pub point: Vector3<f32>,  // Middle of the touching region, where impulses act
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaycastHit {
+    pub entity: EntityHandle,
+    pub point: Vector3<f32>,
+    pub normal: Vector3<f32>, // Unit surface normal, facing back along the ray
+    pub distance: f32,
+}
+
+// A shape placed in the world: either a rounded segment (a point for
+// circles/spheres) or a convex hull with its face normals
+enum WorldShape {
+    Round { core: [Vector3<f32>; 2], radius: f32 },
+    Hull { vertices: Vec<Vector3<f32>>, axes: Vec<Vector3<f32>> },
+}
+
+// At least three finite points with every corner turning the same way and
+// the turns adding up to one revolution, which rules out collinear or repeated
+// points and self-intersecting stars
+fn is_convex_polygon(points: &[Vector2<f32>]) -> bool {
+    if points.len() < 3 || points.iter().any(|point| !point.iter().all(|coordinate| coordinate.is_finite())) {
+        return false;
+    }
+    
+    let mut turn_sign = 0.0;
+    let mut total_turn = 0.0;
+    for (i, a) in points.iter().enumerate() {
+        let edge = points[(i + 1) % points.len()] - a;
+        let next_edge = points[(i + 2) % points.len()] - points[(i + 1) % points.len()];
+        let cross = edge.perp(&next_edge);
+        if cross.abs() <= f32::EPSILON * edge.magnitude() * next_edge.magnitude() || (turn_sign != 0.0 && cross.signum() != turn_sign) {
+            return false;
+        }
+        turn_sign = cross.signum();
+        total_turn += cross.atan2(edge.dot(&next_edge));
+    }
+    (total_turn.abs() - std::f32::consts::TAU).abs() < 1e-3
+}
+
+impl ColliderShape {
+    pub fn is_planar(&self) -> bool {
+        !matches!(self, ColliderShape::Sphere { .. } | ColliderShape::Cuboid { .. })
+    }
+    
+    /// Radius of a sphere around the transform that contains the shape
+    pub fn bounding_radius(&self) -> f32 {
+        match self {
+            ColliderShape::Circle { radius } | ColliderShape::Sphere { radius } => *radius,
+            ColliderShape::Aabb { half_extents } | ColliderShape::Obb { half_extents } => half_extents.magnitude(),
+            ColliderShape::Capsule { half_length, radius } => half_length + radius,
+            ColliderShape::Polygon { points } => points.iter().map(|point| point.magnitude()).fold(0.0, f32::max),
+            ColliderShape::Cuboid { half_extents } => half_extents.magnitude(),
+        }
+    }
+    
+    /// Sizes must be finite and non-negative and polygons strictly convex;
+    /// anything else breaks the contact, area and inertia math
+    pub fn is_valid(&self) -> bool {
+        let valid_size = |size: f32| size.is_finite() && size >= 0.0;
+        match self {
+            ColliderShape::Circle { radius } | ColliderShape::Sphere { radius } => valid_size(*radius),
+            ColliderShape::Aabb { half_extents } | ColliderShape::Obb { half_extents } => half_extents.iter().all(|&half| valid_size(half)),
+            ColliderShape::Cuboid { half_extents } => half_extents.iter().all(|&half| valid_size(half)),
+            ColliderShape::Capsule { half_length, radius } => valid_size(*half_length) && valid_size(*radius),
+            ColliderShape::Polygon { points } => is_convex_polygon(points),
+        }
+    }
+    
+    /// Separating-axis test; `None` when the shapes don't overlap
+    pub fn contact(&self, transform: &WebTransform, other: &ColliderShape, other_transform: &WebTransform) -> Option<Contact> {
+        let shape_a = self.place(transform);
+        let shape_b = other.place(other_transform);
+        let planar = self.is_planar() || other.is_planar();
         
-        let capabilities = BrowserCapabilities::default();
+        let mut axes = Vec::new();
+        shape_a.collect_axes(&shape_b, &mut axes);
+        shape_b.collect_axes(&shape_a, &mut axes);
+        axes.push(Vector3::x()); // Concentric round shapes have no other usable axis
         
-        let mut game_state = Self {
-            entities: HashMap::with_capacity(MAX_ENTITIES),
-            next_entity_id: 1,
-            
-            particle_system: WebParticleSystem::new(),
-            collision_system: WebCollisionSystem::new(),
-            performance: WebPerformanceMonitor::new(),
-            input: WebInputSystem::new(),
-            
-            capabilities,
-            
-            camera_position: Vector3::new(CANVAS_WIDTH / 2.0, CANVAS_HEIGHT / 2.0, -500.0),
-            camera_target: Vector3::new(CANVAS_WIDTH / 2.0, CANVAS_HEIGHT / 2.0, 0.0),
-            camera_fov: 75.0,
+        let mut best: Option<(Vector3<f32>, f32)> = None;
+        for mut axis in axes {
+            // Planar shapes never separate along Z, so only in-plane axes count
+            if planar {
+                axis.z = 0.0;
+            }
+            let length = axis.magnitude();
+            if length < 1e-6 {
+                continue;
+            }
+            axis /= length;
             
-            gravity: Vector3::new(0.0, -490.0, 0.0), // Reduced for web
-            physics_enabled: true,
+            let (min_a, max_a) = shape_a.project(&axis);
+            let (min_b, max_b) = shape_b.project(&axis);
+            let depth = max_a.min(max_b) - min_a.max(min_b);
+            if depth <= 0.0 {
+                return None;
+            }
             
-            score: 0,
-            level: 1,
-            time_scale: 1.0,
-            paused: false,
-            debug_mode: false,
-        };
+            if best.is_none_or(|(_, best_depth)| depth < best_depth) {
+                best = Some((axis, depth));
+            }
+        }
         
-        game_state.initialize_scene();
-        game_state
+        // Orient the normal from the other shape towards this one
+        let (mut normal, depth) = best?;
+        if (transform.position - other_transform.position).dot(&normal) < 0.0 {
+            normal = -normal;
+        }
+        let point = contact_point(&shape_a, &shape_b, &normal);
+        Some(Contact { normal, depth, point })
     }
     
-    fn initialize_scene(&mut self) {
-        // Create player entity
-        let player_id = self.create_entity("Player".to_string(), Vector3::new(CANVAS_WIDTH / 2.0, CANVAS_HEIGHT / 2.0, 0.0));
+    /// Area in the XY plane; 3D shapes use their cross-section
+    pub fn area(&self) -> f32 {
+        match self {
+            ColliderShape::Circle { radius } | ColliderShape::Sphere { radius } => std::f32::consts::PI * radius * radius,
+            ColliderShape::Aabb { half_extents } | ColliderShape::Obb { half_extents } => 4.0 * half_extents.x * half_extents.y,
+            ColliderShape::Cuboid { half_extents } => 4.0 * half_extents.x * half_extents.y,
+            ColliderShape::Capsule { half_length, radius } => 4.0 * half_length * radius + std::f32::consts::PI * radius * radius,
+            ColliderShape::Polygon { points } => {
+                let twice_area: f32 = points.iter().enumerate()
+                    .map(|(i, a)| a.perp(&points[(i + 1) % points.len()]))
+                    .sum();
+                twice_area.abs() * 0.5
+            }
+        }
+    }
+    
+    /// Moment of inertia about the entity position for a mass of 1; planar
+    /// shapes and cuboids alike spin about Z
+    pub fn unit_inertia(&self) -> f32 {
+        match self {
+            ColliderShape::Circle { radius } => radius * radius * 0.5,
+            ColliderShape::Sphere { radius } => radius * radius * 0.4,
+            ColliderShape::Aabb { half_extents } | ColliderShape::Obb { half_extents } => half_extents.magnitude_squared() / 3.0,
+            ColliderShape::Cuboid { half_extents } => (half_extents.x * half_extents.x + half_extents.y * half_extents.y) / 3.0,
+            // Treated as its bounding box
+            ColliderShape::Capsule { half_length, radius } => ((half_length + radius).powi(2) + radius * radius) / 3.0,
+            ColliderShape::Polygon { points } => {
+                // Triangle fan from the entity position; the winding cancels out
+                let (mut weighted, mut area) = (0.0, 0.0);
+                for (i, a) in points.iter().enumerate() {
+                    let b = &points[(i + 1) % points.len()];
+                    let cross = a.perp(b);
+                    weighted += cross * (a.dot(a) + a.dot(b) + b.dot(b));
+                    area += cross;
+                }
+                if area.abs() > 1e-6 {
+                    weighted / (6.0 * area)
+                } else {
+                    self.bounding_radius().powi(2) * 0.5
+                }
+            }
+        }
+    }
+    
+    /// Distance along a unit `direction` to the first hit and the surface
+    /// normal there; a ray starting inside hits at distance 0
+    pub fn raycast(&self, transform: &WebTransform, origin: Vector3<f32>, direction: Vector3<f32>, max_distance: f32) -> Option<(f32, Vector3<f32>)> {
+        let (mut origin, mut direction) = (origin, direction);
         
-        if let Some(player) = self.entities.get_mut(&player_id) {
-            player.add_physics(WebPhysics {
-                mass: 1.0,
-                use_gravity: false, // Top-down view
-                drag: 5.0,
-                ..Default::default()
-            });
-            
-            player.add_renderer(WebRenderer {
-                color: [0.3, 0.8, 1.0, 1.0], // Cyan
-                ..Default::default()
-            });
-            
-            player.add_health(100.0);
-            player.tag = "Player".to_string();
+        // Planar shapes have no Z extent to hit, so trace in their plane
+        if self.is_planar() {
+            origin.z = transform.position.z;
+            direction.z = 0.0;
+            if direction.magnitude_squared() < 1e-12 {
+                return None; // Straight along Z there is no edge to hit
+            }
         }
         
-        // Generate environment entities (reduced for web)
-        self.generate_environment(50);
+        self.place(transform).raycast(&origin, &direction, max_distance)
+    }
+    
+    fn place(&self, transform: &WebTransform) -> WorldShape {
+        let center = transform.position;
+        let (sin, cos) = transform.rotation.to_radians().sin_cos();
+        let axis_x = Vector3::new(cos, sin, 0.0);
+        let axis_y = Vector3::new(-sin, cos, 0.0);
+        let local = |x: f32, y: f32| center + axis_x * x + axis_y * y;
+        
+        match self {
+            ColliderShape::Circle { radius } | ColliderShape::Sphere { radius } => {
+                WorldShape::Round { core: [center, center], radius: *radius }
+            }
+            ColliderShape::Capsule { half_length, radius } => {
+                WorldShape::Round { core: [local(-half_length, 0.0), local(*half_length, 0.0)], radius: *radius }
+            }
+            ColliderShape::Aabb { half_extents } => {
+                let (x, y) = (half_extents.x, half_extents.y);
+                WorldShape::Hull {
+                    vertices: vec![
+                        center + Vector3::new(-x, -y, 0.0),
+                        center + Vector3::new(x, -y, 0.0),
+                        center + Vector3::new(x, y, 0.0),
+                        center + Vector3::new(-x, y, 0.0),
+                    ],
+                    axes: vec![Vector3::x(), Vector3::y()],
+                }
+            }
+            ColliderShape::Obb { half_extents } => {
+                let (x, y) = (half_extents.x, half_extents.y);
+                WorldShape::Hull {
+                    vertices: vec![local(-x, -y), local(x, -y), local(x, y), local(-x, y)],
+                    axes: vec![axis_x, axis_y],
+                }
+            }
+            ColliderShape::Polygon { points } => {
+                let vertices: Vec<Vector3<f32>> = points.iter().map(|point| local(point.x, point.y)).collect();
+                let axes = (0..vertices.len())
+                    .map(|i| {
+                        let edge = vertices[(i + 1) % vertices.len()] - vertices[i];
+                        Vector3::new(-edge.y, edge.x, 0.0)
+                    })
+                    .collect();
+                WorldShape::Hull { vertices, axes }
+            }
+            ColliderShape::Cuboid { half_extents } => {
+                let (x, y, z) = (half_extents.x, half_extents.y, half_extents.z);
+                let vertices = [-1.0, 1.0].iter()
+                    .flat_map(|&sz| [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].map(|(sx, sy)| {
+                        local(sx * x, sy * y) + Vector3::z() * (sz * z)
+                    }))
+                    .collect();
+                WorldShape::Hull { vertices, axes: vec![axis_x, axis_y, Vector3::z()] }
+            }
+        }
+    }
+}
+
+impl WorldShape {
+    // Surface points furthest along `direction`: one for a vertex or round
+    // cap, two for an edge or capsule side, more for a cuboid face
+    fn support_feature(&self, direction: &Vector3<f32>) -> Vec<Vector3<f32>> {
+        let (points, offset): (&[Vector3<f32>], Vector3<f32>) = match self {
+            WorldShape::Round { core, radius } => (core, direction * *radius),
+            WorldShape::Hull { vertices, .. } => (vertices, Vector3::zeros()),
+        };
         
-        console_log!("Scene initialized with {} entities", self.entities.len());
+        let reach = points.iter().map(|point| point.dot(direction)).fold(f32::MIN, f32::max);
+        points.iter()
+            .filter(|point| point.dot(direction) >= reach - 1e-2)
+            .map(|point| point + offset)
+            .collect()
+    }
+    
+    fn raycast(&self, origin: &Vector3<f32>, direction: &Vector3<f32>, max_distance: f32) -> Option<(f32, Vector3<f32>)> {
+        match self {
+            WorldShape::Hull { axes, .. } => {
+                let slabs = axes.iter().map(|axis| {
+                    let (min, max) = self.project(axis);
+                    (*axis, min, max)
+                });
+                clip_ray_to_slabs(slabs, origin, direction, max_distance)
+            }
+            WorldShape::Round { core, radius } => {
+                // A capsule is two end circles plus the box between them
+                let along = core[1] - core[0];
+                let side = Vector3::new(-along.y, along.x, 0.0);
+                let body = if along.magnitude_squared() > 1e-12 {
+                    let slabs = [
+                        (along, core[0].dot(&along), core[1].dot(&along)),
+                        (side, core[0].dot(&side) - radius * side.magnitude(), core[0].dot(&side) + radius * side.magnitude()),
+                    ];
+                    clip_ray_to_slabs(slabs.into_iter(), origin, direction, max_distance)
+                } else {
+                    None
+                };
+                
+                core.iter()
+                    .filter_map(|center| ray_sphere(origin, direction, center, *radius, max_distance))
+                    .chain(body)
+                    .min_by(|a, b| a.0.total_cmp(&b.0))
+            }
+        }
+    }
+    
+    fn project(&self, axis: &Vector3<f32>) -> (f32, f32) {
+        match self {
+            WorldShape::Round { core, radius } => {
+                let (start, end) = (core[0].dot(axis), core[1].dot(axis));
+                (start.min(end) - radius, start.max(end) + radius)
+            }
+            WorldShape::Hull { vertices, .. } => vertices.iter()
+                .map(|vertex| vertex.dot(axis))
+                .fold((f32::MAX, f32::MIN), |(min, max), value| (min.min(value), max.max(value))),
+        }
+    }
+    
+    // For box hulls (Aabb, Obb and Cuboid, the only ones with orthonormal axes):
+    // the point of `segment` nearest the box centre and the closest box point
+    // to it, found by clamping in the box's own frame. Planar boxes have no Z
+    // axis, so the point keeps its Z.
+    fn closest_box_point(&self, segment: &[Vector3<f32>; 2]) -> Option<(Vector3<f32>, Vector3<f32>)> {
+        let WorldShape::Hull { vertices, axes } = self else {
+            return None;
+        };
+        let orthonormal = axes.iter().enumerate().all(|(i, axis)| {
+            (axis.magnitude_squared() - 1.0).abs() < 1e-4 && axes[i + 1..].iter().all(|other| axis.dot(other).abs() < 1e-4)
+        });
+        if !orthonormal || vertices.is_empty() {
+            return None;
+        }
+        
+        let center = vertices.iter().sum::<Vector3<f32>>() / vertices.len() as f32;
+        let point = closest_point_on_segment(segment, &center);
+        let mut box_point = point;
+        for axis in axes {
+            let half_extent = vertices.iter().map(|vertex| (vertex - center).dot(axis)).fold(0.0, f32::max);
+            let offset = (point - center).dot(axis);
+            box_point -= axis * (offset - offset.clamp(-half_extent, half_extent));
+        }
+        Some((point, box_point))
+    }
+    
+    // Face normals for hulls; for rounded shapes, directions from the core
+    // towards the other shape's nearest features (plus the capsule side normal)
+    fn collect_axes(&self, other: &WorldShape, axes: &mut Vec<Vector3<f32>>) {
+        match self {
+            WorldShape::Hull { axes: face_axes, .. } => axes.extend_from_slice(face_axes),
+            WorldShape::Round { core, .. } => {
+                let direction = core[1] - core[0];
+                axes.push(Vector3::new(-direction.y, direction.x, 0.0));
+                
+                match other {
+                    WorldShape::Hull { vertices, .. } => {
+                        for vertex in vertices {
+                            axes.push(vertex - closest_point_on_segment(core, vertex));
+                        }
+                        // A cuboid's edges can be nearer than any of its faces or corners
+                        if let Some((point, box_point)) = other.closest_box_point(core) {
+                            axes.push(box_point - point);
+                        }
+                    }
+                    WorldShape::Round { core: other_core, .. } => {
+                        let (point, other_point) = closest_points_between_segments(core, other_core);
+                        axes.push(other_point - point);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn closest_point_on_segment(segment: &[Vector3<f32>; 2], point: &Vector3<f32>) -> Vector3<f32> {
+    let direction = segment[1] - segment[0];
+    let length_squared = direction.magnitude_squared();
+    if length_squared < 1e-12 {
+        return segment[0];
+    }
+    
+    let t = ((point - segment[0]).dot(&direction) / length_squared).clamp(0.0, 1.0);
+    segment[0] + direction * t
+}
+
+// Unless the segments cross, the closest pair involves an endpoint of one of them
+fn closest_points_between_segments(a: &[Vector3<f32>; 2], b: &[Vector3<f32>; 2]) -> (Vector3<f32>, Vector3<f32>) {
+    let candidates = [
+        (a[0], closest_point_on_segment(b, &a[0])),
+        (a[1], closest_point_on_segment(b, &a[1])),
+        (closest_point_on_segment(a, &b[0]), b[0]),
+        (closest_point_on_segment(a, &b[1]), b[1]),
+    ];
+    candidates.into_iter()
+        .min_by(|(a0, b0), (a1, b1)| (b0 - a0).magnitude_squared().total_cmp(&(b1 - a1).magnitude_squared()))
+        .unwrap_or((a[0], b[0]))
+}
+
+// Middle of where the two touching features overlap sideways, e.g. the centre
+// of the shared stretch when a box rests on a wider one
+fn contact_point(shape_a: &WorldShape, shape_b: &WorldShape, normal: &Vector3<f32>) -> Vector3<f32> {
+    let feature_a = shape_a.support_feature(&-normal);
+    let feature_b = shape_b.support_feature(normal);
+    let centroid = |points: &[Vector3<f32>]| points.iter().sum::<Vector3<f32>>() / points.len().max(1) as f32;
+    let middle = (centroid(&feature_a) + centroid(&feature_b)) * 0.5;
+    
+    // Sideways is only well defined for in-plane normals
+    let tangent = Vector3::new(normal.y, -normal.x, 0.0);
+    if tangent.magnitude_squared() < 1e-6 {
+        return middle;
+    }
+    let tangent = tangent.normalize();
+    
+    let extent = |points: &[Vector3<f32>]| points.iter()
+        .map(|point| point.dot(&tangent))
+        .fold((f32::MAX, f32::MIN), |(min, max), value| (min.min(value), max.max(value)));
+    let (min_a, max_a) = extent(&feature_a);
+    let (min_b, max_b) = extent(&feature_b);
+    let (low, high) = (min_a.max(min_b), max_a.min(max_b));
+    if low > high {
+        return middle;
+    }
+    middle + tangent * ((low + high) * 0.5 - middle.dot(&tangent))
+}
+
+// Narrows [0, max_distance] to where the ray is inside every slab; the normal
+// belongs to the slab entered last
+fn clip_ray_to_slabs(slabs: impl Iterator<Item = (Vector3<f32>, f32, f32)>,
+                     origin: &Vector3<f32>, direction: &Vector3<f32>, max_distance: f32) -> Option<(f32, Vector3<f32>)> {
+    let (mut enter, mut exit) = (0.0, max_distance);
+    let mut normal = -direction;
+    
+    for (axis, min, max) in slabs {
+        let start = origin.dot(&axis);
+        let speed = direction.dot(&axis);
+        if speed.abs() < 1e-9 {
+            if start < min || start > max {
+                return None; // Parallel and outside
+            }
+            continue;
+        }
+        
+        let (t_min, t_max) = ((min - start) / speed, (max - start) / speed);
+        let (near, far, face) = if t_min < t_max { (t_min, t_max, -axis) } else { (t_max, t_min, axis) };
+        if near > enter {
+            enter = near;
+            normal = face;
+        }
+        exit = exit.min(far);
+        if enter > exit {
+            return None;
+        }
+    }
+    
+    let length = normal.magnitude();
+    Some((enter, if length > 1e-9 { normal / length } else { normal }))
+}
+
+fn ray_sphere(origin: &Vector3<f32>, direction: &Vector3<f32>, center: &Vector3<f32>, radius: f32, max_distance: f32) -> Option<(f32, Vector3<f32>)> {
+    let offset = origin - center;
+    let a = direction.magnitude_squared();
+    let b = offset.dot(direction);
+    let c = offset.magnitude_squared() - radius * radius;
+    if c <= 0.0 {
+        return Some((0.0, -direction.normalize()));
+    }
+    
+    let discriminant = b * b - a * c;
+    if a < 1e-12 || discriminant < 0.0 {
+        return None;
+    }
+    let distance = (-b - discriminant.sqrt()) / a;
+    if !(0.0..=max_distance).contains(&distance) {
+        return None;
+    }
+    Some((distance, (offset + direction * distance).normalize()))
+}
+
+// === SPATIAL GRID ===
+
+pub type GridCell = (i32, i32, i32);
+
+// Persistent 3D hash: entities only move between cells when they cross a
+// boundary, so a frame with little motion touches almost nothing.
+#[derive(Debug)]
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<GridCell, Vec<EntityHandle>>,
+    entity_cells: HashMap<EntityHandle, GridCell>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            entity_cells: HashMap::new(),
+        }
+    }
+    
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+    
+    /// Changing the cell size empties the grid; entities re-enter on their next `update`
+    pub fn set_cell_size(&mut self, cell_size: f32) {
+        if cell_size != self.cell_size {
+            self.cell_size = cell_size;
+            self.clear();
+        }
+    }
+    
+    pub fn cell_of(&self, position: Vector3<f32>) -> GridCell {
+        (
+            (position.x / self.cell_size).floor() as i32,
+            (position.y / self.cell_size).floor() as i32,
+            (position.z / self.cell_size).floor() as i32,
+        )
+    }
+    
+    /// Inserts the entity or moves it if it crossed into another cell
+    pub fn update(&mut self, handle: EntityHandle, position: Vector3<f32>) {
+        let cell = self.cell_of(position);
+        match self.entity_cells.insert(handle, cell) {
+            Some(previous) if previous == cell => return,
+            Some(previous) => self.remove_from_cell(handle, previous),
+            None => {}
+        }
+        
+        self.cells.entry(cell).or_default().push(handle);
+    }
+    
+    pub fn remove(&mut self, handle: EntityHandle) -> bool {
+        match self.entity_cells.remove(&handle) {
+            Some(cell) => {
+                self.remove_from_cell(handle, cell);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Drops every entity for which `keep` returns false
+    pub fn retain(&mut self, mut keep: impl FnMut(EntityHandle) -> bool) {
+        let stale: Vec<EntityHandle> = self.entity_cells.keys()
+            .copied()
+            .filter(|&handle| !keep(handle))
+            .collect();
+        for handle in stale {
+            self.remove(handle);
+        }
+    }
+    
+    /// Entities in every cell touching the box grown by one cell, since shapes
+    /// can spill up to half a cell past their own
+    pub fn query_box(&self, min: Vector3<f32>, max: Vector3<f32>) -> Vec<EntityHandle> {
+        let mut handles = Vec::new();
+        self.query_box_into(min, max, &mut handles);
+        handles
+    }
+    
+    /// `query_box` appending to `handles`, for callers that reuse the storage
+    pub fn query_box_into(&self, min: Vector3<f32>, max: Vector3<f32>, handles: &mut Vec<EntityHandle>) {
+        let Some((occupied_min, occupied_max)) = self.occupied_cells() else {
+            return;
+        };
+        
+        // Only the occupied range can hold anything, which also keeps huge boxes cheap
+        let (low, high) = (self.cell_of(min), self.cell_of(max));
+        // `cell_of` saturates far-off corners to the i32 limits, so the margin must too
+        let x_range = low.0.saturating_sub(1).max(occupied_min.0)..=high.0.saturating_add(1).min(occupied_max.0);
+        let y_range = low.1.saturating_sub(1).max(occupied_min.1)..=high.1.saturating_add(1).min(occupied_max.1);
+        let z_range = low.2.saturating_sub(1).max(occupied_min.2)..=high.2.saturating_add(1).min(occupied_max.2);
+        
+        for x in x_range {
+            for y in y_range.clone() {
+                for z in z_range.clone() {
+                    if let Some(cell) = self.cells.get(&(x, y, z)) {
+                        handles.extend_from_slice(cell);
+                    }
+                }
+            }
+        }
+    }
+    
+    /// Cells a unit-direction ray passes through, in order, with the distance
+    /// at which it enters each. Walks only the occupied part of the grid (plus
+    /// the one-cell margin shapes can spill into), so long rays stay cheap
+    pub fn cells_along_ray(&self, origin: Vector3<f32>, direction: Vector3<f32>, max_distance: f32) -> Vec<(f32, GridCell)> {
+        let Some((low, high)) = self.occupied_cells() else {
+            return Vec::new();
+        };
+        
+        let region_min = Vector3::new(low.0, low.1, low.2).cast::<f32>().add_scalar(-1.0) * self.cell_size;
+        let region_max = Vector3::new(high.0, high.1, high.2).cast::<f32>().add_scalar(2.0) * self.cell_size;
+        let slabs = (0..3).map(|axis| (Vector3::ith(axis, 1.0), region_min[axis], region_max[axis]));
+        let Some((start, _)) = clip_ray_to_slabs(slabs, &origin, &direction, max_distance) else {
+            return Vec::new();
+        };
+        
+        let first = self.cell_of(origin + direction * start);
+        let mut cell = [first.0, first.1, first.2];
+        let mut step = [0; 3];
+        let mut next_boundary = [f32::INFINITY; 3];
+        let mut boundary_spacing = [f32::INFINITY; 3];
+        for axis in 0..3 {
+            if direction[axis] > 0.0 {
+                step[axis] = 1;
+                next_boundary[axis] = ((cell[axis] + 1) as f32 * self.cell_size - origin[axis]) / direction[axis];
+            } else if direction[axis] < 0.0 {
+                step[axis] = -1;
+                next_boundary[axis] = (cell[axis] as f32 * self.cell_size - origin[axis]) / direction[axis];
+            }
+            if step[axis] != 0 {
+                boundary_spacing[axis] = self.cell_size / direction[axis].abs();
+            }
+        }
+        
+        let mut cells = Vec::new();
+        let mut distance = start;
+        loop {
+            cells.push((distance, (cell[0], cell[1], cell[2])));
+            
+            let axis = (0..3).min_by(|&a, &b| next_boundary[a].total_cmp(&next_boundary[b])).unwrap_or(0);
+            distance = next_boundary[axis];
+            if distance > max_distance || !(region_min[axis]..=region_max[axis]).contains(&(origin[axis] + direction[axis] * distance)) {
+                break;
+            }
+            cell[axis] += step[axis];
+            next_boundary[axis] += boundary_spacing[axis];
+        }
+        cells
+    }
+    
+    /// Lowest and highest occupied cell coordinates on each axis
+    pub fn occupied_cells(&self) -> Option<(GridCell, GridCell)> {
+        self.cells.keys().fold(None, |bounds, &(x, y, z)| match bounds {
+            None => Some(((x, y, z), (x, y, z))),
+            Some((min, max)) => Some((
+                (min.0.min(x), min.1.min(y), min.2.min(z)),
+                (max.0.max(x), max.1.max(y), max.2.max(z)),
+            )),
+        })
+    }
+    
+    /// Entities in `cell` and the 26 cells around it
+    pub fn neighbors(&self, cell: GridCell) -> impl Iterator<Item = EntityHandle> + '_ {
+        let (x, y, z) = cell;
+        (-1..=1).flat_map(move |dx| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (x + dx, y + dy, z + dz))))
+            .filter_map(move |neighbor| self.cells.get(&neighbor))
+            .flat_map(|handles| handles.iter().copied())
+    }
+    
+    pub fn len(&self) -> usize {
+        self.entity_cells.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.entity_cells.is_empty()
+    }
+    
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.entity_cells.clear();
+    }
+    
+    fn remove_from_cell(&mut self, handle: EntityHandle, cell: GridCell) {
+        if let Some(handles) = self.cells.get_mut(&cell) {
+            if let Some(slot) = handles.iter().position(|&other| other == handle) {
+                handles.swap_remove(slot);
+            }
+            if handles.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+}
+
+// === WEB COLLISION SYSTEM ===
+
+#[derive(Debug)]
+pub struct WebCollisionSystem {
+    spatial_grid: SpatialGrid,
+    contacts: Vec<(EntityHandle, EntityHandle)>, // Sorted pairs touching last update
+    trigger_contacts: Vec<(EntityHandle, EntityHandle)>, // Sorted (trigger, other) overlaps
+    tick_contacts: Vec<(EntityHandle, EntityHandle)>, // Seen by any update since the last report
+    tick_trigger_contacts: Vec<(EntityHandle, EntityHandle)>,
+    reported_contacts: Vec<(EntityHandle, EntityHandle)>, // As of the last report
+    reported_trigger_contacts: Vec<(EntityHandle, EntityHandle)>,
+    pub position_iterations: u32, // Separation passes per update; extra ones settle stacks
+}
+
+impl WebCollisionSystem {
+    pub fn new() -> Self {
+        Self {
+            spatial_grid: SpatialGrid::new(DEFAULT_COLLISION_RADIUS * 2.0),
+            contacts: Vec::new(),
+            trigger_contacts: Vec::new(),
+            tick_contacts: Vec::new(),
+            tick_trigger_contacts: Vec::new(),
+            reported_contacts: Vec::new(),
+            reported_trigger_contacts: Vec::new(),
+            position_iterations: 1,
+        }
+    }
+    
+    pub fn spatial_grid(&self) -> &SpatialGrid {
+        &self.spatial_grid
+    }
+    
+    /// Solid pairs touching after the last update, sorted as (lower, higher)
+    pub fn contacts(&self) -> &[(EntityHandle, EntityHandle)] {
+        &self.contacts
+    }
+    
+    /// Resize and re-bucket the grid to the world's current transforms
+    pub fn refresh_grid(&mut self, world: &World) {
+        // Any touching pair is at most two of the largest radii apart, so with
+        // cells that wide it always lands in the same or a neighboring cell
+        let largest_radius = world.query::<WebPhysics>()
+            .map(|(_, physics)| physics.collision_radius)
+            .chain(world.query::<WebCollider>().map(|(_, collider)| collider.shape.bounding_radius()))
+            .fold(DEFAULT_COLLISION_RADIUS, f32::max);
+        self.spatial_grid.set_cell_size(largest_radius * 2.0);
+        
+        // Bring the grid up to date
+        self.spatial_grid.retain(|handle| world.has::<WebTransform>(handle));
+        for (handle, transform) in world.query::<WebTransform>() {
+            self.spatial_grid.update(handle, transform.position);
+        }
+    }
+    
+    /// Only a reporting update (the last substep of a tick) emits contact
+    /// events and score, covering every pair that touched in any substep
+    /// since the previous report, so substeps neither multiply nor lose them
+    pub fn update(&mut self, world: &mut World, events: &mut EventQueue, report: bool) -> i32 {
+        Self::sweep_continuous(world);
+        self.refresh_grid(world);
+        
+        let shape_of = |handle| collider_shape(world, handle);
+        let is_player = |handle| world.get::<WebEntityInfo>(handle).is_some_and(|info| info.tag == "Player");
+        
+        let mut collisions = Vec::new();
+        let mut trigger_contacts = Vec::new();
+        
+        // Check each awake entity against its own and neighboring cells; pairs
+        // of sleeping or static entities can't change, so they are skipped
+        for (handle, transform) in world.query::<WebTransform>() {
+            if !is_awake(world, handle) {
+                continue;
+            }
+            let cell = self.spatial_grid.cell_of(transform.position);
+            
+            for other in self.spatial_grid.neighbors(cell) {
+                // Each pair once, from the lower handle when both are awake
+                if other == handle || (other < handle && is_awake(world, other)) {
+                    continue;
+                }
+                
+                // Always as (lower, higher)
+                let (id_a, id_b) = (handle.min(other), handle.max(other));
+                if !layers_match(world, id_a, id_b) {
+                    continue;
+                }
+                
+                if let (Some(transform_a), Some(transform_b)) = (world.get::<WebTransform>(id_a), world.get::<WebTransform>(id_b)) {
+                    let (shape_a, shape_b) = (shape_of(id_a), shape_of(id_b));
+                    
+                    // Cheap bounding-sphere reject before the separating-axis test
+                    let distance = (transform_a.position - transform_b.position).magnitude();
+                    if distance >= shape_a.bounding_radius() + shape_b.bounding_radius() {
+                        continue;
+                    }
+                    
+                    if let Some(contact) = shape_a.contact(transform_a, &shape_b, transform_b) {
+                        // Trigger overlaps are only reported, never resolved or scored
+                        let (trigger_a, trigger_b) = (is_trigger(world, id_a), is_trigger(world, id_b));
+                        if trigger_a || trigger_b {
+                            if trigger_a {
+                                trigger_contacts.push((id_a, id_b));
+                            }
+                            if trigger_b {
+                                trigger_contacts.push((id_b, id_a));
+                            }
+                            continue;
+                        }
+                        
+                        collisions.push((id_a, id_b, contact));
+                    }
+                }
+            }
+        }
+        
+        // Resolve in handle order so results don't depend on HashMap iteration order
+        collisions.sort_by_key(|&(id_a, id_b, _)| (id_a, id_b));
+        
+        // Untested pairs keep the contacts they had when they came to rest
+        let resting = |&(a, b): &(EntityHandle, EntityHandle)| {
+            !is_awake(world, a) && !is_awake(world, b) && world.has::<WebTransform>(a) && world.has::<WebTransform>(b)
+        };
+        trigger_contacts.extend(self.trigger_contacts.iter().copied().filter(resting));
+        
+        let mut contacts: Vec<(EntityHandle, EntityHandle)> = collisions.iter()
+            .map(|&(id_a, id_b, _)| (id_a, id_b))
+            .chain(self.contacts.iter().copied().filter(resting))
+            .collect();
+        contacts.sort();
+        self.tick_contacts.extend_from_slice(&contacts);
+        self.contacts = contacts;
+        trigger_contacts.sort();
+        self.tick_trigger_contacts.extend_from_slice(&trigger_contacts);
+        self.trigger_contacts = trigger_contacts;
+        
+        let mut score_increment = 0;
+        if report {
+            let mut contacts = std::mem::take(&mut self.tick_contacts);
+            contacts.sort();
+            contacts.dedup();
+            let mut trigger_contacts = std::mem::take(&mut self.tick_trigger_contacts);
+            trigger_contacts.sort();
+            trigger_contacts.dedup();
+            
+            // Score for player collisions
+            for &(id_a, id_b) in &contacts {
+                if is_player(id_a) || is_player(id_b) {
+                    score_increment += 10;
+                }
+            }
+            
+            diff_sorted_pairs(&self.reported_contacts, &contacts, |(a, b), change| match change {
+                PairChange::Added => events.push(EngineEvent::CollisionStarted { a, b }),
+                PairChange::Kept => {}
+                PairChange::Removed => events.push(EngineEvent::CollisionEnded { a, b }),
+            });
+            self.reported_contacts = contacts;
+            
+            diff_sorted_pairs(&self.reported_trigger_contacts, &trigger_contacts, |(trigger, other), change| {
+                events.push(match change {
+                    PairChange::Added => EngineEvent::TriggerEnter { trigger, other },
+                    PairChange::Kept => EngineEvent::TriggerStay { trigger, other },
+                    PairChange::Removed => EngineEvent::TriggerExit { trigger, other },
+                });
+            });
+            self.reported_trigger_contacts = trigger_contacts;
+        }
+        
+        // Resolve collisions
+        let pairs: Vec<(EntityHandle, EntityHandle)> = collisions.iter().map(|&(id_a, id_b, _)| (id_a, id_b)).collect();
+        for (id_a, id_b, contact) in collisions {
+            // A sleeping body hit by an awake one wakes, and its island with it
+            for handle in [id_a, id_b] {
+                if let Some(physics) = world.get_mut::<WebPhysics>(handle) {
+                    if physics.sleeping {
+                        physics.wake();
+                    }
+                }
+            }
+            
+            let (Some(body_a), Some(body_b)) = (ContactBody::at(world, id_a, contact.point), ContactBody::at(world, id_b, contact.point)) else {
+                continue;
+            };
+            let inverse_mass_sum = body_a.inverse_mass + body_b.inverse_mass;
+            if inverse_mass_sum <= 0.0 {
+                continue; // Two immovable bodies
+            }
+            
+            let relative_velocity = body_a.velocity - body_b.velocity;
+            let (normal, overlap) = (contact.normal, contact.depth);
+            
+            // Impulse along the normal, only while the bodies are approaching
+            let closing_speed = relative_velocity.dot(&normal);
+            let mut impulse = Vector3::zeros();
+            if closing_speed < 0.0 {
+                // Static bodies without physics take on the other body's material
+                let (friction, restitution) = match (world.get::<WebPhysics>(id_a), world.get::<WebPhysics>(id_b)) {
+                    (Some(physics_a), Some(physics_b)) => physics_a.material.combine(&physics_b.material),
+                    (Some(physics), None) | (None, Some(physics)) => (physics.material.friction, physics.material.restitution),
+                    (None, None) => (0.0, 0.0),
+                };
+                let normal_impulse = -(1.0 + restitution) * closing_speed
+                    / (body_a.inverse_effective_mass(&normal) + body_b.inverse_effective_mass(&normal));
+                impulse += normal * normal_impulse;
+                
+                // Friction opposes sliding at the contact, which is what sets bodies spinning
+                let sliding = relative_velocity - normal * closing_speed;
+                let sliding_speed = sliding.magnitude();
+                if sliding_speed > 1e-4 {
+                    let tangent = sliding / sliding_speed;
+                    let friction_impulse = sliding_speed
+                        / (body_a.inverse_effective_mass(&tangent) + body_b.inverse_effective_mass(&tangent));
+                    impulse -= tangent * friction_impulse.min(friction * normal_impulse);
+                }
+            }
+            
+            // Separate entities in proportion to how easily each one moves
+            if let Some(transform_a) = world.get_mut::<WebTransform>(id_a) {
+                transform_a.position += normal * (overlap * body_a.inverse_mass / inverse_mass_sum);
+            }
+            if let Some(transform_b) = world.get_mut::<WebTransform>(id_b) {
+                transform_b.position -= normal * (overlap * body_b.inverse_mass / inverse_mass_sum);
+            }
+            body_a.apply_impulse(world, id_a, impulse);
+            body_b.apply_impulse(world, id_b, -impulse);
+        }
+        
+        // Separating one pair can push a body into the next, so crowded scenes
+        // re-test this update's pairs at their corrected positions. Pairs that
+        // only start touching during these passes wait for the next update
+        for _ in 1..self.position_iterations {
+            for &(id_a, id_b) in &pairs {
+                Self::separate(world, id_a, id_b);
+            }
+        }
+        
+        score_increment
+    }
+    
+    // Moves an overlapping pair apart, splitting the distance like the resolution pass
+    fn separate(world: &mut World, id_a: EntityHandle, id_b: EntityHandle) {
+        let (Some(transform_a), Some(transform_b)) = (world.get::<WebTransform>(id_a), world.get::<WebTransform>(id_b)) else {
+            return;
+        };
+        let Some(contact) = collider_shape(world, id_a).contact(transform_a, &collider_shape(world, id_b), transform_b) else {
+            return;
+        };
+        
+        let inverse_mass = |handle| world.get::<WebPhysics>(handle).map_or(0.0, |physics| physics.inverse_mass());
+        let (inverse_mass_a, inverse_mass_b) = (inverse_mass(id_a), inverse_mass(id_b));
+        let inverse_mass_sum = inverse_mass_a + inverse_mass_b;
+        if inverse_mass_sum <= 0.0 {
+            return;
+        }
+        
+        if let Some(transform_a) = world.get_mut::<WebTransform>(id_a) {
+            transform_a.position += contact.normal * (contact.depth * inverse_mass_a / inverse_mass_sum);
+        }
+        if let Some(transform_b) = world.get_mut::<WebTransform>(id_b) {
+            transform_b.position -= contact.normal * (contact.depth * inverse_mass_b / inverse_mass_sum);
+        }
+    }
+    
+    // Pulls fast `continuous_collision` bodies back to their first contact
+    // along this tick's motion, so the discrete pass can't step over thin
+    // objects. Targets count as static; fast bodies are few, so each one
+    // simply scans every other entity.
+    fn sweep_continuous(world: &mut World) {
+        let mut hits = Vec::new();
+        
+        for (handle, physics, transform) in world.query2::<WebPhysics, WebTransform>() {
+            if !physics.continuous_collision || physics.is_kinematic {
+                continue;
+            }
+            
+            let shape = collider_shape(world, handle);
+            let motion = transform.position - transform.previous_position;
+            if motion.magnitude() <= shape.bounding_radius() {
+                continue; // Too slow to skip past anything this tick
+            }
+            
+            let earliest = world.query::<WebTransform>()
+                .filter(|&(other, _)| other != handle && !is_trigger(world, other) && layers_match(world, handle, other))
+                .filter_map(|(other, other_transform)| {
+                    time_of_impact(&shape, transform, &collider_shape(world, other), other_transform)
+                })
+                .fold(None, |earliest: Option<f32>, time| Some(earliest.map_or(time, |earliest| earliest.min(time))));
+            
+            if let Some(time) = earliest {
+                hits.push((handle, transform.previous_position + motion * time));
+            }
+        }
+        
+        for (handle, position) in hits {
+            if let Some(transform) = world.get_mut::<WebTransform>(handle) {
+                transform.position = position;
+            }
+        }
+    }
+}
+
+// One side of a contact, as seen from the contact point
+struct ContactBody {
+    inverse_mass: f32,
+    inverse_inertia: f32,
+    offset: Vector3<f32>,   // From the body's position to the contact point
+    velocity: Vector3<f32>, // Of the body's surface at the contact point, spin included
+}
+
+impl ContactBody {
+    // Entities without physics are static, like kinematic ones
+    fn at(world: &World, handle: EntityHandle, point: Vector3<f32>) -> Option<Self> {
+        let transform = world.get::<WebTransform>(handle)?;
+        let physics = world.get::<WebPhysics>(handle);
+        let offset = point - transform.position;
+        let spin = physics.map_or(0.0, |physics| physics.angular_velocity.to_radians());
+        
+        Some(Self {
+            inverse_mass: physics.map_or(0.0, |physics| physics.inverse_mass()),
+            inverse_inertia: physics.map_or(0.0, |physics| physics.inverse_inertia(&collider_shape(world, handle))),
+            offset,
+            velocity: transform.velocity + Vector3::new(-spin * offset.y, spin * offset.x, 0.0),
+        })
+    }
+    
+    // How much the contact point's speed along `direction` changes per unit impulse
+    fn inverse_effective_mass(&self, direction: &Vector3<f32>) -> f32 {
+        let lever = self.offset.xy().perp(&direction.xy());
+        self.inverse_mass + lever * lever * self.inverse_inertia
+    }
+    
+    fn apply_impulse(&self, world: &mut World, handle: EntityHandle, impulse: Vector3<f32>) {
+        if let Some(transform) = world.get_mut::<WebTransform>(handle) {
+            transform.velocity += impulse * self.inverse_mass;
+        }
+        if let Some(physics) = world.get_mut::<WebPhysics>(handle) {
+            physics.angular_velocity += (self.offset.xy().perp(&impulse.xy()) * self.inverse_inertia).to_degrees();
+        }
+    }
+}
+
+enum PairChange {
+    Added,
+    Kept,
+    Removed,
+}
+
+// Both lists are sorted, so one merge pass finds new, ongoing and broken pairs
+fn diff_sorted_pairs(previous: &[(EntityHandle, EntityHandle)],
+                     current: &[(EntityHandle, EntityHandle)],
+                     mut report: impl FnMut((EntityHandle, EntityHandle), PairChange)) {
+    let (mut old, mut new) = (0, 0);
+    while old < previous.len() || new < current.len() {
+        match (previous.get(old), current.get(new)) {
+            (Some(&before), Some(&now)) if before == now => {
+                report(now, PairChange::Kept);
+                old += 1;
+                new += 1;
+            }
+            (Some(&before), Some(&now)) if before < now => {
+                report(before, PairChange::Removed);
+                old += 1;
+            }
+            (Some(&before), None) => {
+                report(before, PairChange::Removed);
+                old += 1;
+            }
+            (_, Some(&now)) => {
+                report(now, PairChange::Added);
+                new += 1;
+            }
+            (None, None) => break,
+        }
+    }
+}
+
+// === CONSTRAINTS ===
+
+// Links between entities, solved after integration. Ends without physics (or
+// kinematic ones) are immovable, so they make natural anchors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Constraint {
+    Distance { a: EntityHandle, b: EntityHandle, length: f32 },           // Rigid rod
+    Spring { a: EntityHandle, b: EntityHandle, rest_length: f32, stiffness: f32, damping: f32 },
+    Rope { a: EntityHandle, b: EntityHandle, max_length: f32 },           // Slack until taut
+    Pin { entity: EntityHandle, anchor: Vector3<f32>, length: f32 },      // Rod to a world point; 0 holds it there
+}
+
+impl Constraint {
+    pub fn entities(&self) -> (EntityHandle, Option<EntityHandle>) {
+        match *self {
+            Constraint::Distance { a, b, .. } | Constraint::Spring { a, b, .. } | Constraint::Rope { a, b, .. } => (a, Some(b)),
+            Constraint::Pin { entity, .. } => (entity, None),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum LinkEnd {
+    Entity(EntityHandle),
+    Point(Vector3<f32>),
+}
+
+pub struct ConstraintSolver {
+    constraints: Vec<(u32, Constraint)>,
+    next_id: u32,
+    pub iterations: u32, // Passes over the hard constraints per tick; more is stiffer
+}
+
+impl ConstraintSolver {
+    pub fn new() -> Self {
+        Self {
+            constraints: Vec::new(),
+            next_id: 1,
+            iterations: DEFAULT_CONSTRAINT_ITERATIONS,
+        }
+    }
+    
+    /// Id for `remove`; never 0
+    pub fn add(&mut self, constraint: Constraint) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.constraints.push((id, constraint));
+        id
+    }
+    
+    pub fn remove(&mut self, id: u32) -> bool {
+        let count = self.constraints.len();
+        self.constraints.retain(|&(other, _)| other != id);
+        self.constraints.len() != count
+    }
+    
+    pub fn iter(&self) -> impl Iterator<Item = &Constraint> {
+        self.constraints.iter().map(|(_, constraint)| constraint)
+    }
+    
+    pub fn get(&self, id: u32) -> Option<&Constraint> {
+        self.constraints.iter().find(|(other, _)| *other == id).map(|(_, constraint)| constraint)
+    }
+    
+    pub fn len(&self) -> usize {
+        self.constraints.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.constraints.is_empty()
+    }
+    
+    pub fn clear(&mut self) {
+        self.constraints.clear();
+    }
+    
+    pub fn solve(&mut self, world: &mut World, delta_time: f32) {
+        // Constraints die with either of their entities
+        self.constraints.retain(|(_, constraint)| {
+            let (a, b) = constraint.entities();
+            world.is_alive(a) && b.is_none_or(|b| world.is_alive(b))
+        });
+        
+        // Springs are forces, applied once per tick
+        for (_, constraint) in &self.constraints {
+            if let Constraint::Spring { a, b, rest_length, stiffness, damping } = *constraint {
+                Self::apply_spring(world, a, b, rest_length, stiffness, damping, delta_time);
+            }
+        }
+        
+        // Hard constraints converge by repeated passes in insertion order
+        for _ in 0..self.iterations {
+            for (_, constraint) in &self.constraints {
+                match *constraint {
+                    Constraint::Distance { a, b, length } => Self::solve_link(world, a, LinkEnd::Entity(b), length, length),
+                    Constraint::Rope { a, b, max_length } => Self::solve_link(world, a, LinkEnd::Entity(b), 0.0, max_length),
+                    Constraint::Pin { entity, anchor, length } => Self::solve_link(world, entity, LinkEnd::Point(anchor), length, length),
+                    Constraint::Spring { .. } => {}
+                }
+            }
+        }
+    }
+    
+    // Position, velocity and inverse mass of one end
+    fn body(world: &World, end: LinkEnd) -> Option<(Vector3<f32>, Vector3<f32>, f32)> {
+        match end {
+            LinkEnd::Entity(handle) => {
+                let transform = world.get::<WebTransform>(handle)?;
+                let inverse_mass = world.get::<WebPhysics>(handle).map_or(0.0, |physics| physics.inverse_mass());
+                Some((transform.position, transform.velocity, inverse_mass))
+            }
+            LinkEnd::Point(point) => Some((point, Vector3::zeros(), 0.0)),
+        }
+    }
+    
+    fn move_body(world: &mut World, end: LinkEnd, position_change: Vector3<f32>, velocity_change: Vector3<f32>) {
+        if let LinkEnd::Entity(handle) = end {
+            if let Some(transform) = world.get_mut::<WebTransform>(handle) {
+                transform.position += position_change;
+                transform.velocity += velocity_change;
+            }
+        }
+    }
+    
+    // Moves both ends, in proportion to how easily each one moves, until their
+    // distance lies within [min_length, max_length], and removes the relative
+    // velocity that would pull it back out
+    fn solve_link(world: &mut World, a: EntityHandle, b: LinkEnd, min_length: f32, max_length: f32) {
+        let a = LinkEnd::Entity(a);
+        let (Some((position_a, velocity_a, inverse_mass_a)), Some((position_b, velocity_b, inverse_mass_b))) = (Self::body(world, a), Self::body(world, b)) else {
+            return;
+        };
+        let inverse_mass_sum = inverse_mass_a + inverse_mass_b;
+        if inverse_mass_sum <= 0.0 {
+            return;
+        }
+        
+        let offset = position_b - position_a;
+        let relative_velocity = velocity_b - velocity_a;
+        let (correction, velocity_correction) = if max_length <= 1e-6 {
+            // Holding two points together has no direction to work along
+            (offset, relative_velocity)
+        } else {
+            let distance = offset.magnitude();
+            if distance < 1e-6 {
+                return;
+            }
+            let normal = offset / distance;
+            let error = distance - distance.clamp(min_length, max_length);
+            let drift = relative_velocity.dot(&normal);
+            
+            // A rod stops all stretching and squashing; a rope only stretching once taut
+            let rigid = max_length - min_length <= 1e-6;
+            if error == 0.0 && !rigid {
+                return;
+            }
+            let drift = if rigid || drift * error > 0.0 { drift } else { 0.0 };
+            (normal * error, normal * drift)
+        };
+        
+        Self::move_body(world, a, correction * (inverse_mass_a / inverse_mass_sum), velocity_correction * (inverse_mass_a / inverse_mass_sum));
+        Self::move_body(world, b, -correction * (inverse_mass_b / inverse_mass_sum), -velocity_correction * (inverse_mass_b / inverse_mass_sum));
+    }
+    
+    // Hooke's law plus damping along the spring, as an impulse over `delta_time`
+    fn apply_spring(world: &mut World, a: EntityHandle, b: EntityHandle, rest_length: f32, stiffness: f32, damping: f32, delta_time: f32) {
+        let (a, b) = (LinkEnd::Entity(a), LinkEnd::Entity(b));
+        let (Some((position_a, velocity_a, inverse_mass_a)), Some((position_b, velocity_b, inverse_mass_b))) = (Self::body(world, a), Self::body(world, b)) else {
+            return;
+        };
+        
+        let offset = position_b - position_a;
+        let distance = offset.magnitude();
+        if distance < 1e-6 {
+            return;
+        }
+        let normal = offset / distance;
+        
+        let force = stiffness * (distance - rest_length) + damping * (velocity_b - velocity_a).dot(&normal);
+        let impulse = normal * (force * delta_time);
+        Self::move_body(world, a, Vector3::zeros(), impulse * inverse_mass_a);
+        Self::move_body(world, b, Vector3::zeros(), -impulse * inverse_mass_b);
+    }
+}
+
+impl Default for ConstraintSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// === SLEEPING ===
+
+// Bodies that stay slow for SLEEP_DELAY seconds fall asleep and are skipped by
+// gravity, integration and collision checks. Bodies touching each other or
+// linked by a constraint form an island, which only sleeps (and always wakes)
+// as a whole, so a stack can't fall asleep with one box still sliding.
+pub struct SleepTracker {
+    pub enabled: bool,
+    island_count: usize,
+    sleeping_count: usize,
+}
+
+impl SleepTracker {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            island_count: 0,
+            sleeping_count: 0,
+        }
+    }
+    
+    /// Islands found by the last update, sleeping or not
+    pub fn island_count(&self) -> usize {
+        self.island_count
+    }
+    
+    pub fn sleeping_count(&self) -> usize {
+        self.sleeping_count
+    }
+    
+    pub fn update(&mut self, world: &mut World, contacts: &[(EntityHandle, EntityHandle)], constraints: &ConstraintSolver, delta_time: f32) {
+        // Only bodies collisions can move take part; a static floor would
+        // otherwise join everything standing on it into one island
+        let bodies: Vec<EntityHandle> = world.query::<WebPhysics>()
+            .filter(|(_, physics)| physics.inverse_mass() > 0.0)
+            .map(|(handle, _)| handle)
+            .collect();
+        let index_of: HashMap<EntityHandle, usize> = bodies.iter().enumerate().map(|(index, &handle)| (handle, index)).collect();
+        
+        // Time each awake body has spent below the sleep thresholds
+        for &handle in &bodies {
+            let speed = world.get::<WebTransform>(handle).map_or(0.0, |transform| transform.velocity.magnitude());
+            if let Some(physics) = world.get_mut::<WebPhysics>(handle) {
+                if physics.sleeping {
+                    continue;
+                }
+                
+                let slow = speed < SLEEP_LINEAR_THRESHOLD && physics.angular_velocity.abs() < SLEEP_ANGULAR_THRESHOLD;
+                physics.sleep_timer = if slow && physics.can_sleep && self.enabled {
+                    physics.sleep_timer + delta_time
+                } else {
+                    0.0
+                };
+            }
+        }
+        
+        // Union-find over touching and linked bodies
+        let mut parents: Vec<usize> = (0..bodies.len()).collect();
+        let links = contacts.iter().map(|&(a, b)| (a, Some(b))).chain(constraints.iter().map(Constraint::entities));
+        for (a, b) in links {
+            if let (Some(&index_a), Some(&index_b)) = (index_of.get(&a), b.and_then(|b| index_of.get(&b))) {
+                let (root_a, root_b) = (find_root(&mut parents, index_a), find_root(&mut parents, index_b));
+                parents[root_a] = root_b;
+            }
+        }
+        
+        let mut islands: HashMap<usize, Vec<EntityHandle>> = HashMap::new();
+        for (index, &handle) in bodies.iter().enumerate() {
+            islands.entry(find_root(&mut parents, index)).or_default().push(handle);
+        }
+        
+        self.island_count = islands.len();
+        self.sleeping_count = 0;
+        for members in islands.values() {
+            let ready = self.enabled && members.iter().all(|&handle| {
+                world.get::<WebPhysics>(handle).is_none_or(|physics| physics.sleeping || physics.sleep_timer >= SLEEP_DELAY)
+            });
+            
+            for &handle in members {
+                if ready {
+                    if let Some(physics) = world.get_mut::<WebPhysics>(handle) {
+                        physics.sleeping = true;
+                        physics.angular_velocity = 0.0;
+                    }
+                    if let Some(transform) = world.get_mut::<WebTransform>(handle) {
+                        transform.velocity = Vector3::zeros();
+                        transform.acceleration = Vector3::zeros(); // Or a later substep would wake it
+                    }
+                } else if let Some(physics) = world.get_mut::<WebPhysics>(handle) {
+                    if physics.sleeping {
+                        physics.wake();
+                    }
+                }
+            }
+            if ready {
+                self.sleeping_count += members.len();
+            }
+        }
+    }
+}
+
+impl Default for SleepTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn find_root(parents: &mut [usize], mut index: usize) -> usize {
+    while parents[index] != index {
+        parents[index] = parents[parents[index]]; // Path halving
+        index = parents[index];
+    }
+    index
+}
+
+// === ENGINE EVENTS ===
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EngineEvent {
+    CollisionStarted { a: EntityHandle, b: EntityHandle },
+    CollisionEnded { a: EntityHandle, b: EntityHandle },
+    EntityDied { entity: EntityHandle },
+    EntitySpawned { entity: EntityHandle },
+    QualityChanged { level: u8 },
+    Paused { paused: bool },
+    TriggerEnter { trigger: EntityHandle, other: EntityHandle },
+    TriggerStay { trigger: EntityHandle, other: EntityHandle },
+    TriggerExit { trigger: EntityHandle, other: EntityHandle },
+}
+
+impl EngineEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EngineEvent::CollisionStarted { .. } => "CollisionStarted",
+            EngineEvent::CollisionEnded { .. } => "CollisionEnded",
+            EngineEvent::EntityDied { .. } => "EntityDied",
+            EngineEvent::EntitySpawned { .. } => "EntitySpawned",
+            EngineEvent::QualityChanged { .. } => "QualityChanged",
+            EngineEvent::Paused { .. } => "Paused",
+            EngineEvent::TriggerEnter { .. } => "TriggerEnter",
+            EngineEvent::TriggerStay { .. } => "TriggerStay",
+            EngineEvent::TriggerExit { .. } => "TriggerExit",
+        }
+    }
+}
+
+// Events stay queued until drained (normally by JS once per frame). Systems
+// read `current_tick` for what happened since the fixed tick began.
+#[derive(Debug, Default)]
+pub struct EventQueue {
+    events: VecDeque<EngineEvent>,
+    tick_start: usize,
+    pub dropped_events: u64,
+}
+
+impl EventQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn push(&mut self, event: EngineEvent) {
+        // Nobody is draining; drop the oldest rather than grow forever
+        if self.events.len() >= MAX_QUEUED_EVENTS {
+            self.events.pop_front();
+            self.tick_start = self.tick_start.saturating_sub(1);
+            self.dropped_events += 1;
+        }
+        self.events.push_back(event);
+    }
+    
+    pub fn begin_tick(&mut self) {
+        self.tick_start = self.events.len();
+    }
+    
+    /// Events emitted since the current fixed tick started
+    pub fn current_tick(&self) -> impl Iterator<Item = &EngineEvent> {
+        self.events.range(self.tick_start..)
+    }
+    
+    /// Every event not yet drained, oldest first
+    pub fn pending(&self) -> impl Iterator<Item = &EngineEvent> {
+        self.events.iter()
+    }
+    
+    pub fn drain(&mut self) -> Vec<EngineEvent> {
+        self.tick_start = 0;
+        std::mem::take(&mut self.events).into()
+    }
+    
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+// Entities without a collider fall back to a circle of their physics radius.
+// Called per candidate pair, so real colliders are borrowed, never cloned.
+fn collider_shape(world: &World, handle: EntityHandle) -> Cow<'_, ColliderShape> {
+    match world.get::<WebCollider>(handle) {
+        Some(collider) => Cow::Borrowed(&collider.shape),
+        None => Cow::Owned(ColliderShape::Circle {
+            radius: world.get::<WebPhysics>(handle).map_or(DEFAULT_COLLISION_RADIUS, |physics| physics.collision_radius),
+        }),
+    }
+}
+
+fn collision_layer(world: &World, handle: EntityHandle) -> u32 {
+    world.get::<WebPhysics>(handle).map_or(DEFAULT_COLLISION_LAYER, |physics| physics.collision_layer)
+}
+
+// Sleeping and static (physics-less) entities can't start a contact on their own
+fn is_awake(world: &World, handle: EntityHandle) -> bool {
+    world.get::<WebPhysics>(handle).is_some_and(|physics| !physics.sleeping)
+}
+
+fn is_trigger(world: &World, handle: EntityHandle) -> bool {
+    world.get::<WebCollider>(handle).is_some_and(|collider| collider.is_trigger)
+}
+
+fn layers_match(world: &World, id_a: EntityHandle, id_b: EntityHandle) -> bool {
+    match (world.get::<WebPhysics>(id_a), world.get::<WebPhysics>(id_b)) {
+        (Some(physics_a), Some(physics_b)) => physics_a.collides_with(physics_b),
+        // Entities without physics sit on the default layer and accept everything
+        (Some(physics), None) | (None, Some(physics)) => physics.collides_with(&WebPhysics::default()),
+        (None, None) => true,
+    }
+}
+
+// Earliest fraction of the mover's motion (previous_position -> position) at
+// which it overlaps a static `other`. A swept circle/sphere over the bounding
+// radii finds when contact becomes possible; round pairs are exact from there,
+// other shapes step forward with the SAT test.
+fn time_of_impact(shape: &ColliderShape, transform: &WebTransform,
+                  other_shape: &ColliderShape, other_transform: &WebTransform) -> Option<f32> {
+    let mut offset = transform.previous_position - other_transform.position;
+    let mut motion = transform.position - transform.previous_position;
+    if shape.is_planar() || other_shape.is_planar() {
+        offset.z = 0.0;
+        motion.z = 0.0;
+    }
+    
+    let round = |shape: &ColliderShape| matches!(shape, ColliderShape::Circle { .. } | ColliderShape::Sphere { .. });
+    let exact = round(shape) && round(other_shape);
+    
+    // Round pairs aim slightly inside contact so the discrete pass resolves them
+    let mut reach = shape.bounding_radius() + other_shape.bounding_radius();
+    if exact {
+        if offset.magnitude() < reach {
+            return None; // Already overlapping; the discrete pass has it
+        }
+        reach = (reach - CCD_CONTACT_SLOP).max(reach * 0.5);
+    }
+    
+    // |offset + motion * t| = reach
+    let a = motion.magnitude_squared();
+    let b = 2.0 * offset.dot(&motion);
+    let c = offset.magnitude_squared() - reach * reach;
+    let entry = if c <= 0.0 {
+        0.0
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if a < 1e-12 || discriminant < 0.0 {
+            return None;
+        }
+        (-b - discriminant.sqrt()) / (2.0 * a)
+    };
+    if !(0.0..=1.0).contains(&entry) {
+        return None;
+    }
+    if exact {
+        return Some(entry);
+    }
+    
+    // Steps of half the mover's size can't jump over anything
+    let step = (shape.bounding_radius() * 0.5 / a.sqrt()).max(1e-3);
+    let mut probe = *transform;
+    let mut time = entry;
+    while time <= 1.0 {
+        probe.position = transform.previous_position + (transform.position - transform.previous_position) * time;
+        if shape.contact(&probe, other_shape, other_transform).is_some() {
+            return Some(time);
+        }
+        time += step;
+    }
+    None
+}
+
+// === FIXED TIMESTEP ===
+
+// Accumulates real frame time and hands out whole simulation ticks, so physics
+// behaves the same at any frame rate. Leftover time becomes the render alpha.
+#[derive(Debug)]
+pub struct FixedTimestep {
+    pub tick_rate: f32,
+    pub max_catch_up_steps: u32,
+    accumulator: f64,
+    pub total_ticks: u64,
+    pub dropped_ticks: u64,
+}
+
+impl FixedTimestep {
+    pub fn new(tick_rate: f32, max_catch_up_steps: u32) -> Self {
+        Self {
+            tick_rate: tick_rate.clamp(10.0, 240.0),
+            max_catch_up_steps: max_catch_up_steps.max(1),
+            accumulator: 0.0,
+            total_ticks: 0,
+            dropped_ticks: 0,
+        }
+    }
+    
+    pub fn step_seconds(&self) -> f32 {
+        1.0 / self.tick_rate
+    }
+    
+    /// Adds `frame_time` seconds and returns how many ticks to simulate now
+    pub fn advance(&mut self, frame_time: f32) -> u32 {
+        let step = self.step_seconds() as f64;
+        self.accumulator += frame_time.max(0.0) as f64;
+        
+        let mut steps = 0;
+        // Small tolerance absorbs float noise from millisecond timestamps
+        while self.accumulator + 1e-6 >= step && steps < self.max_catch_up_steps {
+            self.accumulator = (self.accumulator - step).max(0.0);
+            steps += 1;
+        }
+        
+        // Too far behind: drop the backlog instead of spiralling
+        if self.accumulator >= step {
+            self.dropped_ticks += (self.accumulator / step) as u64;
+            self.accumulator %= step;
+        }
+        
+        self.total_ticks += steps as u64;
+        steps
+    }
+    
+    /// Fraction of a tick left in the accumulator, in [0, 1)
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step_seconds() as f64).min(1.0) as f32
+    }
+    
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
+    }
+}
+
+// How finely each tick's physics is resolved. Every tick runs the Physics and
+// PostPhysics stages `substeps` times with an equal share of the tick, and each
+// collision update makes `position_iterations` separation passes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhysicsQuality {
+    pub substeps: u32,
+    pub position_iterations: u32,
+}
+
+impl PhysicsQuality {
+    pub fn new(substeps: u32, position_iterations: u32) -> Self {
+        Self {
+            substeps: substeps.clamp(1, MAX_SUBSTEPS),
+            position_iterations: position_iterations.clamp(1, MAX_POSITION_ITERATIONS),
+        }
+    }
+    
+    /// Preset for a performance quality level; higher levels stack more stably
+    pub fn for_level(quality_level: u8) -> Self {
+        match quality_level {
+            0 => Self::new(1, 1),
+            1 => Self::new(2, 2),
+            _ => Self::new(4, 4),
+        }
+    }
+}
+
+// === SYSTEM SCHEDULER ===
+
+// The three simulation stages run once per fixed tick in declaration order,
+// with Physics and PostPhysics repeated once per substep (see `PhysicsQuality`);
+// RenderPrep runs once per rendered frame after all ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Stage {
+    PreUpdate,
+    Physics,
+    PostPhysics,
+    RenderPrep,
+}
+
+pub type SystemFn = Box<dyn FnMut(&mut WebGameState, f32)>;
+
+struct ScheduledSystem {
+    id: u64, // Unlike the name, never reused by a replacement
+    name: String,
+    stage: Stage,
+    before: Vec<String>,
+    after: Vec<String>,
+    enabled: bool,
+    run: Option<SystemFn>, // Lent out while the system runs
+}
+
+#[derive(Default)]
+pub struct SystemScheduler {
+    systems: Vec<ScheduledSystem>,
+    order: Vec<usize>,
+    dirty: bool,
+    next_id: u64,
+}
+
+impl SystemScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Adds a system, replacing any existing system with the same name
+    pub fn add_system(&mut self,
+                      name: &str,
+                      stage: Stage,
+                      system: impl FnMut(&mut WebGameState, f32) + 'static) -> SystemConfig<'_> {
+        self.remove_system(name);
+        self.next_id += 1;
+        self.systems.push(ScheduledSystem {
+            id: self.next_id,
+            name: name.to_string(),
+            stage,
+            before: Vec::new(),
+            after: Vec::new(),
+            enabled: true,
+            run: Some(Box::new(system)),
+        });
+        self.dirty = true;
+        
+        let index = self.systems.len() - 1;
+        SystemConfig { scheduler: self, index }
+    }
+    
+    pub fn remove_system(&mut self, name: &str) -> bool {
+        let count = self.systems.len();
+        self.systems.retain(|system| system.name != name);
+        self.dirty |= self.systems.len() != count;
+        self.systems.len() != count
+    }
+    
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        match self.systems.iter_mut().find(|system| system.name == name) {
+            Some(system) => {
+                system.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    pub fn is_enabled(&self, name: &str) -> Option<bool> {
+        self.systems.iter().find(|system| system.name == name).map(|system| system.enabled)
+    }
+    
+    /// System names in execution order, grouped by stage
+    pub fn execution_order(&self) -> Vec<String> {
+        let (order, _) = self.sorted_order();
+        order.into_iter().map(|index| self.systems[index].name.clone()).collect()
+    }
+    
+    /// Recomputes the order if systems changed; returns a warning on cycles
+    pub fn refresh_order(&mut self) -> Option<String> {
+        if !self.dirty {
+            return None;
+        }
+        
+        let (order, cycle) = self.sorted_order();
+        self.order = order;
+        self.dirty = false;
+        
+        if cycle.is_empty() {
+            None
+        } else {
+            Some(format!("System ordering cycle between [{}]; using registration order", cycle.join(", ")))
+        }
+    }
+    
+    /// Ids of the stage's enabled systems in execution order, as of the last `refresh_order`
+    fn stage_ids(&self, stage: Stage, ids: &mut Vec<u64>) {
+        ids.clear();
+        ids.extend(self.order.iter()
+            .map(|&index| &self.systems[index])
+            .filter(|system| system.stage == stage && system.enabled)
+            .map(|system| system.id));
+    }
+    
+    // None once the system was removed, replaced or disabled
+    fn lend(&mut self, id: u64) -> Option<SystemFn> {
+        self.systems.iter_mut()
+            .find(|system| system.id == id && system.enabled)
+            .and_then(|system| system.run.take())
+    }
+    
+    // A system removed or replaced while it ran is simply dropped
+    fn give_back(&mut self, id: u64, run: SystemFn) {
+        if let Some(system) = self.systems.iter_mut().find(|system| system.id == id) {
+            system.run = Some(run);
+        }
+    }
+    
+    // Stage order first, then a topological sort of before/after constraints
+    // inside each stage. Ties (and cycles) fall back to registration order.
+    fn sorted_order(&self) -> (Vec<usize>, Vec<String>) {
+        let mut order = Vec::with_capacity(self.systems.len());
+        let mut cycle = Vec::new();
+        
+        let mut stages: Vec<Stage> = self.systems.iter().map(|system| system.stage).collect();
+        stages.sort();
+        stages.dedup();
+        
+        for stage in stages {
+            let members: Vec<usize> = (0..self.systems.len())
+                .filter(|&index| self.systems[index].stage == stage)
+                .collect();
+            let position_of = |name: &str| members.iter().position(|&index| self.systems[index].name == name);
+            
+            // edges[a] lists members that must run after member a
+            let mut edges = vec![Vec::new(); members.len()];
+            let mut incoming = vec![0usize; members.len()];
+            for (local, &index) in members.iter().enumerate() {
+                let system = &self.systems[index];
+                for target in system.before.iter().filter_map(|name| position_of(name)) {
+                    edges[local].push(target);
+                    incoming[target] += 1;
+                }
+                for source in system.after.iter().filter_map(|name| position_of(name)) {
+                    edges[source].push(local);
+                    incoming[local] += 1;
+                }
+            }
+            
+            let mut placed = vec![false; members.len()];
+            for _ in 0..members.len() {
+                let next = (0..members.len()).find(|&local| !placed[local] && incoming[local] == 0);
+                let Some(local) = next else {
+                    break;
+                };
+                
+                placed[local] = true;
+                order.push(members[local]);
+                for &target in &edges[local] {
+                    incoming[target] -= 1;
+                }
+            }
+            
+            for (local, &index) in members.iter().enumerate() {
+                if !placed[local] {
+                    cycle.push(self.systems[index].name.clone());
+                    order.push(index);
+                }
+            }
+        }
+        
+        (order, cycle)
+    }
+}
+
+pub struct SystemConfig<'s> {
+    scheduler: &'s mut SystemScheduler,
+    index: usize,
+}
+
+impl<'s> SystemConfig<'s> {
+    /// Run before `name` when both are in the same stage
+    pub fn before(self, name: &str) -> Self {
+        self.scheduler.systems[self.index].before.push(name.to_string());
+        self.scheduler.dirty = true;
+        self
+    }
+    
+    /// Run after `name` when both are in the same stage
+    pub fn after(self, name: &str) -> Self {
+        self.scheduler.systems[self.index].after.push(name.to_string());
+        self.scheduler.dirty = true;
+        self
+    }
+}
+
+// === CAMERA ===
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Projection {
+    Perspective,
+    Orthographic,
+}
+
+impl Projection {
+    pub fn from_name(name: &str) -> Option<Projection> {
+        match name {
+            "perspective" => Some(Projection::Perspective),
+            "orthographic" => Some(Projection::Orthographic),
+            _ => None,
+        }
+    }
+}
+
+// Looks along +Z at `focus` from `distance` away, with canvas-style axes on
+// screen: +X right, +Y down. Follows a target through a dead zone, leading it
+// by its velocity, and can be held inside the world bounds.
+#[derive(Debug, Clone)]
+pub struct WebCamera {
+    pub focus: Vector3<f32>,     // Point looked at
+    pub distance: f32,
+    pub projection: Projection,
+    pub fov: f32,                // Vertical, in degrees, before zoom
+    pub zoom: f32,
+    pub near: f32,
+    pub far: f32,
+    pub viewport: Vector2<f32>,  // Canvas size in pixels; sets the aspect ratio
+    pub follow_speed: f32,       // Share of the gap to the target closed per second
+    pub dead_zone: Vector2<f32>, // Half-size of the box the target moves in freely
+    pub look_ahead: f32,         // Seconds of target velocity to lead by
+    pub clamp_to_bounds: bool,   // Keep the view inside the world bounds
+}
+
+impl Default for WebCamera {
+    fn default() -> Self {
+        Self {
+            focus: Vector3::new(CANVAS_WIDTH / 2.0, CANVAS_HEIGHT / 2.0, 0.0),
+            distance: 500.0,
+            projection: Projection::Perspective,
+            fov: 75.0,
+            zoom: 1.0,
+            near: 1.0,
+            far: 5000.0,
+            viewport: Vector2::new(CANVAS_WIDTH, CANVAS_HEIGHT),
+            follow_speed: 3.0,
+            dead_zone: Vector2::zeros(),
+            look_ahead: 0.0,
+            clamp_to_bounds: false,
+        }
+    }
+}
+
+impl WebCamera {
+    pub fn position(&self) -> Vector3<f32> {
+        self.focus - Vector3::z() * self.distance
+    }
+    
+    pub fn aspect(&self) -> f32 {
+        self.viewport.x.max(1.0) / self.viewport.y.max(1.0)
+    }
+    
+    /// Vertical field of view in degrees once zoom is applied
+    pub fn effective_fov(&self) -> f32 {
+        let half = (self.fov.to_radians() * 0.5).tan() / self.zoom.max(MIN_CAMERA_ZOOM);
+        (2.0 * half.atan()).to_degrees()
+    }
+    
+    /// Half the visible width and height on the plane through `focus`
+    pub fn half_extents(&self) -> Vector2<f32> {
+        match self.projection {
+            Projection::Perspective => self.slopes() * self.distance,
+            Projection::Orthographic => self.viewport * (0.5 / self.zoom.max(MIN_CAMERA_ZOOM)),
+        }
+    }
+    
+    // Perspective half-width and half-height per unit of depth
+    fn slopes(&self) -> Vector2<f32> {
+        let half_height = (self.effective_fov().to_radians() * 0.5).tan();
+        Vector2::new(half_height * self.aspect(), half_height)
+    }
+    
+    /// Whether any of the sphere lies inside the view frustum
+    pub fn sees(&self, center: Vector3<f32>, radius: f32) -> bool {
+        let depth = center.z - self.position().z;
+        if depth + radius < self.near || depth - radius > self.far {
+            return false;
+        }
+        
+        let reach = match self.projection {
+            Projection::Perspective => {
+                // Side planes lean outwards, so the sphere's reach across them grows with the slope
+                let slopes = self.slopes();
+                slopes * depth + slopes.map(|slope| radius * (1.0 + slope * slope).sqrt())
+            }
+            Projection::Orthographic => self.half_extents().add_scalar(radius),
+        };
+        (center.x - self.focus.x).abs() <= reach.x && (center.y - self.focus.y).abs() <= reach.y
+    }
+    
+    /// Box around the part of the frustum between depths `z_min` and
+    /// `z_max`, grown so it holds every sphere up to `margin` in radius that
+    /// `sees` would accept; `None` if that range is outside near..far
+    pub fn view_box(&self, z_min: f32, z_max: f32, margin: f32) -> Option<(Vector3<f32>, Vector3<f32>)> {
+        let eye_z = self.position().z;
+        let z_min = (z_min - margin).max(eye_z + self.near);
+        let z_max = (z_max + margin).min(eye_z + self.far);
+        if z_min > z_max {
+            return None;
+        }
+        
+        let half = match self.projection {
+            Projection::Perspective => {
+                let slopes = self.slopes();
+                slopes * (z_max - eye_z) + slopes.map(|slope| margin * (1.0 + slope * slope).sqrt())
+            }
+            Projection::Orthographic => self.half_extents().add_scalar(margin),
+        };
+        Some((
+            Vector3::new(self.focus.x - half.x, self.focus.y - half.y, z_min),
+            Vector3::new(self.focus.x + half.x, self.focus.y + half.y, z_max),
+        ))
+    }
+    
+    pub fn view_matrix(&self) -> Matrix4<f32> {
+        // Screen up is world -Y, matching canvas coordinates
+        Matrix4::look_at_rh(&Point3::from(self.position()), &Point3::from(self.focus), &-Vector3::y())
+    }
+    
+    pub fn projection_matrix(&self) -> Matrix4<f32> {
+        match self.projection {
+            Projection::Perspective => Matrix4::new_perspective(self.aspect(), self.effective_fov().to_radians(), self.near, self.far),
+            Projection::Orthographic => {
+                let half = self.half_extents();
+                Matrix4::new_orthographic(-half.x, half.x, -half.y, half.y, self.near, self.far)
+            }
+        }
+    }
+    
+    /// Eases the focus towards `target` (led by `velocity`) once it leaves the dead zone
+    pub fn follow(&mut self, target: Vector3<f32>, velocity: Vector3<f32>, delta_time: f32) {
+        let goal = target + velocity * self.look_ahead;
+        let mut shift = Vector3::zeros();
+        for axis in 0..2 {
+            let offset = goal[axis] - self.focus[axis];
+            let excess = offset.abs() - self.dead_zone[axis];
+            if excess > 0.0 {
+                shift[axis] = excess.copysign(offset);
+            }
+        }
+        self.focus += shift * (self.follow_speed * delta_time).min(1.0);
+    }
+    
+    /// Moves the focus so the view stays inside `bounds`; a view larger than
+    /// the world is centered on it
+    pub fn clamp_to(&mut self, bounds: &WorldBounds) {
+        let half = self.half_extents();
+        for axis in 0..2 {
+            let (min, max) = (bounds.min[axis], bounds.max[axis]);
+            self.focus[axis] = if max - min > half[axis] * 2.0 {
+                self.focus[axis].clamp(min + half[axis], max - half[axis])
+            } else {
+                (min + max) * 0.5
+            };
+        }
+    }
+}
+
+// === VIEW CULLING ===
+
+// Render prep leaves out entities and particles the camera can't see, so they
+// never reach the render buffers. The visible set is kept, so a pass only
+// tests entities near the view; one spawned after the pass waits for the next.
+pub struct ViewCuller {
+    pub enabled: bool,
+    tested: bool,                        // Whether the last pass culled at all
+    visible: HashSet<EntityHandle>,      // Reused each pass for its capacity
+    candidates: Vec<EntityHandle>,       // Scratch, likewise
+    visible_entities: usize,
+    culled_entities: usize,
+    culled_particles: usize,
+}
+
+impl ViewCuller {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            tested: false,
+            visible: HashSet::new(),
+            candidates: Vec::new(),
+            visible_entities: 0,
+            culled_entities: 0,
+            culled_particles: 0,
+        }
+    }
+    
+    pub fn is_culled(&self, handle: EntityHandle) -> bool {
+        self.tested && !self.visible.contains(&handle)
+    }
+    
+    /// Visible renderers the last pass kept
+    pub fn visible_entities(&self) -> usize {
+        self.visible_entities
+    }
+    
+    pub fn culled_entities(&self) -> usize {
+        self.culled_entities
+    }
+    
+    pub fn culled_particles(&self) -> usize {
+        self.culled_particles
+    }
+}
+
+impl Default for ViewCuller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Bounding radius of what gets drawn: the collider's, under the entity's
+// largest scale (including its parents' when `model` is given)
+fn render_radius(world: &World, handle: EntityHandle, transform: &WebTransform, model: Option<&Matrix4<f32>>) -> f32 {
+    let radius = match world.get::<WebCollider>(handle) {
+        Some(collider) => collider.shape.bounding_radius(),
+        None => world.get::<WebPhysics>(handle).map_or(DEFAULT_COLLISION_RADIUS, |physics| physics.collision_radius),
+    };
+    let scale = match model {
+        Some(model) => (0..3).map(|column| model.fixed_view::<3, 1>(0, column).magnitude()).fold(0.0, f32::max),
+        None => transform.scale.abs().max(),
+    };
+    radius * scale
+}
+
+// === RENDER BUFFERS ===
+
+// Engine-owned render data rewritten in place each frame, so JS can read it
+// through `Float32Array` views of wasm memory instead of fresh copies. Clearing
+// keeps the capacity; a pointer only moves when a buffer has to grow (or wasm
+// memory itself grows), so JS should rebuild its views when one changes.
+#[derive(Debug, Default)]
+pub struct RenderBuffers {
+    entities: Vec<f32>,            // Same layout as `get_entity_render_data`
+    previous_transforms: Vec<f32>, // Same layout as `get_entity_previous_transform_data`
+    batches: Vec<u32>,             // Same layout as `get_render_batches`
+    particles: Vec<f32>,
+    camera: Vec<f32>,
+    draw_order: Vec<DrawKey>,      // Scratch, kept for its capacity
+}
+
+// Entities are drawn by layer, then blend mode, then texture; the handle keeps
+// equal keys in a stable order
+type DrawKey = (u8, BlendMode, u32, EntityHandle);
+
+impl RenderBuffers {
+    pub fn entities(&self) -> &[f32] {
+        &self.entities
+    }
+    
+    pub fn previous_transforms(&self) -> &[f32] {
+        &self.previous_transforms
+    }
+    
+    pub fn batches(&self) -> &[u32] {
+        &self.batches
+    }
+    
+    pub fn particles(&self) -> &[f32] {
+        &self.particles
+    }
+    
+    pub fn camera(&self) -> &[f32] {
+        &self.camera
+    }
+}
+
+// === MAIN WEB GAME STATE ===
+
+pub struct WebGameState {
+    // Platform services
+    platform: Platform,
+    
+    // Entity management
+    world: World,
+    player: Option<EntityHandle>,
+    
+    // Systems
+    particle_system: WebParticleSystem,
+    collision_system: WebCollisionSystem,
+    constraint_solver: ConstraintSolver,
+    sleep_tracker: SleepTracker,
+    view_culler: ViewCuller,
+    render_buffers: RenderBuffers,
+    performance: WebPerformanceMonitor,
+    input: WebInputSystem,
+    timestep: FixedTimestep,
+    scheduler: SystemScheduler,
+    stage_ids: Vec<u64>, // Reused by `run_stage`
+    events: EventQueue,
+    
+    // Browser capabilities
+    capabilities: BrowserCapabilities,
+    
+    // Camera
+    camera: WebCamera,
+    
+    // Physics
+    gravity: Vector3<f32>,
+    physics_enabled: bool,
+    world_bounds: WorldBounds,
+    materials: PhysicsMaterials,
+    physics_preset: PhysicsQuality,          // Of the last quality level set by hand, never the adaptive one
+    physics_quality: Option<PhysicsQuality>, // Overrides the preset
+    substep: u32,                            // Index within the current tick
+    
+    // Game state
+    score: i32,
+    level: i32,
+    time_scale: f32,
+    paused: bool,
+    debug_mode: bool,
+}
+
+impl WebGameState {
+    pub fn new(platform: Platform) -> Self {
+        platform_log!(platform.logger, "Creating Web Game State for v1.12 Enhanced Edition");
+        
+        let capabilities = BrowserCapabilities::default();
+        let performance = WebPerformanceMonitor::new(platform.clock.now_ms());
+        let physics_preset = PhysicsQuality::for_level(performance.quality_level);
+        
+        let mut game_state = Self {
+            platform,
+            
+            world: World::with_capacity(MAX_ENTITIES),
+            player: None,
+            
+            particle_system: WebParticleSystem::new(),
+            collision_system: WebCollisionSystem::new(),
+            constraint_solver: ConstraintSolver::new(),
+            sleep_tracker: SleepTracker::new(),
+            view_culler: ViewCuller::new(),
+            render_buffers: RenderBuffers::default(),
+            performance,
+            input: WebInputSystem::new(),
+            timestep: FixedTimestep::new(DEFAULT_TICK_RATE, MAX_CATCH_UP_STEPS),
+            scheduler: SystemScheduler::new(),
+            stage_ids: Vec::new(),
+            events: EventQueue::new(),
+            
+            capabilities,
+            
+            camera: WebCamera::default(),
+            
+            gravity: Vector3::new(0.0, -490.0, 0.0), // Reduced for web
+            physics_enabled: true,
+            world_bounds: WorldBounds::default(),
+            materials: PhysicsMaterials::new(),
+            physics_preset,
+            physics_quality: None,
+            substep: 0,
+            
+            score: 0,
+            level: 1,
+            time_scale: 1.0,
+            paused: false,
+            debug_mode: false,
+        };
+        
+        game_state.register_core_systems();
+        game_state.initialize_scene();
+        game_state
+    }
+    
+    /// Game state with deterministic, silent platform services for native runs
+    pub fn headless(seed: u32) -> Self {
+        Self::new(Platform::headless(seed))
+    }
+    
+    fn initialize_scene(&mut self) {
+        // Create player entity
+        let player = self.world.spawn()
+            .with(WebEntityInfo {
+                name: "Player".to_string(),
+                tag: "Player".to_string(),
+            })
+            .with(WebTransform::at(Vector3::new(CANVAS_WIDTH / 2.0, CANVAS_HEIGHT / 2.0, 0.0)))
+            .with(WebPhysics {
+                mass: 1.0,
+                use_gravity: false, // Top-down view
+                drag: 5.0,
+                can_sleep: false, // Always listening for input
+                ..Default::default()
+            })
+            .with(WebRenderer {
+                color: [0.3, 0.8, 1.0, 1.0], // Cyan
+                ..Default::default()
+            })
+            .with(WebHealth::new(100.0))
+            .build();
+        self.player = Some(player);
+        self.events.push(EngineEvent::EntitySpawned { entity: player });
+        
+        // Generate environment entities (reduced for web)
+        self.generate_environment(50);
+        
+        platform_log!(self.platform.logger, "Scene initialized with {} entities", self.world.len());
+    }
+    
+    fn generate_environment(&mut self, count: usize) {
+        let rng = &mut self.platform.rng;
+        
+        for i in 0..count {
+            let position = Vector3::new(
+                rng.next_f64() as f32 * CANVAS_WIDTH,
+                rng.next_f64() as f32 * CANVAS_HEIGHT,
+                0.0,
+            );
+            
+            let physics = WebPhysics {
+                mass: 0.5 + rng.next_f64() as f32 * 2.0,
+                material: PhysicsMaterial {
+                    restitution: 0.3 + rng.next_f64() as f32 * 0.7,
+                    ..Default::default()
+                },
+                drag: 0.1 + rng.next_f64() as f32 * 0.8,
+                angular_velocity: 45.0, // Classic idle spin, winding down so resting bodies can sleep
+                angular_drag: ENVIRONMENT_ANGULAR_DRAG,
+                ..Default::default()
+            };
+            
+            let renderer = WebRenderer {
+                color: [
+                    0.5 + rng.next_f64() as f32 * 0.5,
+                    0.5 + rng.next_f64() as f32 * 0.5,
+                    0.5 + rng.next_f64() as f32 * 0.5,
+                    1.0,
+                ],
+                ..Default::default()
+            };
+            
+            let entity = self.world.spawn()
+                .with(WebEntityInfo {
+                    name: format!("Environment_{}", i),
+                    tag: "Environment".to_string(),
+                })
+                .with(WebTransform::at(position))
+                .with(physics)
+                .with(renderer)
+                .build();
+            self.events.push(EngineEvent::EntitySpawned { entity });
+        }
+    }
+    
+    /// Spawns an entity with just a name and transform; add more via `world_mut`
+    pub fn create_entity(&mut self, name: String, position: Vector3<f32>) -> EntityHandle {
+        let entity = self.world.spawn()
+            .with(WebEntityInfo::new(name))
+            .with(WebTransform::at(position))
+            .build();
+        self.events.push(EngineEvent::EntitySpawned { entity });
+        entity
+    }
+    
+    /// Removes the entity now; its handle (and any copies JS holds) becomes invalid
+    pub fn remove_entity(&mut self, handle: EntityHandle) -> bool {
+        if !self.world.despawn(handle) {
+            return false;
+        }
+        
+        if self.player == Some(handle) {
+            self.player = None;
+        }
+        true
+    }
+    
+    pub fn update(&mut self, current_time: f64) {
+        if self.paused {
+            return;
+        }
+        
+        let quality_level = self.performance.quality_level;
+        let delta_time = self.performance.update(current_time, self.platform.logger.as_ref()) * self.time_scale;
+        self.report_quality_change(quality_level);
+        
+        // Simulation runs in whole fixed ticks; render prep uses the frame delta
+        let steps = self.timestep.advance(self.performance.frame_delta * self.time_scale);
+        let fixed_delta = self.timestep.step_seconds();
+        let physics_quality = self.physics_quality();
+        self.collision_system.position_iterations = physics_quality.position_iterations;
+        let substep_delta = fixed_delta / physics_quality.substeps as f32;
+        for _ in 0..steps {
+            self.events.begin_tick();
+            self.run_stage(Stage::PreUpdate, fixed_delta);
+            for substep in 0..physics_quality.substeps {
+                self.substep = substep;
+                self.run_stage(Stage::Physics, substep_delta);
+                self.run_stage(Stage::PostPhysics, substep_delta);
+            }
+            self.substep = 0;
+        }
+        
+        self.run_stage(Stage::RenderPrep, delta_time);
+        
+        // Debug output
+        if self.debug_mode && self.performance.fps_counter % 60 == 0 {
+            platform_log!(self.platform.logger, "FPS: {:.1}, Entities: {}, Particles: {}, Quality: {}", 
+                        self.performance.current_fps, 
+                        self.world.len(), 
+                        self.particle_system.particle_count(),
+                        self.performance.quality_level);
+        }
+    }
+    
+    /// Advances the simulation by `delta_time` seconds without a real clock
+    pub fn step(&mut self, delta_time: f32) {
+        let current_time = self.performance.last_frame_time + delta_time as f64 * 1000.0;
+        self.update(current_time);
+    }
+    
+    // Systems need the whole state, so only each system's closure leaves the
+    // scheduler while it runs. Systems can still inspect the scheduler and add,
+    // remove or toggle systems; additions first run in the next stage pass.
+    fn run_stage(&mut self, stage: Stage, delta_time: f32) {
+        if let Some(warning) = self.scheduler.refresh_order() {
+            platform_log!(self.platform.logger, "{}", warning);
+        }
+        
+        let mut ids = std::mem::take(&mut self.stage_ids);
+        self.scheduler.stage_ids(stage, &mut ids);
+        for &id in &ids {
+            if let Some(mut run) = self.scheduler.lend(id) {
+                run(self, delta_time);
+                self.scheduler.give_back(id, run);
+            }
+        }
+        self.stage_ids = ids;
+    }
+    
+    // === SYSTEMS ===
+    
+    /// Registers a system; chain `.before(..)`/`.after(..)` to order it within its stage
+    pub fn add_system(&mut self,
+                      name: &str,
+                      stage: Stage,
+                      system: impl FnMut(&mut WebGameState, f32) + 'static) -> SystemConfig<'_> {
+        self.scheduler.add_system(name, stage, system)
+    }
+    
+    pub fn remove_system(&mut self, name: &str) -> bool {
+        self.scheduler.remove_system(name)
+    }
+    
+    pub fn set_system_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        self.scheduler.set_enabled(name, enabled)
+    }
+    
+    pub fn scheduler(&self) -> &SystemScheduler {
+        &self.scheduler
+    }
+    
+    fn register_core_systems(&mut self) {
+        self.add_system("snapshot_transforms", Stage::PreUpdate, Self::snapshot_transforms_system);
+        self.add_system("player_input", Stage::PreUpdate, Self::player_input_system)
+            .after("snapshot_transforms");
+        
+        // Forces first so they are integrated in the same tick
+        self.add_system("gravity", Stage::Physics, Self::gravity_system)
+            .before("integrate");
+        self.add_system("integrate", Stage::Physics, Self::integrate_system);
+        self.add_system("constraints", Stage::Physics, Self::constraints_system)
+            .after("integrate");
+        self.add_system("bounds", Stage::Physics, Self::bounds_system)
+            .after("constraints");
+        self.add_system("health", Stage::Physics, Self::health_system);
+        
+        self.add_system("collision", Stage::PostPhysics, Self::resolve_collisions_system);
+        self.add_system("sleep", Stage::PostPhysics, Self::sleep_system)
+            .after("collision");
+        self.add_system("cleanup", Stage::PostPhysics, Self::cleanup_system)
+            .after("collision");
+        
+        self.add_system("particles", Stage::RenderPrep, Self::update_particles_system);
+        self.add_system("camera", Stage::RenderPrep, Self::camera_system);
+        self.add_system("culling", Stage::RenderPrep, Self::culling_system)
+            .after("camera")
+            .after("particles");
+    }
+    
+    fn snapshot_transforms_system(&mut self, _delta_time: f32) {
+        // Snapshot transforms so rendering can interpolate towards this tick
+        for (_, transform) in self.world.query_mut::<WebTransform>() {
+            transform.previous_position = transform.position;
+            transform.previous_rotation = transform.rotation;
+        }
+    }
+    
+    fn player_input_system(&mut self, _delta_time: f32) {
+        let movement = self.input.get_movement_input();
+        if movement.magnitude() > 0.1 {
+            if let Some(player) = self.player.and_then(|handle| self.world.get_mut::<WebTransform>(handle)) {
+                let move_speed = 300.0;
+                player.acceleration += movement * move_speed;
+            }
+        }
+    }
+    
+    fn gravity_system(&mut self, _delta_time: f32) {
+        // Accelerations last the whole tick, so gravity is only added once
+        if self.performance.quality_level < 1 || !self.physics_enabled || self.substep > 0 {
+            return;
+        }
+        
+        let gravity = self.gravity;
+        self.world.for_each2_mut::<WebPhysics, WebTransform>(|_, physics, transform| {
+            if !physics.is_kinematic && !physics.sleeping && physics.use_gravity {
+                transform.acceleration += gravity;
+            }
+        });
+    }
+    
+    fn integrate_system(&mut self, delta_time: f32) {
+        // Turn torques into angular acceleration first; that needs each collider
+        let angular_accelerations: Vec<(EntityHandle, f32)> = self.world.query::<WebPhysics>()
+            .filter(|(_, physics)| physics.torque != 0.0)
+            .map(|(handle, physics)| (handle, physics.torque * physics.inverse_inertia(&collider_shape(&self.world, handle))))
+            .collect();
+        for (handle, angular_acceleration) in angular_accelerations {
+            if let Some(physics) = self.world.get_mut::<WebPhysics>(handle) {
+                physics.angular_velocity += angular_acceleration.to_degrees() * delta_time;
+            }
+        }
+        
+        let last_substep = self.is_last_substep();
+        self.world.for_each2_mut::<WebPhysics, WebTransform>(|_, physics, transform| {
+            // Anything pushing or moving a sleeping body wakes it
+            let pushed = physics.torque != 0.0 || transform.acceleration != Vector3::zeros() || transform.velocity != Vector3::zeros();
+            if physics.sleeping && pushed {
+                physics.wake();
+            }
+            
+            if !physics.is_kinematic && !physics.sleeping {
+                // Apply drag
+                transform.velocity *= 1.0 - (physics.drag * delta_time);
+                physics.angular_velocity *= 1.0 - (physics.angular_drag * delta_time);
+                
+                transform.velocity += transform.acceleration * delta_time;
+                transform.position += transform.velocity * delta_time;
+                transform.rotation += physics.angular_velocity * delta_time;
+                
+                // Keep rotation in [0, 360) without breaking interpolation
+                let turns = (transform.rotation / 360.0).floor() * 360.0;
+                transform.rotation -= turns;
+                transform.previous_rotation -= turns;
+            }
+            
+            // Reset accumulators once the tick's last substep has used them
+            if last_substep {
+                transform.acceleration = Vector3::zeros();
+                physics.torque = 0.0;
+            }
+        });
+    }
+    
+    fn is_last_substep(&self) -> bool {
+        self.substep + 1 >= self.physics_quality().substeps
+    }
+    
+    fn constraints_system(&mut self, delta_time: f32) {
+        self.constraint_solver.solve(&mut self.world, delta_time);
+    }
+    
+    fn sleep_system(&mut self, delta_time: f32) {
+        self.sleep_tracker.update(&mut self.world, self.collision_system.contacts(), &self.constraint_solver, delta_time);
+    }
+    
+    fn bounds_system(&mut self, _delta_time: f32) {
+        let handles: Vec<EntityHandle> = self.world.query::<WebTransform>().map(|(handle, _)| handle).collect();
+        let mut escaped = Vec::new();
+        
+        for handle in handles {
+            let mode = self.world.get::<WebBoundary>(handle).map_or(self.world_bounds.mode, |boundary| boundary.mode);
+            let radius = collider_shape(&self.world, handle).bounding_radius();
+            let restitution = self.world.get::<WebPhysics>(handle).map_or(1.0, |physics| physics.material.restitution);
+            let Some(transform) = self.world.get_mut::<WebTransform>(handle) else {
+                continue;
+            };
+            
+            if !self.world_bounds.apply(mode, transform, radius, restitution) {
+                escaped.push(handle);
+            }
+        }
+        
+        for handle in escaped {
+            if self.remove_entity(handle) {
+                self.events.push(EngineEvent::EntityDied { entity: handle });
+            }
+        }
+    }
+    
+    fn health_system(&mut self, delta_time: f32) {
+        for (_, health) in self.world.query_mut::<WebHealth>() {
+            if health.regeneration != 0.0 {
+                health.current += health.regeneration * delta_time;
+                health.current = health.current.min(health.max).max(0.0);
+            }
+        }
+    }
+    
+    fn resolve_collisions_system(&mut self, _delta_time: f32) {
+        if self.performance.quality_level < 1 {
+            return;
+        }
+        
+        let report = self.is_last_substep();
+        let score_increment = self.collision_system.update(&mut self.world, &mut self.events, report);
+        self.score += score_increment;
+    }
+    
+    fn cleanup_system(&mut self, _delta_time: f32) {
+        let dead: Vec<EntityHandle> = self.world.query::<WebHealth>()
+            .filter(|(_, health)| health.current <= 0.0)
+            .map(|(handle, _)| handle)
+            .collect();
+        for handle in dead {
+            if self.remove_entity(handle) {
+                self.events.push(EngineEvent::EntityDied { entity: handle });
+            }
+        }
+    }
+    
+    fn update_particles_system(&mut self, delta_time: f32) {
+        if self.performance.quality_level >= 2 {
+            self.particle_system.update(delta_time);
+        }
+    }
+    
+    fn camera_system(&mut self, delta_time: f32) {
+        // Update camera to follow player
+        if let Some(player) = self.player.and_then(|handle| self.world.get::<WebTransform>(handle)) {
+            self.camera.follow(player.position, player.velocity, delta_time);
+        }
+        
+        if self.camera.clamp_to_bounds {
+            self.camera.clamp_to(&self.world_bounds);
+        }
+    }
+    
+    fn culling_system(&mut self, _delta_time: f32) {
+        let mut culler = std::mem::take(&mut self.view_culler);
+        culler.visible.clear();
+        culler.tested = culler.enabled;
+        let shown = self.world.query::<WebRenderer>()
+            .filter(|(_, renderer)| renderer.visible)
+            .count();
+        if !culler.enabled {
+            culler.visible_entities = shown;
+            culler.culled_entities = 0;
+            culler.culled_particles = self.particle_system.cull(|_, _| true);
+            self.view_culler = culler;
+            return;
+        }
+        
+        // Only entities the grid finds near the view get the exact test; the
+        // box is grown by a cell, and an unscaled entity is never drawn wider
+        // than that. Scaled-up entities may be, and parented ones sit in the
+        // grid at their local position, so those are tested wherever they are
+        self.collision_system.refresh_grid(&self.world);
+        let grid = self.collision_system.spatial_grid();
+        let margin = grid.cell_size();
+        culler.candidates.clear();
+        if let Some((min, max)) = grid.occupied_cells()
+            .and_then(|(low, high)| self.camera.view_box(low.2 as f32 * margin, (high.2 + 1) as f32 * margin, margin)) {
+            grid.query_box_into(min, max, &mut culler.candidates);
+        }
+        culler.candidates.extend(self.world.query::<WebTransform>()
+            .filter(|(_, transform)| transform.scale.abs().max() > 1.0)
+            .map(|(handle, _)| handle));
+        culler.candidates.extend(self.world.query::<WebParent>().map(|(handle, _)| handle));
+        
+        for &handle in &culler.candidates {
+            let (Some(renderer), Some(transform)) = (self.world.get::<WebRenderer>(handle), self.world.get::<WebTransform>(handle)) else {
+                continue;
+            };
+            if !renderer.visible || culler.visible.contains(&handle) {
+                continue;
+            }
+            
+            let in_view = if self.world.has::<WebParent>(handle) {
+                // Test where the hierarchy puts it
+                let model = self.model_matrix(handle).unwrap_or_else(Matrix4::identity);
+                let center = model.fixed_view::<3, 1>(0, 3).into_owned();
+                self.camera.sees(center, render_radius(&self.world, handle, transform, Some(&model)) + CULL_MARGIN)
+            } else {
+                self.camera.sees(transform.position, render_radius(&self.world, handle, transform, None) + CULL_MARGIN)
+            };
+            if in_view {
+                culler.visible.insert(handle);
+            }
+        }
+        
+        let camera = &self.camera;
+        culler.culled_particles = self.particle_system.cull(|position, size| camera.sees(position, size));
+        culler.visible_entities = culler.visible.len();
+        culler.culled_entities = shown - culler.visible_entities;
+        self.view_culler = culler;
+    }
+    
+    // === INPUT ===
+    
+    pub fn handle_key_event(&mut self, key_code: u32, pressed: bool) {
+        self.input.set_key(key_code, pressed);
+        
+        if pressed {
+            match key_code {
+                32 => { // Space
+                    self.set_paused(!self.paused);
+                }
+                192 => { // Tilde (~)
+                    self.debug_mode = !self.debug_mode;
+                    platform_log!(self.platform.logger, "Debug mode {}", if self.debug_mode { "enabled" } else { "disabled" });
+                }
+                _ => {}
+            }
+        }
+    }
+    
+    pub fn handle_mouse_event(&mut self, x: f32, y: f32, delta_x: f32, delta_y: f32) {
+        self.input.set_mouse(x, y, delta_x, delta_y);
+    }
+    
+    pub fn handle_touch_event(&mut self, touches: Vec<f32>) {
+        let touch_pairs: Vec<(f32, f32)> = touches
+            .chunks_exact(2)
+            .map(|chunk| (chunk[0], chunk[1]))
+            .collect();
+        self.input.set_touch(touch_pairs);
+    }
+    
+    // === QUERIES ===
+    
+    pub fn get_score(&self) -> i32 {
+        self.score
+    }
+    
+    pub fn get_entity_count(&self) -> usize {
+        self.world.len()
+    }
+    
+    pub fn get_particle_count(&self) -> usize {
+        self.particle_system.particle_count()
+    }
+    
+    pub fn get_fps(&self) -> f32 {
+        self.performance.current_fps
+    }
+    
+    pub fn get_frame_time(&self) -> f32 {
+        self.performance.average_frame_time_ms
+    }
+    
+    pub fn get_quality_level(&self) -> u8 {
+        self.performance.quality_level
+    }
+    
+    /// The override if one is set, otherwise the preset of the quality level
+    /// last chosen with `set_quality_level`. Adaptive quality never changes it,
+    /// so the simulation (and any replay of it) doesn't depend on frame times
+    pub fn physics_quality(&self) -> PhysicsQuality {
+        self.physics_quality.unwrap_or(self.physics_preset)
+    }
+    
+    pub fn performance(&self) -> &WebPerformanceMonitor {
+        &self.performance
+    }
+    
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+    
+    pub fn events(&self) -> &EventQueue {
+        &self.events
+    }
+    
+    /// Hands over every queued event; call once per frame after `update`
+    pub fn drain_events(&mut self) -> Vec<EngineEvent> {
+        self.events.drain()
+    }
+    
+    pub fn get_seed(&self) -> u32 {
+        self.platform.rng.seed()
+    }
+    
+    /// Blend factor between previous and current transforms for this frame
+    pub fn get_interpolation_alpha(&self) -> f32 {
+        self.timestep.alpha()
+    }
+    
+    pub fn timestep(&self) -> &FixedTimestep {
+        &self.timestep
+    }
+    
+    pub fn set_tick_rate(&mut self, tick_rate: f32) {
+        self.timestep.tick_rate = tick_rate.clamp(10.0, 240.0);
+        self.timestep.reset();
+        platform_log!(self.platform.logger, "Tick rate set to {} Hz", self.timestep.tick_rate);
+    }
+    
+    pub fn set_max_catch_up_steps(&mut self, steps: u32) {
+        self.timestep.max_catch_up_steps = steps.max(1);
+    }
+    
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+    
+    /// Direct component access, e.g. to attach user-defined components
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+    
+    pub fn is_entity_valid(&self, handle: EntityHandle) -> bool {
+        self.world.is_alive(handle)
+    }
+    
+    pub fn player_handle(&self) -> Option<EntityHandle> {
+        self.player
+    }
+    
+    /// Points input and camera at another entity; fails for stale handles
+    pub fn set_player(&mut self, handle: EntityHandle) -> bool {
+        if !self.is_entity_valid(handle) {
+            return false;
+        }
+        
+        self.player = Some(handle);
+        true
+    }
+    
+    pub fn set_entity_physics(&mut self, handle: EntityHandle, mass: f32, bounciness: f32, is_kinematic: bool) -> bool {
+        if !self.world.is_alive(handle) {
+            return false;
+        }
+        
+        if !self.world.has::<WebPhysics>(handle) {
+            self.world.insert(handle, WebPhysics::default());
+        }
+        if let Some(physics) = self.world.get_mut::<WebPhysics>(handle) {
+            physics.mass = mass.max(0.0);
+            physics.material.restitution = bounciness.clamp(0.0, 1.0);
+            physics.is_kinematic = is_kinematic;
+            physics.wake();
+        }
+        true
+    }
+    
+    /// Gives the entity a named material (adding physics if missing) and sets
+    /// its mass from the material's density; false for unknown names
+    pub fn set_entity_material(&mut self, handle: EntityHandle, name: &str) -> bool {
+        let Some(material) = self.materials.get(name) else {
+            return false;
+        };
+        if !self.world.is_alive(handle) {
+            return false;
+        }
+        
+        if !self.world.has::<WebPhysics>(handle) {
+            self.world.insert(handle, WebPhysics::default());
+        }
+        let mass = material.mass_for(&collider_shape(&self.world, handle));
+        if let Some(physics) = self.world.get_mut::<WebPhysics>(handle) {
+            physics.material = material;
+            physics.mass = mass;
+            physics.wake();
+        }
+        true
+    }
+    
+    /// Gives the named material to every entity whose name or tag is
+    /// `name_or_tag`, the way scene files assign them. Returns how many got it
+    pub fn assign_material(&mut self, name_or_tag: &str, material: &str) -> usize {
+        if self.materials.get(material).is_none() {
+            return 0;
+        }
+        
+        let handles: Vec<EntityHandle> = self.world.query::<WebEntityInfo>()
+            .filter(|(_, info)| info.name == name_or_tag || info.tag == name_or_tag)
+            .map(|(handle, _)| handle)
+            .collect();
+        handles.into_iter()
+            .filter(|&handle| self.set_entity_material(handle, material))
+            .count()
+    }
+    
+    pub fn materials(&self) -> &PhysicsMaterials {
+        &self.materials
+    }
+    
+    pub fn materials_mut(&mut self) -> &mut PhysicsMaterials {
+        &mut self.materials
+    }
+    
+    /// Sets the layer bits the entity is on and the layer bits it collides with
+    pub fn set_collision_layers(&mut self, handle: EntityHandle, layer: u32, mask: u32) -> bool {
+        match self.world.get_mut::<WebPhysics>(handle) {
+            Some(physics) => {
+                physics.collision_layer = layer;
+                physics.collision_mask = mask;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Opts the entity in or out of swept (continuous) collision detection
+    pub fn set_continuous_collision(&mut self, handle: EntityHandle, enabled: bool) -> bool {
+        match self.world.get_mut::<WebPhysics>(handle) {
+            Some(physics) => {
+                physics.continuous_collision = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Adds torque about Z, applied at the next integration step
+    pub fn apply_torque(&mut self, handle: EntityHandle, torque: f32) -> bool {
+        match self.world.get_mut::<WebPhysics>(handle) {
+            Some(physics) => {
+                physics.torque += torque;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    pub fn set_angular_velocity(&mut self, handle: EntityHandle, degrees_per_second: f32) -> bool {
+        match self.world.get_mut::<WebPhysics>(handle) {
+            Some(physics) => {
+                physics.angular_velocity = degrees_per_second;
+                physics.wake();
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// A non-positive `inertia` goes back to deriving it from mass and collider
+    pub fn set_angular_physics(&mut self, handle: EntityHandle, angular_drag: f32, inertia: f32) -> bool {
+        match self.world.get_mut::<WebPhysics>(handle) {
+            Some(physics) => {
+                physics.angular_drag = angular_drag.max(0.0);
+                physics.inertia = (inertia > 0.0).then_some(inertia);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Wakes the entity's island; needed after moving a sleeping body directly
+    pub fn wake_entity(&mut self, handle: EntityHandle) -> bool {
+        match self.world.get_mut::<WebPhysics>(handle) {
+            Some(physics) => {
+                physics.wake();
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Lets the entity fall asleep when at rest, or keeps it (and its island) awake
+    pub fn set_can_sleep(&mut self, handle: EntityHandle, can_sleep: bool) -> bool {
+        match self.world.get_mut::<WebPhysics>(handle) {
+            Some(physics) => {
+                physics.can_sleep = can_sleep;
+                physics.wake();
+                true
+            }
+            None => false,
+        }
+    }
+    
+    pub fn is_sleeping(&self, handle: EntityHandle) -> bool {
+        self.world.get::<WebPhysics>(handle).is_some_and(|physics| physics.sleeping)
+    }
+    
+    /// Turning sleeping off wakes every island at the next tick
+    pub fn set_sleeping_enabled(&mut self, enabled: bool) {
+        self.sleep_tracker.enabled = enabled;
+    }
+    
+    pub fn sleep_tracker(&self) -> &SleepTracker {
+        &self.sleep_tracker
+    }
+    
+    /// Takes effect at the next frame's render prep
+    pub fn set_view_culling(&mut self, enabled: bool) {
+        self.view_culler.enabled = enabled;
+    }
+    
+    pub fn view_culler(&self) -> &ViewCuller {
+        &self.view_culler
+    }
+    
+    /// Id of the new constraint, or `None` if one of its entities is gone
+    pub fn add_constraint(&mut self, constraint: Constraint) -> Option<u32> {
+        let (a, b) = constraint.entities();
+        if !self.world.is_alive(a) || !b.is_none_or(|b| self.world.is_alive(b)) {
+            return None;
+        }
+        
+        Some(self.constraint_solver.add(constraint))
+    }
+    
+    pub fn remove_constraint(&mut self, id: u32) -> bool {
+        self.constraint_solver.remove(id)
+    }
+    
+    pub fn constraint_solver(&self) -> &ConstraintSolver {
+        &self.constraint_solver
+    }
+    
+    /// Replaces the entity's collision shape; fails for stale handles and
+    /// shapes that aren't `is_valid`
+    pub fn set_collider(&mut self, handle: EntityHandle, shape: ColliderShape) -> bool {
+        if !shape.is_valid() {
+            return false;
+        }
+        
+        let is_trigger = self.world.get::<WebCollider>(handle).is_some_and(|collider| collider.is_trigger);
+        self.world.is_alive(handle) && self.world.insert(handle, WebCollider { shape, is_trigger })
+    }
+    
+    /// Turns the entity's collider into a sensor (or back); keeps its current shape
+    pub fn set_trigger(&mut self, handle: EntityHandle, is_trigger: bool) -> bool {
+        if !self.world.is_alive(handle) {
+            return false;
+        }
+        
+        if let Some(collider) = self.world.get_mut::<WebCollider>(handle) {
+            collider.is_trigger = is_trigger;
+        } else {
+            let shape = collider_shape(&self.world, handle).into_owned();
+            self.world.insert(handle, WebCollider { shape, is_trigger });
+        }
+        true
+    }
+    
+    /// Closest solid collider along the ray whose layer is in `mask`; triggers
+    /// are ignored so sensors don't block line of sight
+    pub fn raycast(&mut self, origin: Vector3<f32>, direction: Vector3<f32>, max_distance: f32, mask: u32) -> Option<RaycastHit> {
+        let length = direction.magnitude();
+        if length < 1e-6 || max_distance.is_nan() || max_distance < 0.0 {
+            return None;
+        }
+        let direction = direction / length;
+        
+        self.collision_system.refresh_grid(&self.world);
+        let grid = self.collision_system.spatial_grid();
+        let mut tested = HashSet::new();
+        let mut closest: Option<RaycastHit> = None;
+        
+        for (entry, cell) in grid.cells_along_ray(origin, direction, max_distance) {
+            // Every hit lies in a visited cell, and its entity sits in that
+            // cell's neighborhood, so nothing past the closest hit can beat it
+            if closest.is_some_and(|hit| entry > hit.distance) {
+                break;
+            }
+            
+            for handle in grid.neighbors(cell) {
+                if !tested.insert(handle) || is_trigger(&self.world, handle) || collision_layer(&self.world, handle) & mask == 0 {
+                    continue;
+                }
+                let Some(transform) = self.world.get::<WebTransform>(handle) else {
+                    continue;
+                };
+                
+                let Some((distance, normal)) = collider_shape(&self.world, handle).raycast(transform, origin, direction, max_distance) else {
+                    continue;
+                };
+                // Ties go to the lower handle so results are deterministic
+                if closest.is_none_or(|hit| (distance, handle) < (hit.distance, hit.entity)) {
+                    closest = Some(RaycastHit { entity: handle, point: origin + direction * distance, normal, distance });
+                }
+            }
+        }
+        closest
+    }
+    
+    /// Solid and trigger colliders touching a circle in the XY plane, in handle order
+    pub fn overlap_circle(&mut self, center: Vector3<f32>, radius: f32) -> Vec<EntityHandle> {
+        let radius = radius.max(0.0);
+        let circle = ColliderShape::Circle { radius };
+        let circle_transform = WebTransform::at(center);
+        let reach = Vector3::new(radius, radius, radius);
+        
+        self.collision_system.refresh_grid(&self.world);
+        let mut handles: Vec<EntityHandle> = self.collision_system.spatial_grid()
+            .query_box(center - reach, center + reach)
+            .into_iter()
+            .filter(|&handle| {
+                self.world.get::<WebTransform>(handle).is_some_and(|transform| {
+                    circle.contact(&circle_transform, &collider_shape(&self.world, handle), transform).is_some()
+                })
+            })
+            .collect();
+        handles.sort();
+        handles
+    }
+    
+    /// Entity whose position is closest to `point`, optionally only those with
+    /// `tag`; an empty tag matches every entity
+    pub fn nearest_entity(&mut self, point: Vector3<f32>, tag: &str) -> Option<EntityHandle> {
+        if !point.iter().all(|coordinate| coordinate.is_finite()) {
+            return None;
+        }
+        
+        self.collision_system.refresh_grid(&self.world);
+        let grid = self.collision_system.spatial_grid();
+        let (occupied_min, occupied_max) = grid.occupied_cells()?;
+        
+        // A box this wide around `point` contains every occupied cell
+        let grid_min = Vector3::new(occupied_min.0, occupied_min.1, occupied_min.2).cast::<f32>() * grid.cell_size();
+        let grid_max = Vector3::new(occupied_max.0, occupied_max.1, occupied_max.2).cast::<f32>().add_scalar(1.0) * grid.cell_size();
+        let full_reach = (point - grid_min).abs().sup(&(grid_max - point).abs()).max();
+        
+        let matches = |handle: EntityHandle| {
+            tag.is_empty() || self.world.get::<WebEntityInfo>(handle).is_some_and(|info| info.tag == tag)
+        };
+        
+        // Grow the search box until it holds a match no farther than its own
+        // half-width, which rules out anything outside it
+        let mut reach = grid.cell_size();
+        loop {
+            let extent = Vector3::new(reach, reach, reach);
+            let closest = grid.query_box(point - extent, point + extent)
+                .into_iter()
+                .filter(|&handle| matches(handle))
+                .filter_map(|handle| {
+                    let transform = self.world.get::<WebTransform>(handle)?;
+                    Some(((transform.position - point).magnitude(), handle))
+                })
+                .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+            
+            let covers_grid = reach >= full_reach;
+            match closest {
+                Some((distance, handle)) if distance <= reach || covers_grid => return Some(handle),
+                None if covers_grid => return None,
+                _ => reach *= 2.0,
+            }
+        }
+    }
+    
+    pub fn world_bounds(&self) -> &WorldBounds {
+        &self.world_bounds
+    }
+    
+    /// Moves the world rectangle; corners may be given in either order
+    pub fn set_world_bounds(&mut self, corner: Vector2<f32>, opposite: Vector2<f32>, margin: f32) {
+        self.world_bounds.min = corner.inf(&opposite);
+        self.world_bounds.max = corner.sup(&opposite);
+        self.world_bounds.margin = margin.max(0.0);
+    }
+    
+    pub fn set_boundary_mode(&mut self, mode: BoundaryMode) {
+        self.world_bounds.mode = mode;
+    }
+    
+    /// Gives the entity its own boundary mode, or `None` to follow the world's
+    pub fn set_entity_boundary_mode(&mut self, handle: EntityHandle, mode: Option<BoundaryMode>) -> bool {
+        if !self.world.is_alive(handle) {
+            return false;
+        }
+        
+        match mode {
+            Some(mode) => self.world.insert(handle, WebBoundary { mode }),
+            None => {
+                self.world.remove::<WebBoundary>(handle);
+                true
+            }
+        }
+    }
+    
+    /// Renders `handle` relative to `parent`, or on its own with `None`; false
+    /// if either is gone or the link would make a cycle
+    pub fn set_entity_parent(&mut self, handle: EntityHandle, parent: Option<EntityHandle>) -> bool {
+        if !self.world.is_alive(handle) {
+            return false;
+        }
+        
+        let Some(parent) = parent else {
+            self.world.remove::<WebParent>(handle);
+            return true;
+        };
+        if !self.world.is_alive(parent) {
+            return false;
+        }
+        
+        // Walk up from the new parent; reaching `handle` means a cycle
+        let mut ancestor = Some(parent);
+        while let Some(current) = ancestor {
+            if current == handle {
+                return false;
+            }
+            ancestor = self.world.get::<WebParent>(current).map(|link| link.parent);
+        }
+        self.world.insert(handle, WebParent { parent })
+    }
+    
+    /// Where the entity falls in draw order; adds a renderer if missing
+    pub fn set_entity_rendering(&mut self, handle: EntityHandle, render_layer: u8, blend_mode: BlendMode, texture_id: u32) -> bool {
+        if !self.world.is_alive(handle) {
+            return false;
+        }
+        
+        if !self.world.has::<WebRenderer>(handle) {
+            self.world.insert(handle, WebRenderer::default());
+        }
+        if let Some(renderer) = self.world.get_mut::<WebRenderer>(handle) {
+            renderer.render_layer = render_layer;
+            renderer.blend_mode = blend_mode;
+            renderer.texture_id = texture_id;
+        }
+        true
+    }
+    
+    pub fn set_entity_scale(&mut self, handle: EntityHandle, scale: Vector3<f32>) -> bool {
+        match self.world.get_mut::<WebTransform>(handle) {
+            Some(transform) => {
+                transform.scale = scale;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    pub fn set_quality_level(&mut self, quality: u8) {
+        let quality_level = self.performance.quality_level;
+        self.performance.quality_level = quality.min(2);
+        self.performance.adaptive_quality = false;
+        self.physics_preset = PhysicsQuality::for_level(self.performance.quality_level);
+        platform_log!(self.platform.logger, "Quality manually set to {}", self.performance.quality_level);
+        self.report_quality_change(quality_level);
+    }
+    
+    /// Fixes substeps and iterations regardless of quality level; `None` goes back to the preset
+    pub fn set_physics_quality(&mut self, physics_quality: Option<PhysicsQuality>) {
+        self.physics_quality = physics_quality;
+    }
+    
+    fn report_quality_change(&mut self, previous: u8) {
+        if self.performance.quality_level != previous {
+            self.events.push(EngineEvent::QualityChanged { level: self.performance.quality_level });
+        }
+    }
+    
+    pub fn set_paused(&mut self, paused: bool) {
+        if self.paused == paused {
+            return;
+        }
+        
+        self.paused = paused;
+        self.events.push(EngineEvent::Paused { paused });
+        platform_log!(self.platform.logger, "Game {}", if self.paused { "paused" } else { "resumed" });
+    }
+    
+    pub fn enable_adaptive_quality(&mut self, enabled: bool) {
+        self.performance.adaptive_quality = enabled;
+        platform_log!(self.platform.logger, "Adaptive quality {}", if enabled { "enabled" } else { "disabled" });
+    }
+    
+    /// Per visible entity in draw order (see `get_render_batches`), 20 floats:
+    /// - 0..16: the 4x4 model matrix in column-major order (as `uniformMatrix4fv`
+    ///   expects) with scale, rotation, translation and parents applied
+    /// - 16..20: red, green, blue and alpha, with opacity folded into alpha
+    pub fn get_entity_render_data(&self) -> Vec<f32> {
+        let mut order = Vec::new();
+        self.draw_order(&mut order);
+        let mut data = Vec::with_capacity(order.len() * 20);
+        self.write_entity_render_data(&order, &mut data);
+        data
+    }
+    
+    fn draw_order(&self, order: &mut Vec<DrawKey>) {
+        order.clear();
+        order.extend(self.world.query2::<WebRenderer, WebTransform>()
+            .filter(|&(handle, renderer, _)| renderer.visible && !self.view_culler.is_culled(handle))
+            .map(|(handle, renderer, _)| (renderer.render_layer, renderer.blend_mode, renderer.texture_id, handle)));
+        order.sort_unstable();
+    }
+    
+    fn write_entity_render_data(&self, order: &[DrawKey], data: &mut Vec<f32>) {
+        for &(_, _, _, handle) in order {
+            let Some(renderer) = self.world.get::<WebRenderer>(handle) else {
+                continue;
+            };
+            
+            let model = self.model_matrix(handle).unwrap_or_else(Matrix4::identity);
+            data.extend_from_slice(model.as_slice());
+            data.extend_from_slice(&[
+                renderer.color[0], renderer.color[1], renderer.color[2], 
+                renderer.color[3] * renderer.opacity,
+            ]);
+        }
+    }
+    
+    /// One draw call's worth of entities per row, in draw order: [offset,
+    /// count, blend_mode, texture_id], with offset and count in entities.
+    /// Rows are sorted by render layer, then blend mode, then texture.
+    pub fn get_render_batches(&self) -> Vec<u32> {
+        let mut order = Vec::new();
+        self.draw_order(&mut order);
+        let mut batches = Vec::new();
+        Self::write_render_batches(&order, &mut batches);
+        batches
+    }
+    
+    fn write_render_batches(order: &[DrawKey], batches: &mut Vec<u32>) {
+        let mut start = 0;
+        for (index, &(layer, blend_mode, texture_id, _)) in order.iter().enumerate() {
+            let last_in_batch = order.get(index + 1)
+                .is_none_or(|&(next_layer, next_blend, next_texture, _)| (next_layer, next_blend, next_texture) != (layer, blend_mode, texture_id));
+            if last_in_batch {
+                batches.extend_from_slice(&[start as u32, (index + 1 - start) as u32, blend_mode as u32, texture_id]);
+                start = index + 1;
+            }
+        }
+    }
+    
+    /// World-space model matrix: the entity's own transform under its parents'
+    pub fn model_matrix(&self, handle: EntityHandle) -> Option<Matrix4<f32>> {
+        let own = self.world.get::<WebTransform>(handle)?.local_matrix();
+        Some(self.ancestor_transforms(handle).fold(own, |matrix, parent| parent.local_matrix() * matrix))
+    }
+    
+    /// World-space position and rotation at the start of the current tick, so
+    /// children interpolate along with their parents
+    pub fn previous_world_transform(&self, handle: EntityHandle) -> Option<(Vector3<f32>, f32)> {
+        let own = self.world.get::<WebTransform>(handle)?;
+        let (matrix, rotation) = self.ancestor_transforms(handle)
+            .fold((own.previous_local_matrix(), own.previous_rotation), |(matrix, rotation), parent| {
+                (parent.previous_local_matrix() * matrix, rotation + parent.previous_rotation)
+            });
+        Some((matrix.fixed_view::<3, 1>(0, 3).into_owned(), rotation))
+    }
+    
+    // Transforms of the entity's parent, grandparent and so on. Depth-limited
+    // in case a cycle was built through `world_mut`
+    fn ancestor_transforms(&self, handle: EntityHandle) -> impl Iterator<Item = &WebTransform> + '_ {
+        let mut current = handle;
+        std::iter::from_fn(move || {
+            current = self.world.get::<WebParent>(current)?.parent;
+            self.world.get::<WebTransform>(current)
+        })
+        .take(MAX_HIERARCHY_DEPTH)
+    }
+    
+    /// Previous-tick world position and rotation per entity, in the same order
+    /// as `get_entity_render_data`: [prev_x, prev_y, prev_z, prev_rotation].
+    /// Rotation adds up the parents' rotations
+    pub fn get_entity_previous_transform_data(&self) -> Vec<f32> {
+        let mut order = Vec::new();
+        self.draw_order(&mut order);
+        let mut data = Vec::with_capacity(order.len() * 4);
+        self.write_entity_previous_transform_data(&order, &mut data);
+        data
+    }
+    
+    fn write_entity_previous_transform_data(&self, order: &[DrawKey], data: &mut Vec<f32>) {
+        for &(_, _, _, handle) in order {
+            let Some((position, rotation)) = self.previous_world_transform(handle) else {
+                continue;
+            };
+            
+            data.extend_from_slice(&[position.x, position.y, position.z, rotation]);
+        }
+    }
+    
+    pub fn get_particle_render_data(&self) -> Vec<f32> {
+        self.particle_system.get_render_data()
+    }
+    
+    /// 40 floats: view then projection matrix (both 4x4, column-major), then
+    /// position xyz, focus xyz, vertical fov in degrees with zoom applied, zoom
+    pub fn get_camera_data(&self) -> Vec<f32> {
+        let mut data = Vec::with_capacity(40);
+        self.write_camera_data(&mut data);
+        data
+    }
+    
+    fn write_camera_data(&self, data: &mut Vec<f32>) {
+        let (position, focus) = (self.camera.position(), self.camera.focus);
+        data.extend_from_slice(self.camera.view_matrix().as_slice());
+        data.extend_from_slice(self.camera.projection_matrix().as_slice());
+        data.extend_from_slice(&[
+            position.x, position.y, position.z,
+            focus.x, focus.y, focus.z,
+            self.camera.effective_fov(),
+            self.camera.zoom,
+        ]);
+    }
+    
+    pub fn camera(&self) -> &WebCamera {
+        &self.camera
+    }
+    
+    pub fn camera_mut(&mut self) -> &mut WebCamera {
+        &mut self.camera
+    }
+    
+    /// The real canvas size, for the camera's aspect ratio and orthographic extents
+    pub fn set_canvas_size(&mut self, width: f32, height: f32) {
+        self.camera.viewport = Vector2::new(width.max(1.0), height.max(1.0));
+    }
+    
+    /// Rewrites every render buffer from the current state without allocating
+    /// (beyond growing a buffer past its largest size so far)
+    pub fn update_render_buffers(&mut self) {
+        let mut buffers = std::mem::take(&mut self.render_buffers);
+        self.write_render_buffers(&mut buffers);
+        self.render_buffers = buffers;
+    }
+    
+    /// Every render buffer written into fresh storage, for callers that hand
+    /// the data off instead of reading it in place
+    pub fn render_data(&self) -> RenderBuffers {
+        let mut buffers = RenderBuffers::default();
+        self.write_render_buffers(&mut buffers);
+        buffers
+    }
+    
+    // Sorts the draw order once and writes every buffer from it
+    fn write_render_buffers(&self, buffers: &mut RenderBuffers) {
+        self.draw_order(&mut buffers.draw_order);
+        buffers.entities.clear();
+        self.write_entity_render_data(&buffers.draw_order, &mut buffers.entities);
+        buffers.previous_transforms.clear();
+        self.write_entity_previous_transform_data(&buffers.draw_order, &mut buffers.previous_transforms);
+        buffers.batches.clear();
+        Self::write_render_batches(&buffers.draw_order, &mut buffers.batches);
+        buffers.particles.clear();
+        self.particle_system.write_render_data(&mut buffers.particles);
+        buffers.camera.clear();
+        self.write_camera_data(&mut buffers.camera);
+    }
+    
+    pub fn render_buffers(&self) -> &RenderBuffers {
+        &self.render_buffers
+    }
+    
+    // === COMMANDS ===
+    
+    pub fn create_explosion(&mut self, x: f32, y: f32, z: f32, intensity: f32) {
+        let position = Vector3::new(x, y, z);
+        self.particle_system.create_explosion(position, intensity, self.platform.rng.as_mut());
+    }
+    
+    pub fn add_entity(&mut self, x: f32, y: f32, z: f32, name: String, tag: String) -> EntityHandle {
+        let position = Vector3::new(x, y, z);
+        let entity = self.world.spawn()
+            .with(WebEntityInfo { name, tag })
+            .with(WebTransform::at(position))
+            .with(WebPhysics::default())
+            .with(WebRenderer::default())
+            .build();
+        self.events.push(EngineEvent::EntitySpawned { entity });
+        entity
+    }
+    
+    pub fn set_browser_capabilities(&mut self, 
+                                   webgl2: bool, 
+                                   hardware_accel: bool, 
+                                   is_mobile: bool, 
+                                   cpu_cores: u32) {
+        self.capabilities.webgl2_available = webgl2;
+        self.capabilities.hardware_acceleration = hardware_accel;
+        self.capabilities.is_mobile = is_mobile;
+        self.capabilities.cpu_cores = cpu_cores;
+        
+        // Adjust performance based on capabilities
+        let quality_level = self.performance.quality_level;
+        if is_mobile || !hardware_accel {
+            self.performance.quality_level = 1; // Start with medium quality on mobile/slow devices
+        }
+        self.report_quality_change(quality_level);
+        
+        platform_log!(self.platform.logger, "Browser capabilities updated: WebGL2={}, HW Accel={}, Mobile={}, Cores={}", 
+                    webgl2, hardware_accel, is_mobile, cpu_cores);
+    }
+    
+    /// Reseeds the engine RNG and rebuilds the world from that seed
+    pub fn set_seed(&mut self, seed: u32) {
+        self.platform.rng.set_seed(seed);
+        platform_log!(self.platform.logger, "Seed set to {}", seed);
+        self.reset_game();
+    }
+    
+    pub fn reset_game(&mut self) {
+        platform_log!(self.platform.logger, "Resetting web game state");
+        
+        // Replay the current seed so a reset reproduces the same world
+        let seed = self.platform.rng.seed();
+        self.platform.rng.set_seed(seed);
+        
+        // Clearing frees every handle, so old ones stay invalid
+        self.world.clear();
+        self.player = None;
+        self.score = 0;
+        self.level = 1;
+        self.set_paused(false);
+        
+        self.particle_system = WebParticleSystem::new();
+        self.collision_system = WebCollisionSystem::new();
+        self.constraint_solver.clear();
+        self.timestep.reset();
+        
+        self.initialize_scene();
+    }
+    
+    pub fn cleanup(&mut self) {
+        platform_log!(self.platform.logger, "Cleaning up Web Game Engine v1.12");
+        self.world.clear();
+        self.player = None;
+        self.particle_system = WebParticleSystem::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const FRAME: f32 = 1.0 / 60.0;
+    
+    fn position(state: &WebGameState, handle: EntityHandle) -> Vector3<f32> {
+        state.world().get::<WebTransform>(handle).unwrap().position
+    }
+    
+    // Steps a default scene with the player held right, then records every
+    // entity position, the score and the particle render data
+    fn run_scene(seed: u32, frames: usize) -> (Vec<f32>, i32, Vec<f32>) {
+        let mut state = WebGameState::headless(seed);
+        state.handle_key_event(68, true); // D
+        for _ in 0..frames {
+            state.step(FRAME);
+        }
+        state.create_explosion(CANVAS_WIDTH / 2.0, CANVAS_HEIGHT / 2.0, 0.0, 1.0);
+        for _ in 0..10 {
+            state.step(FRAME);
+        }
+        
+        let mut positions: Vec<f32> = state.world().query::<WebTransform>()
+            .flat_map(|(_, transform)| [transform.position.x, transform.position.y, transform.position.z])
+            .collect();
+        positions.extend(state.get_particle_render_data());
+        (positions, state.get_score(), state.get_particle_render_data())
+    }
+    
+    #[test]
+    fn headless_state_builds_default_scene() {
+        let state = WebGameState::headless(1);
+        let player = state.player_handle().unwrap();
+        assert!(state.is_entity_valid(player));
+        assert_eq!(position(&state, player), Vector3::new(CANVAS_WIDTH / 2.0, CANVAS_HEIGHT / 2.0, 0.0));
+        assert_eq!(state.get_entity_count(), 51);
+        assert_eq!(state.get_score(), 0);
+        assert_eq!(state.get_particle_count(), 0);
+    }
+    
+    #[test]
+    fn held_input_moves_player() {
+        let mut state = WebGameState::headless(1);
+        let player = state.player_handle().unwrap();
+        let start = position(&state, player);
+        state.handle_key_event(68, true); // D
+        for _ in 0..30 {
+            state.step(FRAME);
+        }
+        let moved = position(&state, player) - start;
+        assert!(moved.x > 1.0, "{:?}", moved);
+        assert!(moved.y.abs() < 1e-3, "{:?}", moved);
+    }
+    
+    #[test]
+    fn explosion_spawns_and_expires_particles() {
+        let mut state = WebGameState::headless(1);
+        state.create_explosion(100.0, 100.0, 0.0, 1.0);
+        let spawned = state.get_particle_count();
+        assert_eq!(spawned, 30);
+        state.step(FRAME);
+        assert_eq!(state.get_particle_count(), spawned);
+        for _ in 0..180 {
+            state.step(FRAME);
+        }
+        assert_eq!(state.get_particle_count(), 0);
+    }
+    
+    #[test]
+    fn player_collisions_score() {
+        let mut state = WebGameState::headless(1);
+        let player = state.player_handle().unwrap();
+        let at = position(&state, player);
+        let other = state.add_entity(at.x + 10.0, at.y, 0.0, "Crate".to_string(), "Environment".to_string());
+        state.world_mut().get_mut::<WebPhysics>(other).unwrap().use_gravity = false;
+        state.step(FRAME);
+        assert_eq!(state.get_score(), 10);
+    }
+    
+    #[test]
+    fn same_seed_replays_same_frames() {
+        let first = run_scene(7, 120);
+        assert_eq!(first, run_scene(7, 120));
+        assert_ne!(first.0, run_scene(8, 120).0);
+        assert!(!first.2.is_empty());
+    }
+    
+    #[test]
+    fn frame_rate_does_not_change_simulation() {
+        let simulate = |frames: usize, frame_time: f32| {
+            let mut state = WebGameState::headless(3);
+            state.enable_adaptive_quality(false); // Slow frames would otherwise lower the quality level
+            state.handle_key_event(68, true); // D
+            for _ in 0..frames {
+                state.step(frame_time);
+            }
+            let positions: Vec<Vector3<f32>> = state.world().query::<WebTransform>()
+                .map(|(_, transform)| transform.position)
+                .collect();
+            (state.timestep().total_ticks, positions)
+        };
+        
+        let at_60 = simulate(60, 1.0 / 60.0);
+        assert_eq!(at_60.0, 60);
+        assert_eq!(at_60, simulate(30, 1.0 / 30.0));
+    }
+    
+    #[test]
+    fn long_frames_cap_catch_up_ticks() {
+        let mut state = WebGameState::headless(1);
+        state.step(FRAME * 1.5);
+        assert_eq!(state.timestep().total_ticks, 1);
+        let alpha = state.get_interpolation_alpha();
+        assert!((alpha - 0.5).abs() < 0.01, "{}", alpha);
+        
+        state.step(1.0);
+        assert_eq!(state.timestep().total_ticks, 1 + MAX_CATCH_UP_STEPS as u64);
+        assert!(state.timestep().dropped_ticks > 0);
+        assert!(state.get_interpolation_alpha() < 1.0);
+    }
+    
+    #[test]
+    fn stale_handles_stay_invalid() {
+        let mut state = WebGameState::headless(1);
+        let entity = state.add_entity(10.0, 20.0, 0.0, "Crate".to_string(), "Environment".to_string());
+        assert_eq!(EntityHandle::from_f64(entity.to_f64()), Some(entity));
+        assert!(EntityHandle::from_f64(0.0).is_none_or(|handle| !state.is_entity_valid(handle)));
+        assert_eq!(EntityHandle::from_f64(0.5), None);
+        
+        // A despawned slot is reused under a new generation
+        assert!(state.remove_entity(entity));
+        assert!(!state.remove_entity(entity));
+        let reused = state.add_entity(30.0, 40.0, 0.0, "Crate".to_string(), "Environment".to_string());
+        assert_ne!(reused, entity);
+        assert!(!state.is_entity_valid(entity));
+        assert!(state.world().get::<WebTransform>(entity).is_none());
+        assert_eq!(position(&state, reused), Vector3::new(30.0, 40.0, 0.0));
+        
+        let player = state.player_handle().unwrap();
+        state.reset_game();
+        assert!(!state.is_entity_valid(player) && !state.is_entity_valid(reused));
+        assert!(state.is_entity_valid(state.player_handle().unwrap()));
+    }
+    
+    #[test]
+    fn removing_player_clears_player_handle() {
+        let mut state = WebGameState::headless(1);
+        let player = state.player_handle().unwrap();
+        assert!(state.remove_entity(player));
+        assert_eq!(state.player_handle(), None);
+        state.handle_key_event(68, true); // D
+        state.step(FRAME);
+        
+        let entity = state.add_entity(10.0, 20.0, 0.0, "Hero".to_string(), "Player".to_string());
+        assert!(state.set_player(entity));
+        assert!(!state.set_player(player));
+        assert_eq!(state.player_handle(), Some(entity));
+    }
+    
+    // Two floating bodies on the left of the screen, away from the default scene's player
+    fn floating_pair(state: &mut WebGameState, gap: f32) -> (EntityHandle, EntityHandle) {
+        state.world_mut().clear();
+        let a = state.add_entity(100.0, 300.0, 0.0, "A".to_string(), "Environment".to_string());
+        let b = state.add_entity(100.0 + gap, 300.0, 0.0, "B".to_string(), "Environment".to_string());
+        for handle in [a, b] {
+            let physics = state.world_mut().get_mut::<WebPhysics>(handle).unwrap();
+            physics.use_gravity = false;
+            physics.drag = 0.0;
+        }
+        state.drain_events();
+        (a, b)
+    }
+    
+    #[test]
+    fn events_report_spawns_pauses_and_quality() {
+        let mut state = WebGameState::headless(1);
+        let spawned = state.drain_events().iter()
+            .filter(|event| matches!(event, EngineEvent::EntitySpawned { .. }))
+            .count();
+        assert_eq!(spawned, state.get_entity_count());
+        
+        // Repeating a pause state is not a change
+        state.set_paused(true);
+        state.set_paused(true);
+        state.set_paused(false);
+        assert_eq!(state.drain_events(), vec![EngineEvent::Paused { paused: true }, EngineEvent::Paused { paused: false }]);
+        
+        state.set_quality_level(0);
+        assert_eq!(state.drain_events(), vec![EngineEvent::QualityChanged { level: 0 }]);
+        assert!(state.drain_events().is_empty());
+    }
+    
+    #[test]
+    fn events_report_collision_start_and_end_once() {
+        let mut state = WebGameState::headless(1);
+        let (a, b) = floating_pair(&mut state, 10.0);
+        state.world_mut().get_mut::<WebTransform>(b).unwrap().velocity = Vector3::new(300.0, 0.0, 0.0);
+        
+        let mut events = Vec::new();
+        for _ in 0..30 {
+            state.step(FRAME);
+            events.extend(state.drain_events());
+        }
+        let (a, b) = (a.min(b), a.max(b));
+        assert_eq!(events, vec![EngineEvent::CollisionStarted { a, b }, EngineEvent::CollisionEnded { a, b }]);
+    }
+    
+    #[test]
+    fn full_event_queue_drops_oldest() {
+        let mut queue = EventQueue::new();
+        for level in 0..MAX_QUEUED_EVENTS + 2 {
+            queue.push(EngineEvent::QualityChanged { level: (level % 256) as u8 });
+        }
+        assert_eq!(queue.len(), MAX_QUEUED_EVENTS);
+        assert_eq!(queue.dropped_events, 2);
+        assert_eq!(queue.pending().next(), Some(&EngineEvent::QualityChanged { level: 2 }));
+        
+        queue.begin_tick();
+        queue.push(EngineEvent::Paused { paused: true });
+        assert_eq!(queue.current_tick().collect::<Vec<_>>(), vec![&EngineEvent::Paused { paused: true }]);
+        assert_eq!(queue.drain().len(), MAX_QUEUED_EVENTS);
+        assert!(queue.is_empty());
+    }
+    
+    fn at(x: f32, y: f32, rotation: f32) -> WebTransform {
+        let mut transform = WebTransform::at(Vector3::new(x, y, 0.0));
+        transform.rotation = rotation;
+        transform
+    }
+    
+    #[test]
+    fn sat_contacts_between_shapes() {
+        let square = ColliderShape::Aabb { half_extents: Vector2::new(10.0, 10.0) };
+        let circle = ColliderShape::Circle { radius: 5.0 };
+        let contact = square.contact(&at(0.0, 0.0, 0.0), &circle, &at(13.0, 0.0, 0.0)).unwrap();
+        assert!((contact.depth - 2.0).abs() < 1e-4);
+        assert!((contact.normal - Vector3::new(-1.0, 0.0, 0.0)).magnitude() < 1e-4);
+        assert!(square.contact(&at(0.0, 0.0, 0.0), &circle, &at(14.0, 14.0, 0.0)).is_none());
+        
+        // Only the oriented box turns, reaching further along X at 45 degrees
+        let oriented = ColliderShape::Obb { half_extents: Vector2::new(10.0, 10.0) };
+        assert!(oriented.contact(&at(0.0, 0.0, 45.0), &circle, &at(18.0, 0.0, 0.0)).is_some());
+        assert!(square.contact(&at(0.0, 0.0, 45.0), &circle, &at(18.0, 0.0, 0.0)).is_none());
+        
+        let capsule = ColliderShape::Capsule { half_length: 20.0, radius: 2.0 };
+        assert!(capsule.contact(&at(0.0, 0.0, 0.0), &circle, &at(19.0, 6.0, 0.0)).is_some());
+        assert!(capsule.contact(&at(0.0, 0.0, 90.0), &circle, &at(19.0, 6.0, 0.0)).is_none());
+        
+        let triangle = ColliderShape::Polygon { points: vec![Vector2::new(0.0, 0.0), Vector2::new(10.0, 0.0), Vector2::new(0.0, 10.0)] };
+        assert!(triangle.contact(&at(0.0, 0.0, 0.0), &circle, &at(9.0, 9.0, 0.0)).is_none());
+        assert!(triangle.contact(&at(0.0, 0.0, 0.0), &circle, &at(6.0, 6.0, 0.0)).is_some());
+        
+        // Planar shapes extend along Z; spheres don't
+        let mut above = at(0.0, 0.0, 0.0);
+        above.position.z = 7.0;
+        let cuboid = ColliderShape::Cuboid { half_extents: Vector3::new(5.0, 5.0, 5.0) };
+        assert!(cuboid.contact(&at(0.0, 0.0, 0.0), &ColliderShape::Sphere { radius: 1.0 }, &above).is_none());
+        assert!(cuboid.contact(&at(0.0, 0.0, 0.0), &circle, &above).is_some());
+        
+        // Past a cuboid's vertical edge: 0.636 from it, so a 0.5 sphere misses
+        let unit = ColliderShape::Cuboid { half_extents: Vector3::new(1.0, 1.0, 1.0) };
+        let sphere = ColliderShape::Sphere { radius: 0.5 };
+        assert!(sphere.contact(&at(1.45, 1.45, 0.0), &unit, &at(0.0, 0.0, 0.0)).is_none());
+        assert!(sphere.contact(&at(1.3, 1.3, 0.0), &unit, &at(0.0, 0.0, 0.0)).is_some());
+        assert!(unit.contact(&at(0.0, 0.0, 0.0), &sphere, &at(1.45, 1.45, 0.0)).is_none());
+    }
+    
+    #[test]
+    fn invalid_colliders_are_rejected() {
+        let polygon = |points: &[(f32, f32)]| ColliderShape::Polygon {
+            points: points.iter().map(|&(x, y)| Vector2::new(x, y)).collect(),
+        };
+        assert!(polygon(&[(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)]).is_valid());
+        assert!(polygon(&[(0.0, 0.0), (0.0, 10.0), (10.0, 0.0)]).is_valid());
+        assert!(!polygon(&[(0.0, 0.0), (10.0, 0.0)]).is_valid());
+        assert!(!polygon(&[(0.0, 0.0), (5.0, 0.0), (10.0, 0.0)]).is_valid());
+        assert!(!polygon(&[(0.0, 0.0), (5.0, 0.0), (10.0, 0.0), (0.0, 10.0)]).is_valid());
+        assert!(!polygon(&[(0.0, 0.0), (10.0, 0.0), (2.0, 2.0), (0.0, 10.0)]).is_valid());
+        assert!(!polygon(&[(0.0, 0.0), (10.0, 0.0), (10.0, 0.0), (0.0, 10.0)]).is_valid());
+        let star: Vec<(f32, f32)> = (0..5)
+            .map(|i| (i as f32 * 4.0 * std::f32::consts::PI / 5.0).sin_cos())
+            .collect();
+        assert!(!polygon(&star).is_valid());
+        assert!(!ColliderShape::Circle { radius: -1.0 }.is_valid());
+        assert!(!ColliderShape::Obb { half_extents: Vector2::new(1.0, -1.0) }.is_valid());
+        assert!(!ColliderShape::Capsule { half_length: f32::NAN, radius: 1.0 }.is_valid());
+        assert!(ColliderShape::Cuboid { half_extents: Vector3::new(1.0, 0.0, 2.0) }.is_valid());
+        
+        let mut state = WebGameState::headless(1);
+        let entity = state.add_entity(10.0, 20.0, 0.0, "Crate".to_string(), "Environment".to_string());
+        assert!(!state.set_collider(entity, ColliderShape::Sphere { radius: -2.0 }));
+        assert!(!state.set_collider(entity, polygon(&[(0.0, 0.0), (10.0, 0.0), (2.0, 2.0), (0.0, 10.0)])));
+        assert!(state.world().get::<WebCollider>(entity).is_none());
+        assert!(state.set_collider(entity, ColliderShape::Sphere { radius: 2.0 }));
+    }
+    
+    #[test]
+    fn triggers_report_without_pushing() {
+        let mut state = WebGameState::headless(1);
+        let (zone, body) = floating_pair(&mut state, 10.0);
+        assert!(state.set_trigger(zone, true));
+        state.step(FRAME);
+        state.step(FRAME);
+        assert_eq!(state.drain_events(), vec![
+            EngineEvent::TriggerEnter { trigger: zone, other: body },
+            EngineEvent::TriggerStay { trigger: zone, other: body },
+        ]);
+        assert_eq!(position(&state, body).x, 110.0);
+        
+        state.world_mut().get_mut::<WebTransform>(body).unwrap().position.x = 300.0;
+        state.step(FRAME);
+        assert_eq!(state.drain_events(), vec![EngineEvent::TriggerExit { trigger: zone, other: body }]);
+    }
+    
+    #[test]
+    fn continuous_collision_stops_tunneling() {
+        // 100 units per tick against a wall 2 units thick, or a circle of radius 3
+        let final_x = |continuous: bool, wall_shape: ColliderShape| {
+            let mut state = WebGameState::headless(1);
+            let (wall, bullet) = floating_pair(&mut state, 300.0);
+            state.world_mut().get_mut::<WebTransform>(wall).unwrap().position.x = 500.0;
+            assert!(state.set_collider(wall, wall_shape));
+            assert!(state.set_entity_physics(wall, 1.0, 0.5, true));
+            assert!(state.set_collider(bullet, ColliderShape::Circle { radius: 2.0 }));
+            assert!(state.set_continuous_collision(bullet, continuous));
+            state.world_mut().get_mut::<WebTransform>(bullet).unwrap().velocity = Vector3::new(6000.0, 0.0, 0.0);
+            for _ in 0..3 {
+                state.step(FRAME);
+            }
+            position(&state, bullet).x
+        };
+        
+        let thin = ColliderShape::Aabb { half_extents: Vector2::new(1.0, 100.0) };
+        assert!(final_x(false, thin.clone()) > 500.0);
+        assert!(final_x(true, thin) < 500.0);
+        assert!(final_x(false, ColliderShape::Circle { radius: 3.0 }) > 500.0);
+        assert!(final_x(true, ColliderShape::Circle { radius: 3.0 }) < 500.0);
+    }
+    
+    #[test]
+    fn queries_find_closest_hits_and_overlaps() {
+        let mut state = WebGameState::headless(1);
+        state.world_mut().clear();
+        let wall = state.add_entity(100.0, 100.0, 0.0, "Wall".to_string(), "Environment".to_string());
+        let near = state.add_entity(300.0, 100.0, 0.0, "Near".to_string(), "Enemy".to_string());
+        let far = state.add_entity(900.0, 700.0, 0.0, "Far".to_string(), "Enemy".to_string());
+        assert!(state.set_collider(wall, ColliderShape::Aabb { half_extents: Vector2::new(10.0, 20.0) }));
+        
+        let hit = state.raycast(Vector3::new(0.0, 105.0, 0.0), Vector3::x(), 1000.0, u32::MAX).unwrap();
+        assert_eq!(hit.entity, wall);
+        assert!((hit.distance - 90.0).abs() < 1e-3, "{:?}", hit);
+        assert!((hit.normal + Vector3::x()).magnitude() < 1e-4, "{:?}", hit);
+        
+        // Directions needn't be normalized; range and mask both limit hits
+        let hit = state.raycast(Vector3::new(1000.0, 100.0, 0.0), Vector3::new(-2.0, 0.0, 0.0), 1e9, u32::MAX).unwrap();
+        assert_eq!(hit.entity, near);
+        assert!(state.raycast(Vector3::new(1000.0, 100.0, 0.0), -Vector3::x(), 600.0, u32::MAX).is_none());
+        assert!(state.raycast(Vector3::new(0.0, 105.0, 0.0), Vector3::x(), 1000.0, 0).is_none());
+        assert_eq!(state.raycast(Vector3::new(900.0, -50.0, 0.0), Vector3::y(), f32::INFINITY, u32::MAX).unwrap().entity, far);
+        
+        // Triggers don't block rays but still overlap
+        assert!(state.set_trigger(wall, true));
+        assert_eq!(state.raycast(Vector3::new(0.0, 105.0, 0.0), Vector3::x(), 1000.0, u32::MAX).unwrap().entity, near);
+        let mut expected = vec![wall, near];
+        expected.sort();
+        assert_eq!(state.overlap_circle(Vector3::new(200.0, 100.0, 0.0), 95.0), expected);
+        assert!(state.overlap_circle(Vector3::new(200.0, 400.0, 0.0), 5.0).is_empty());
+        
+        assert_eq!(state.nearest_entity(Vector3::zeros(), ""), Some(wall));
+        assert_eq!(state.nearest_entity(Vector3::zeros(), "Enemy"), Some(near));
+        assert_eq!(state.nearest_entity(Vector3::new(5000.0, 5000.0, 0.0), "Enemy"), Some(far));
+        assert_eq!(state.nearest_entity(Vector3::zeros(), "Pickup"), None);
+    }
+    
+    #[test]
+    fn constraints_hold_and_die_with_their_entities() {
+        let mut state = WebGameState::headless(1);
+        state.world_mut().clear();
+        state.set_boundary_mode(BoundaryMode::Unbounded);
+        let pinned = state.add_entity(100.0, 100.0, 0.0, "Pinned".to_string(), "Environment".to_string());
+        let swinging = state.add_entity(150.0, 100.0, 0.0, "Swinging".to_string(), "Environment".to_string());
+        let climber = state.add_entity(400.0, 400.0, 0.0, "Climber".to_string(), "Environment".to_string());
+        let anchor = state.add_entity(700.0, 400.0, 0.0, "Anchor".to_string(), "Environment".to_string());
+        for handle in [pinned, swinging, climber, anchor] {
+            state.set_collision_layers(handle, 0, 0);
+        }
+        
+        let rod = state.add_constraint(Constraint::Distance { a: pinned, b: swinging, length: 50.0 }).unwrap();
+        state.add_constraint(Constraint::Pin { entity: pinned, anchor: Vector3::new(100.0, 100.0, 0.0), length: 0.0 }).unwrap();
+        let rope = state.add_constraint(Constraint::Rope { a: climber, b: anchor, max_length: 400.0 }).unwrap();
+        for _ in 0..120 {
+            state.step(FRAME);
+        }
+        
+        // The rod swings down under gravity but keeps its length
+        let (pinned_at, swinging_at) = (position(&state, pinned), position(&state, swinging));
+        assert!((pinned_at - Vector3::new(100.0, 100.0, 0.0)).magnitude() < 1.0, "{:?}", pinned_at);
+        assert!(((swinging_at - pinned_at).magnitude() - 50.0).abs() < 1.0, "{:?}", swinging_at);
+        assert!(swinging_at.y < 100.0, "{:?}", swinging_at);
+        assert!((position(&state, climber) - position(&state, anchor)).magnitude() <= 401.0);
+        
+        assert!(state.remove_constraint(rope));
+        assert!(!state.remove_constraint(rope));
+        state.remove_entity(swinging);
+        state.step(FRAME);
+        assert!(state.constraint_solver().get(rod).is_none());
+        assert_eq!(state.constraint_solver().len(), 1);
+        assert!(state.add_constraint(Constraint::Rope { a: swinging, b: climber, max_length: 1.0 }).is_none());
+    }
+    
+    #[test]
+    fn islands_sleep_and_wake_together() {
+        let mut state = WebGameState::headless(1);
+        let (a, b) = floating_pair(&mut state, 20.0);
+        let alone = state.add_entity(600.0, 600.0, 0.0, "Alone".to_string(), "Environment".to_string());
+        state.world_mut().get_mut::<WebPhysics>(alone).unwrap().use_gravity = false;
+        for _ in 0..120 {
+            state.step(FRAME);
+        }
+        assert!(state.is_sleeping(a) && state.is_sleeping(b) && state.is_sleeping(alone));
+        
+        state.apply_torque(alone, 100.0);
+        state.step(FRAME);
+        assert!(!state.is_sleeping(alone) && state.is_sleeping(a));
+        state.world_mut().get_mut::<WebTransform>(a).unwrap().velocity = Vector3::new(0.0, 50.0, 0.0);
+        state.step(FRAME);
+        assert!(!state.is_sleeping(a));
+        
+        state.set_sleeping_enabled(false);
+        for _ in 0..60 {
+            state.step(FRAME);
+        }
+        assert!(!state.is_sleeping(b) && !state.is_sleeping(alone));
+    }
+    
+    #[test]
+    fn resting_environment_stack_falls_asleep() {
+        let mut state = WebGameState::headless(1);
+        state.world_mut().clear();
+        state.set_boundary_mode(BoundaryMode::Clamp);
+        state.generate_environment(3);
+        let stack: Vec<EntityHandle> = state.world().query::<WebPhysics>().map(|(handle, _)| handle).collect();
+        for (level, &handle) in stack.iter().enumerate() {
+            let transform = state.world_mut().get_mut::<WebTransform>(handle).unwrap();
+            transform.position = Vector3::new(300.0, DEFAULT_COLLISION_RADIUS * (1.0 + 2.0 * level as f32), 0.0);
+        }
+        
+        // Still spinning at first, but the idle spin winds down
+        for _ in 0..60 {
+            state.step(FRAME);
+        }
+        assert!(stack.iter().all(|&handle| !state.is_sleeping(handle)));
+        for _ in 0..600 {
+            state.step(FRAME);
+        }
+        assert!(stack.iter().all(|&handle| state.is_sleeping(handle)));
+        assert_eq!(state.sleep_tracker().sleeping_count(), 3);
+    }
+    
+    #[test]
+    fn systems_can_change_the_schedule_while_running() {
+        let mut state = WebGameState::headless(1);
+        let runs = std::rc::Rc::new(std::cell::Cell::new(0));
+        let counted = runs.clone();
+        state.add_system("counted", Stage::PreUpdate, move |_, _| counted.set(counted.get() + 1));
+        state.add_system("once", Stage::PreUpdate, |state, _| {
+            assert_eq!(state.scheduler().is_enabled("once"), Some(true));
+            assert!(state.set_system_enabled("gravity", false));
+            assert!(state.remove_system("once"));
+        }).before("counted");
+        
+        state.step(FRAME);
+        state.step(FRAME);
+        assert_eq!(runs.get(), 2);
+        assert_eq!(state.scheduler().is_enabled("once"), None);
+        assert_eq!(state.scheduler().is_enabled("gravity"), Some(false));
+        
+        // A system replaced mid-stage keeps the replacement
+        state.add_system("replacer", Stage::PreUpdate, |state, _| {
+            state.add_system("replacer", Stage::PreUpdate, |state, _| state.set_paused(true));
+        });
+        state.step(FRAME);
+        state.step(FRAME);
+        assert!(state.is_paused());
+    }
+    
+    #[test]
+    fn bad_query_points_neither_hang_nor_overflow() {
+        let mut state = WebGameState::headless(1);
+        state.world_mut().clear();
+        let only = state.add_entity(100.0, 100.0, 0.0, "Only".to_string(), "Enemy".to_string());
+        
+        assert_eq!(state.nearest_entity(Vector3::new(f32::NAN, 0.0, 0.0), ""), None);
+        assert_eq!(state.nearest_entity(Vector3::new(0.0, f32::INFINITY, 0.0), ""), None);
+        assert_eq!(state.nearest_entity(Vector3::new(1e12, -1e12, 0.0), ""), Some(only));
+        assert_eq!(state.nearest_entity(Vector3::new(1e12, 0.0, 0.0), "Pickup"), None);
+        
+        // Corners past the i32 cell range saturate instead of wrapping
+        let grid = state.collision_system.spatial_grid();
+        assert_eq!(grid.query_box(Vector3::repeat(-1e12), Vector3::repeat(1e12)), vec![only]);
+        assert_eq!(grid.query_box(Vector3::repeat(f32::NAN), Vector3::repeat(1e12)), vec![only]);
+        assert!(grid.query_box(Vector3::repeat(1e12), Vector3::repeat(2e12)).is_empty());
+    }
+    
+    
+    #[test]
+    fn boundary_modes_at_the_right_edge() {
+        let bounds = WorldBounds { margin: 50.0, ..WorldBounds::default() };
+        let radius = 10.0;
+        let leave = |mode: BoundaryMode, x: f32| {
+            let mut transform = at(x, 100.0, 0.0);
+            transform.velocity = Vector3::new(200.0, 0.0, 0.0);
+            let kept = bounds.apply(mode, &mut transform, radius, 0.5);
+            (kept, transform)
+        };
+        let past_margin = CANVAS_WIDTH + 60.0;
+        
+        let (kept, wrapped) = leave(BoundaryMode::Wrap, past_margin);
+        assert!(kept);
+        assert_eq!(wrapped.position.x, -50.0);
+        assert_eq!(wrapped.previous_position, wrapped.position);
+        let (_, clamped) = leave(BoundaryMode::Clamp, past_margin);
+        assert_eq!((clamped.position.x, clamped.velocity.x), (CANVAS_WIDTH - radius, 0.0));
+        let (_, bounced) = leave(BoundaryMode::Bounce, past_margin);
+        assert_eq!((bounced.position.x, bounced.velocity.x), (CANVAS_WIDTH - radius, -100.0));
+        let (kept, unbounded) = leave(BoundaryMode::Unbounded, past_margin);
+        assert!(kept);
+        assert_eq!(unbounded.position.x, past_margin);
+        assert!(!leave(BoundaryMode::Destroy, past_margin).0);
+        
+        // Wrap and destroy wait until the body is past the margin
+        assert_eq!(leave(BoundaryMode::Wrap, CANVAS_WIDTH + 40.0).1.position.x, CANVAS_WIDTH + 40.0);
+        assert!(leave(BoundaryMode::Destroy, CANVAS_WIDTH + 40.0).0);
+        
+        // The engine removes a destroyed entity and reports it; its neighbour wraps
+        let mut state = WebGameState::headless(1);
+        let (a, b) = floating_pair(&mut state, 100.0);
+        state.set_boundary_mode(BoundaryMode::Wrap);
+        assert!(state.set_entity_boundary_mode(a, Some(BoundaryMode::Destroy)));
+        for handle in [a, b] {
+            state.world_mut().get_mut::<WebTransform>(handle).unwrap().position.x += 5000.0;
+        }
+        state.step(FRAME);
+        assert!(!state.is_entity_valid(a));
+        assert!(state.is_entity_valid(b));
+        assert_eq!(position(&state, b).x, -state.world_bounds().margin);
+        let died: Vec<EngineEvent> = state.drain_events().into_iter()
+            .filter(|event| matches!(event, EngineEvent::EntityDied { .. }))
+            .collect();
+        assert_eq!(died, vec![EngineEvent::EntityDied { entity: a }]);
+    }
+    
+    
+    #[test]
+    fn off_center_hits_spin_and_torque_integrates() {
+        let mut state = WebGameState::headless(1);
+        let (a, b) = floating_pair(&mut state, 60.0);
+        
+        // A sideways push 10 units off the center, along +Y, spins it negatively
+        let body = ContactBody::at(state.world(), a, Vector3::new(100.0, 310.0, 0.0)).unwrap();
+        let inverse_inertia = body.inverse_inertia;
+        body.apply_impulse(state.world_mut(), a, Vector3::new(5.0, 0.0, 0.0));
+        let physics = *state.world().get::<WebPhysics>(a).unwrap();
+        assert_eq!(state.world().get::<WebTransform>(a).unwrap().velocity, Vector3::new(5.0, 0.0, 0.0));
+        assert!((physics.angular_velocity - (-50.0 * inverse_inertia).to_degrees()).abs() < 1e-3, "{:?}", physics);
+        
+        // A ball striking a box off its center sets the box spinning
+        state.world_mut().get_mut::<WebTransform>(a).unwrap().velocity = Vector3::zeros();
+        assert!(state.set_angular_velocity(a, 0.0));
+        assert!(state.set_collider(a, ColliderShape::Obb { half_extents: Vector2::new(20.0, 20.0) }));
+        assert!(state.set_collider(b, ColliderShape::Circle { radius: 5.0 }));
+        let ball = state.world_mut().get_mut::<WebTransform>(b).unwrap();
+        ball.position.y += 15.0;
+        ball.velocity = Vector3::new(-300.0, 0.0, 0.0);
+        for _ in 0..10 {
+            state.step(FRAME);
+        }
+        let spin = state.world().get::<WebPhysics>(a).unwrap().angular_velocity;
+        assert!(spin > 1.0, "{}", spin);
+        
+        // One tick of torque adds torque / inertia over the tick, then stops
+        assert!(state.set_angular_velocity(a, 0.0));
+        assert!(state.set_angular_physics(a, 0.0, 2.0));
+        assert!(state.apply_torque(a, 4.0));
+        state.step(FRAME);
+        let expected = (2.0 * FRAME).to_degrees();
+        let spin = state.world().get::<WebPhysics>(a).unwrap().angular_velocity;
+        assert!((spin - expected).abs() < 1e-3, "{} vs {}", spin, expected);
+        state.step(FRAME);
+        assert_eq!(state.world().get::<WebPhysics>(a).unwrap().angular_velocity, spin);
+        
+        // Angular drag decays spin roughly exponentially
+        assert!(state.set_angular_physics(a, 0.5, 2.0));
+        assert!(state.set_angular_velocity(a, 100.0));
+        for _ in 0..60 {
+            state.step(FRAME);
+        }
+        let spin = state.world().get::<WebPhysics>(a).unwrap().angular_velocity;
+        assert!((spin - 100.0 * (-0.5f32).exp()).abs() < 1.0, "{}", spin);
+    }
+    
+    
+    #[test]
+    fn more_iterations_settle_deep_overlaps() {
+        // Five circles of radius 10 packed 8 apart, each overlapping its neighbours by 12
+        let remaining_overlap = |position_iterations: u32| {
+            let mut state = WebGameState::headless(1);
+            state.world_mut().clear();
+            state.set_physics_quality(Some(PhysicsQuality::new(1, position_iterations)));
+            let pile: Vec<EntityHandle> = (0..5)
+                .map(|i| state.add_entity(300.0 + i as f32 * 8.0, 300.0, 0.0, format!("Crate {}", i), "Environment".to_string()))
+                .collect();
+            for &handle in &pile {
+                assert!(state.set_collider(handle, ColliderShape::Circle { radius: 10.0 }));
+                let physics = state.world_mut().get_mut::<WebPhysics>(handle).unwrap();
+                physics.use_gravity = false;
+                physics.drag = 0.0;
+            }
+            
+            state.step(FRAME);
+            pile.windows(2)
+                .map(|pair| (20.0 - (position(&state, pair[1]) - position(&state, pair[0])).magnitude()).max(0.0))
+                .sum::<f32>()
+        };
+        
+        let (single, several) = (remaining_overlap(1), remaining_overlap(8));
+        assert!(several < single * 0.5, "{} vs {}", several, single);
+        
+        // Adaptive quality changes leave the physics preset alone
+        let mut state = WebGameState::headless(1);
+        let preset = state.physics_quality();
+        state.performance.quality_level = 0;
+        assert_eq!(state.physics_quality(), preset);
+        state.set_quality_level(0);
+        assert_eq!(state.physics_quality(), PhysicsQuality::for_level(0));
+    }
+    
+    
+    #[test]
+    fn materials_combine_and_set_mass() {
+        let surface = |friction: f32, restitution: f32, mode: CombineMode| PhysicsMaterial {
+            friction,
+            restitution,
+            friction_combine: mode,
+            restitution_combine: mode,
+            ..PhysicsMaterial::default()
+        };
+        let combined = |mode_a, mode_b| surface(0.2, 0.4, mode_a).combine(&surface(0.6, 0.8, mode_b));
+        let close = |(friction, restitution): (f32, f32), expected: (f32, f32)| {
+            (friction - expected.0).abs() < 1e-6 && (restitution - expected.1).abs() < 1e-6
+        };
+        assert!(close(combined(CombineMode::Average, CombineMode::Average), (0.4, 0.6)));
+        assert!(close(combined(CombineMode::Min, CombineMode::Min), (0.2, 0.4)));
+        assert!(close(combined(CombineMode::Multiply, CombineMode::Multiply), (0.12, 0.32)));
+        assert!(close(combined(CombineMode::Max, CombineMode::Max), (0.6, 0.8)));
+        
+        // Max outranks Multiply outranks Min outranks Average, from either side
+        assert!(close(combined(CombineMode::Average, CombineMode::Max), (0.6, 0.8)));
+        assert!(close(combined(CombineMode::Multiply, CombineMode::Min), (0.12, 0.32)));
+        assert!(close(combined(CombineMode::Min, CombineMode::Average), (0.2, 0.4)));
+        assert_eq!(surface(1.0, 0.9, CombineMode::Max).combine(&surface(1.0, 1.5, CombineMode::Average)).1, 1.0);
+        
+        // Density 1 with the default collider weighs 1; mass scales with area
+        let mud = PhysicsMaterial::mud();
+        assert!((mud.mass_for(&ColliderShape::Circle { radius: DEFAULT_COLLISION_RADIUS }) - 1.6).abs() < 1e-5);
+        let square = ColliderShape::Aabb { half_extents: Vector2::new(DEFAULT_COLLISION_RADIUS, DEFAULT_COLLISION_RADIUS) };
+        assert!((mud.mass_for(&square) - 1.6 * 4.0 / std::f32::consts::PI).abs() < 1e-5);
+        
+        let mut state = WebGameState::headless(1);
+        let (a, b) = floating_pair(&mut state, 100.0);
+        assert!(state.set_collider(a, square.clone()));
+        assert!(state.set_entity_material(a, "mud"));
+        let physics = state.world().get::<WebPhysics>(a).unwrap();
+        assert_eq!((physics.material, physics.mass), (mud, mud.mass_for(&square)));
+        assert!(!state.set_entity_material(a, "lava"));
+        
+        // Scene files assign by entity name or tag
+        assert_eq!(state.assign_material("B", "ice"), 1);
+        assert_eq!(state.world().get::<WebPhysics>(b).unwrap().material, PhysicsMaterial::ice());
+        assert_eq!(state.assign_material("Environment", "rubber"), 2);
+        assert_eq!(state.world().get::<WebPhysics>(a).unwrap().material, PhysicsMaterial::rubber());
+        assert_eq!(state.assign_material("Environment", "lava"), 0);
+        assert_eq!(state.assign_material("Nobody", "ice"), 0);
+    }
+    
+    
+    #[test]
+    fn model_matrices_compose_with_parents() {
+        let mut state = WebGameState::headless(1);
+        state.world_mut().clear();
+        let parent = state.add_entity(100.0, 50.0, 0.0, "Parent".to_string(), "Environment".to_string());
+        let child = state.add_entity(10.0, 0.0, 0.0, "Child".to_string(), "Environment".to_string());
+        assert!(state.set_entity_parent(child, Some(parent)));
+        let transform = state.world_mut().get_mut::<WebTransform>(parent).unwrap();
+        transform.rotation = 90.0;
+        transform.scale = Vector3::new(2.0, 3.0, 1.0);
+        
+        // Scale, then rotate, then translate, with the child inside its parent's space
+        let maps = |handle: EntityHandle, local: [f32; 2], expected: [f32; 2]| {
+            let point = state.model_matrix(handle).unwrap() * nalgebra::Vector4::new(local[0], local[1], 0.0, 1.0);
+            assert!((point.xy() - Vector2::from(expected)).magnitude() < 1e-4, "{:?} -> {:?}", local, point);
+        };
+        maps(parent, [0.0, 0.0], [100.0, 50.0]);
+        maps(parent, [1.0, 0.0], [100.0, 52.0]);
+        maps(parent, [0.0, 1.0], [97.0, 50.0]);
+        maps(child, [0.0, 0.0], [100.0, 70.0]);
+        maps(child, [1.0, 0.0], [100.0, 72.0]);
+        
+        // Previous transforms go through the parents' previous transforms too;
+        // layers put the parent first in draw order
+        assert!(state.set_entity_rendering(parent, 0, BlendMode::Normal, 0));
+        assert!(state.set_entity_rendering(child, 1, BlendMode::Normal, 0));
+        let transform = state.world_mut().get_mut::<WebTransform>(parent).unwrap();
+        transform.previous_position = Vector3::new(90.0, 50.0, 0.0);
+        transform.previous_rotation = 0.0;
+        let transform = state.world_mut().get_mut::<WebTransform>(child).unwrap();
+        transform.previous_position = Vector3::new(10.0, 0.0, 0.0);
+        transform.previous_rotation = 45.0;
+        assert_eq!(state.get_entity_previous_transform_data(), vec![90.0, 50.0, 0.0, 0.0, 110.0, 50.0, 0.0, 45.0]);
+    }
+    
+    
+    #[test]
+    fn batches_line_up_with_entity_data() {
+        let mut state = WebGameState::headless(1);
+        state.world_mut().clear();
+        let keys = [
+            (1, BlendMode::Normal, 2),
+            (0, BlendMode::Additive, 1),
+            (0, BlendMode::Normal, 1),
+            (0, BlendMode::Normal, 1),
+            (1, BlendMode::Normal, 2),
+            (0, BlendMode::Normal, 3),
+        ];
+        for (i, &(layer, blend_mode, texture_id)) in keys.iter().enumerate() {
+            let handle = state.add_entity(i as f32 * 10.0, 0.0, 0.0, format!("Sprite {}", i), "Environment".to_string());
+            assert!(state.set_entity_rendering(handle, layer, blend_mode, texture_id));
+            state.world_mut().get_mut::<WebRenderer>(handle).unwrap().color[0] = i as f32;
+        }
+        
+        let buffers = state.render_data();
+        assert_eq!(buffers.batches(), &[
+            0, 2, BlendMode::Normal as u32, 1,
+            2, 1, BlendMode::Normal as u32, 3,
+            3, 1, BlendMode::Additive as u32, 1,
+            4, 2, BlendMode::Normal as u32, 2,
+        ]);
+        
+        // Every entity in a batch has that batch's blend mode and texture, and
+        // its previous transform sits at the same index
+        for batch in buffers.batches().chunks(4) {
+            for index in batch[0]..batch[0] + batch[1] {
+                let index = index as usize;
+                let sprite = buffers.entities()[index * 20 + 16] as usize;
+                let (_, blend_mode, texture_id) = keys[sprite];
+                assert_eq!((blend_mode as u32, texture_id), (batch[2], batch[3]));
+                assert_eq!(buffers.entities()[index * 20 + 12], sprite as f32 * 10.0);
+                assert_eq!(buffers.previous_transforms()[index * 4], sprite as f32 * 10.0);
+            }
+        }
+        assert_eq!(buffers.entities().len(), keys.len() * 20);
+        assert_eq!(buffers.entities(), &state.get_entity_render_data()[..]);
+        assert_eq!(buffers.batches(), &state.get_render_batches()[..]);
+    }
+    
+    
+    #[test]
+    fn camera_follows_clamps_and_projects() {
+        let center = Vector3::new(CANVAS_WIDTH / 2.0, CANVAS_HEIGHT / 2.0, 0.0);
+        let close = |a: Vector3<f32>, b: Vector3<f32>| (a - b).magnitude() < 1e-3;
+        
+        // The target moves freely inside the dead zone, then drags the focus by the excess
+        let mut camera = WebCamera { dead_zone: Vector2::new(50.0, 50.0), follow_speed: 1000.0, ..WebCamera::default() };
+        camera.follow(center + Vector3::new(30.0, -40.0, 0.0), Vector3::zeros(), FRAME);
+        assert_eq!(camera.focus, center);
+        camera.follow(center + Vector3::new(100.0, -40.0, 0.0), Vector3::zeros(), FRAME);
+        assert!(close(camera.focus, center + Vector3::new(50.0, 0.0, 0.0)), "{:?}", camera.focus);
+        
+        // Look-ahead leads by the velocity; a slow follow closes only part of the gap
+        let mut camera = WebCamera { look_ahead: 0.5, follow_speed: 1000.0, ..WebCamera::default() };
+        camera.follow(center, Vector3::new(100.0, 0.0, 0.0), FRAME);
+        assert!(close(camera.focus, center + Vector3::new(50.0, 0.0, 0.0)), "{:?}", camera.focus);
+        let mut camera = WebCamera { follow_speed: 2.0, ..WebCamera::default() };
+        camera.follow(center + Vector3::new(0.0, 100.0, 0.0), Vector3::zeros(), 0.25);
+        assert!(close(camera.focus, center + Vector3::new(0.0, 50.0, 0.0)), "{:?}", camera.focus);
+        
+        // Orthographic extents are half the viewport over zoom
+        let mut camera = WebCamera { projection: Projection::Orthographic, viewport: Vector2::new(200.0, 100.0), ..WebCamera::default() };
+        assert_eq!(camera.half_extents(), Vector2::new(100.0, 50.0));
+        camera.zoom = 2.0;
+        assert_eq!(camera.half_extents(), Vector2::new(50.0, 25.0));
+        
+        // Clamping keeps the whole view inside the bounds, centering a view wider than the world
+        camera.zoom = 1.0;
+        let bounds = WorldBounds::default();
+        camera.focus = Vector3::new(10.0, 10.0, 0.0);
+        camera.clamp_to(&bounds);
+        assert_eq!(camera.focus, Vector3::new(100.0, 50.0, 0.0));
+        camera.focus = Vector3::new(CANVAS_WIDTH, CANVAS_HEIGHT, 0.0);
+        camera.clamp_to(&bounds);
+        assert_eq!(camera.focus, Vector3::new(CANVAS_WIDTH - 100.0, CANVAS_HEIGHT - 50.0, 0.0));
+        camera.viewport.x = CANVAS_WIDTH * 2.0;
+        camera.clamp_to(&bounds);
+        assert_eq!(camera.focus.x, CANVAS_WIDTH / 2.0);
+        
+        // Perspective extents at the focus follow the fov, aspect and zoom
+        let mut camera = WebCamera { fov: 90.0, viewport: Vector2::new(800.0, 400.0), ..WebCamera::default() };
+        assert!((camera.half_extents() - Vector2::new(1000.0, 500.0)).magnitude() < 1e-2, "{:?}", camera.half_extents());
+        assert!(camera.sees(camera.focus + Vector3::new(999.0, 0.0, 0.0), 0.0));
+        assert!(!camera.sees(camera.focus + Vector3::new(1001.0, 0.0, 0.0), 0.0));
+        camera.zoom = 2.0;
+        assert!((camera.half_extents() - Vector2::new(500.0, 250.0)).magnitude() < 1e-2, "{:?}", camera.half_extents());
+        
+        // +X is right and +Y is down on screen, as on the canvas
+        let clip = camera.projection_matrix() * camera.view_matrix();
+        let screen = |offset: Vector3<f32>| {
+            let point = clip * (camera.focus + offset).push(1.0);
+            point.xy() / point.w
+        };
+        assert!(screen(Vector3::zeros()).magnitude() < 1e-5);
+        let right = screen(Vector3::new(100.0, 0.0, 0.0));
+        assert!(right.x > 0.0 && right.y.abs() < 1e-5, "{:?}", right);
+        let down = screen(Vector3::new(0.0, 100.0, 0.0));
+        assert!(down.y < 0.0 && down.x.abs() < 1e-5, "{:?}", down);
+    }
+    
+    
+    #[test]
+    fn culling_keeps_only_what_the_camera_sees() {
+        let mut state = WebGameState::headless(1);
+        state.world_mut().clear();
+        state.set_boundary_mode(BoundaryMode::Unbounded);
+        *state.camera_mut() = WebCamera {
+            projection: Projection::Orthographic,
+            viewport: Vector2::new(200.0, 100.0),
+            focus: Vector3::new(400.0, 300.0, 0.0),
+            ..WebCamera::default()
+        };
+        
+        // The view spans x 300..500; colliders are the default radius 16
+        let mut spawn = |x: f32, y: f32, name: &str| {
+            let handle = state.add_entity(x, y, 0.0, name.to_string(), "Environment".to_string());
+            state.world_mut().remove::<WebPhysics>(handle);
+            handle
+        };
+        let centered = spawn(400.0, 300.0, "Centered");
+        let edge = spawn(520.0, 300.0, "Edge");      // Reaches in by its radius and the cull margin
+        let far = spawn(900.0, 300.0, "Far");
+        let scaled = spawn(1000.0, 300.0, "Scaled"); // Far off, but drawn 40 times larger
+        let parent = spawn(2000.0, 300.0, "Parent");
+        let child = spawn(-1600.0, 0.0, "Child");    // In view only once placed under its parent
+        let hidden = spawn(400.0, 300.0, "Hidden");
+        state.world_mut().get_mut::<WebTransform>(scaled).unwrap().scale = Vector3::new(40.0, 40.0, 1.0);
+        assert!(state.set_entity_parent(child, Some(parent)));
+        state.world_mut().get_mut::<WebRenderer>(hidden).unwrap().visible = false;
+        
+        state.step(FRAME);
+        let culler = state.view_culler();
+        assert_eq!((culler.visible_entities(), culler.culled_entities()), (4, 2));
+        for handle in [centered, edge, scaled, child] {
+            assert!(!culler.is_culled(handle), "{:?}", handle);
+        }
+        assert!(culler.is_culled(far) && culler.is_culled(parent));
+        
+        // Only the visible entities reach the render data, at their world positions
+        let mut drawn: Vec<f32> = state.get_entity_render_data().chunks(20).map(|entity| entity[12]).collect();
+        drawn.sort_by(f32::total_cmp);
+        assert_eq!(drawn, vec![400.0, 400.0, 520.0, 1000.0]);
+        
+        // Without culling everything shown is drawn
+        state.set_view_culling(false);
+        state.step(FRAME);
+        assert_eq!((state.view_culler().visible_entities(), state.view_culler().culled_entities()), (6, 0));
+        assert_eq!(state.get_entity_render_data().len(), 6 * 20);
+    }
+    
+}
+
+// === WASM ENGINE WRAPPER ===
+// Thin adapter: wires browser platform services into the core and converts
+// results into JS values. No simulation logic lives here.
+
+#[wasm_bindgen]
+pub struct WebGameEngine {
+    game_state: WebGameState,
+}
+
+#[wasm_bindgen]
+impl WebGameEngine {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WebGameEngine {
+        console_log!("Initializing WASM Web Game Engine v1.12");
+        std::panic::set_hook(Box::new(console_error_panic_hook::hook));
+        
+        WebGameEngine {
+            game_state: WebGameState::new(Platform::browser()),
+        }
+    }
+    
+    #[wasm_bindgen]
+    pub fn update_frame(&mut self, current_time: f64) {
+        self.game_state.update(current_time);
+    }
+    
+    #[wasm_bindgen]
+    pub fn handle_key(&mut self, key_code: u32, pressed: bool) {
+        self.game_state.handle_key_event(key_code, pressed);
+    }
+    
+    #[wasm_bindgen]
+    pub fn handle_mouse(&mut self, x: f32, y: f32, delta_x: f32, delta_y: f32) {
+        self.game_state.handle_mouse_event(x, y, delta_x, delta_y);
+    }
+    
+    #[wasm_bindgen]
+    pub fn handle_touch(&mut self, touches: Vec<f32>) {
+        self.game_state.handle_touch_event(touches);
+    }
+    
+    #[wasm_bindgen]
+    pub fn get_render_data(&self) -> JsValue {
+        let data = js_sys::Object::new();
+        // One pass, so the entity, previous-transform and batch arrays share a draw order
+        let buffers = self.game_state.render_data();
+        
+        js_sys::Reflect::set(&data, &"entities".into(), 
+                           &js_sys::Float32Array::from(buffers.entities()).into()).unwrap();
+        js_sys::Reflect::set(&data, &"previousTransforms".into(), 
+                           &js_sys::Float32Array::from(buffers.previous_transforms()).into()).unwrap();
+        js_sys::Reflect::set(&data, &"batches".into(), 
+                           &js_sys::Uint32Array::from(buffers.batches()).into()).unwrap();
+        js_sys::Reflect::set(&data, &"alpha".into(), 
+                           &self.game_state.get_interpolation_alpha().into()).unwrap();
+        js_sys::Reflect::set(&data, &"particles".into(), 
+                           &js_sys::Float32Array::from(buffers.particles()).into()).unwrap();
+        js_sys::Reflect::set(&data, &"camera".into(), 
+                           &js_sys::Float32Array::from(buffers.camera()).into()).unwrap();
+        
+        data.into()
+    }
+    
+    /// Refreshes the persistent render buffers; read them with the
+    /// `*_buffer_ptr`/`*_buffer_len` pairs as `Float32Array`s over wasm memory,
+    /// in the same layouts as `get_render_data`
+    #[wasm_bindgen]
+    pub fn update_render_buffers(&mut self) {
+        self.game_state.update_render_buffers();
+    }
+    
+    #[wasm_bindgen]
+    pub fn entity_buffer_ptr(&self) -> *const f32 {
+        self.game_state.render_buffers().entities().as_ptr()
+    }
+    
+    #[wasm_bindgen]
+    pub fn entity_buffer_len(&self) -> usize {
+        self.game_state.render_buffers().entities().len()
+    }
+    
+    #[wasm_bindgen]
+    pub fn previous_transform_buffer_ptr(&self) -> *const f32 {
+        self.game_state.render_buffers().previous_transforms().as_ptr()
+    }
+    
+    #[wasm_bindgen]
+    pub fn previous_transform_buffer_len(&self) -> usize {
+        self.game_state.render_buffers().previous_transforms().len()
+    }
+    
+    /// Read as a `Uint32Array`
+    #[wasm_bindgen]
+    pub fn batch_buffer_ptr(&self) -> *const u32 {
+        self.game_state.render_buffers().batches().as_ptr()
+    }
+    
+    #[wasm_bindgen]
+    pub fn batch_buffer_len(&self) -> usize {
+        self.game_state.render_buffers().batches().len()
+    }
+    
+    #[wasm_bindgen]
+    pub fn particle_buffer_ptr(&self) -> *const f32 {
+        self.game_state.render_buffers().particles().as_ptr()
+    }
+    
+    #[wasm_bindgen]
+    pub fn particle_buffer_len(&self) -> usize {
+        self.game_state.render_buffers().particles().len()
+    }
+    
+    #[wasm_bindgen]
+    pub fn camera_buffer_ptr(&self) -> *const f32 {
+        self.game_state.render_buffers().camera().as_ptr()
+    }
+    
+    #[wasm_bindgen]
+    pub fn camera_buffer_len(&self) -> usize {
+        self.game_state.render_buffers().camera().len()
+    }
+    
+    #[wasm_bindgen]
+    pub fn get_performance_info(&self) -> JsValue {
+        let info = js_sys::Object::new();
+        let performance = self.game_state.performance();
+        
+        js_sys::Reflect::set(&info, &"fps".into(), &performance.current_fps.into()).unwrap();
+        js_sys::Reflect::set(&info, &"frameTime".into(), &performance.average_frame_time_ms.into()).unwrap();
+        js_sys::Reflect::set(&info, &"qualityLevel".into(), &performance.quality_level.into()).unwrap();
+        js_sys::Reflect::set(&info, &"adaptiveQuality".into(), &performance.adaptive_quality.into()).unwrap();
+        let physics_quality = self.game_state.physics_quality();
+        js_sys::Reflect::set(&info, &"physicsSubsteps".into(), &physics_quality.substeps.into()).unwrap();
+        js_sys::Reflect::set(&info, &"positionIterations".into(), &physics_quality.position_iterations.into()).unwrap();
+        js_sys::Reflect::set(&info, &"entityCount".into(), &self.game_state.get_entity_count().into()).unwrap();
+        js_sys::Reflect::set(&info, &"particleCount".into(), &self.game_state.get_particle_count().into()).unwrap();
+        js_sys::Reflect::set(&info, &"droppedFrames".into(), &performance.dropped_frames.into()).unwrap();
+        js_sys::Reflect::set(&info, &"tickRate".into(), &self.game_state.timestep().tick_rate.into()).unwrap();
+        js_sys::Reflect::set(&info, &"droppedTicks".into(), &(self.game_state.timestep().dropped_ticks as f64).into()).unwrap();
+        js_sys::Reflect::set(&info, &"droppedEvents".into(), &(self.game_state.events().dropped_events as f64).into()).unwrap();
+        js_sys::Reflect::set(&info, &"sleepingBodies".into(), &(self.game_state.sleep_tracker().sleeping_count() as u32).into()).unwrap();
+        js_sys::Reflect::set(&info, &"islands".into(), &(self.game_state.sleep_tracker().island_count() as u32).into()).unwrap();
+        let culler = self.game_state.view_culler();
+        js_sys::Reflect::set(&info, &"visibleEntities".into(), &(culler.visible_entities() as u32).into()).unwrap();
+        js_sys::Reflect::set(&info, &"culledEntities".into(), &(culler.culled_entities() as u32).into()).unwrap();
+        js_sys::Reflect::set(&info, &"culledParticles".into(), &(culler.culled_particles() as u32).into()).unwrap();
+        
+        info.into()
+    }
+    
+    /// Drains queued events as `[{ type, ...fields }]`; handles are packed numbers
+    #[wasm_bindgen]
+    pub fn drain_events(&mut self) -> JsValue {
+        let events = js_sys::Array::new();
+        
+        for event in self.game_state.drain_events() {
+            let object = js_sys::Object::new();
+            js_sys::Reflect::set(&object, &"type".into(), &event.name().into()).unwrap();
+            
+            match event {
+                EngineEvent::CollisionStarted { a, b } | EngineEvent::CollisionEnded { a, b } => {
+                    js_sys::Reflect::set(&object, &"a".into(), &a.to_f64().into()).unwrap();
+                    js_sys::Reflect::set(&object, &"b".into(), &b.to_f64().into()).unwrap();
+                }
+                EngineEvent::EntityDied { entity } | EngineEvent::EntitySpawned { entity } => {
+                    js_sys::Reflect::set(&object, &"entity".into(), &entity.to_f64().into()).unwrap();
+                }
+                EngineEvent::QualityChanged { level } => {
+                    js_sys::Reflect::set(&object, &"level".into(), &level.into()).unwrap();
+                }
+                EngineEvent::Paused { paused } => {
+                    js_sys::Reflect::set(&object, &"paused".into(), &paused.into()).unwrap();
+                }
+                EngineEvent::TriggerEnter { trigger, other }
+                | EngineEvent::TriggerStay { trigger, other }
+                | EngineEvent::TriggerExit { trigger, other } => {
+                    js_sys::Reflect::set(&object, &"trigger".into(), &trigger.to_f64().into()).unwrap();
+                    js_sys::Reflect::set(&object, &"other".into(), &other.to_f64().into()).unwrap();
+                }
+            }
+            
+            events.push(&object);
+        }
+        
+        events.into()
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_paused(&mut self, paused: bool) {
+        self.game_state.set_paused(paused);
+    }
+    
+    #[wasm_bindgen]
+    pub fn get_score(&self) -> i32 {
+        self.game_state.get_score()
+    }
+    
+    #[wasm_bindgen]
+    pub fn get_entity_count(&self) -> usize {
+        self.game_state.get_entity_count()
+    }
+    
+    #[wasm_bindgen]
+    pub fn get_particle_count(&self) -> usize {
+        self.game_state.get_particle_count()
+    }
+    
+    #[wasm_bindgen]
+    pub fn get_fps(&self) -> f32 {
+        self.game_state.get_fps()
+    }
+    
+    #[wasm_bindgen]
+    pub fn get_frame_time(&self) -> f32 {
+        self.game_state.get_frame_time()
+    }
+    
+    #[wasm_bindgen]
+    pub fn get_quality_level(&self) -> u8 {
+        self.game_state.get_quality_level()
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_quality_level(&mut self, quality: u8) {
+        self.game_state.set_quality_level(quality);
+    }
+    
+    #[wasm_bindgen]
+    pub fn enable_adaptive_quality(&mut self, enabled: bool) {
+        self.game_state.enable_adaptive_quality(enabled);
+    }
+    
+    /// Pass 0 for either to go back to the preset of the last quality level set by hand
+    #[wasm_bindgen]
+    pub fn set_physics_quality(&mut self, substeps: u32, position_iterations: u32) {
+        let physics_quality = if substeps == 0 || position_iterations == 0 {
+            None
+        } else {
+            Some(PhysicsQuality::new(substeps, position_iterations))
+        };
+        self.game_state.set_physics_quality(physics_quality);
+    }
+    
+    #[wasm_bindgen]
+    pub fn create_explosion(&mut self, x: f32, y: f32, z: f32, intensity: f32) {
+        self.game_state.create_explosion(x, y, z, intensity);
+    }
+    
+    /// Returns the new entity's handle packed as a number (see `EntityHandle::to_f64`)
+    #[wasm_bindgen]
+    pub fn add_entity(&mut self, x: f32, y: f32, z: f32, name: String, tag: String) -> f64 {
+        self.game_state.add_entity(x, y, z, name, tag).to_f64()
     }
     
-    fn generate_environment(&mut self, count: usize) {
-        for i in 0..count {
-            let position = Vector3::new(
-                Math::random() as f32 * CANVAS_WIDTH,
-                Math::random() as f32 * CANVAS_HEIGHT,
-                0.0,
-            );
-            
-            let entity_id = self.create_entity(format!("Environment_{}", i), position);
-            
-            if let Some(entity) = self.entities.get_mut(&entity_id) {
-                entity.add_physics(WebPhysics {
-                    mass: 0.5 + Math::random() as f32 * 2.0,
-                    bounciness: 0.3 + Math::random() as f32 * 0.7,
-                    drag: 0.1 + Math::random() as f32 * 0.8,
-                    ..Default::default()
-                });
-                
-                entity.add_renderer(WebRenderer {
-                    color: [
-                        0.5 + Math::random() as f32 * 0.5,
-                        0.5 + Math::random() as f32 * 0.5,
-                        0.5 + Math::random() as f32 * 0.5,
-                        1.0,
-                    ],
-                    ..Default::default()
-                });
-                
-                entity.tag = "Environment".to_string();
-            }
-        }
+    #[wasm_bindgen]
+    pub fn remove_entity(&mut self, handle: f64) -> bool {
+        EntityHandle::from_f64(handle).is_some_and(|handle| self.game_state.remove_entity(handle))
     }
     
-    pub fn create_entity(&mut self, name: String, position: Vector3<f32>) -> u32 {
-        let id = self.next_entity_id;
-        self.next_entity_id += 1;
-        
-        let entity = WebEntity::new(id, name, position);
-        self.entities.insert(id, entity);
-        
-        id
+    #[wasm_bindgen]
+    pub fn is_entity_valid(&self, handle: f64) -> bool {
+        EntityHandle::from_f64(handle).is_some_and(|handle| self.game_state.is_entity_valid(handle))
     }
     
+    /// Player handle, or 0 (never a valid handle) when there is no player
     #[wasm_bindgen]
-    pub fn update(&mut self, current_time: f64) {
-        if self.paused {
-            return;
-        }
-        
-        let delta_time = self.performance.update(current_time) * self.time_scale;
-        
-        // Update input
-        let movement = self.input.get_movement_input();
-        if movement.magnitude() > 0.1 {
-            if let Some(player) = self.entities.get_mut(&1) {
-                let move_speed = 300.0;
-                player.transform.acceleration += movement * move_speed;
-            }
-        }
-        
-        // Update entities based on quality level
-        for entity in self.entities.values_mut() {
-            entity.update(delta_time);
+    pub fn get_player_handle(&self) -> f64 {
+        self.game_state.player_handle().map_or(0.0, |handle| handle.to_f64())
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_player_handle(&mut self, handle: f64) -> bool {
+        EntityHandle::from_f64(handle).is_some_and(|handle| self.game_state.set_player(handle))
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_seed(&mut self, seed: u32) {
+        self.game_state.set_seed(seed);
+    }
+    
+    #[wasm_bindgen]
+    pub fn get_seed(&self) -> u32 {
+        self.game_state.get_seed()
+    }
+    
+    /// Sets collision mass, bounciness and kinematic flag; adds physics if missing
+    #[wasm_bindgen]
+    pub fn set_entity_physics(&mut self, handle: f64, mass: f32, bounciness: f32, is_kinematic: bool) -> bool {
+        EntityHandle::from_f64(handle).is_some_and(|handle| {
+            self.game_state.set_entity_physics(handle, mass, bounciness, is_kinematic)
+        })
+    }
+    
+    /// Modes are "average", "min", "multiply" or "max"; false if either is unknown
+    #[wasm_bindgen]
+    pub fn define_physics_material(&mut self, name: String, friction: f32, restitution: f32, density: f32,
+                                   friction_combine: String, restitution_combine: String) -> bool {
+        let (Some(friction_combine), Some(restitution_combine)) = (CombineMode::from_name(&friction_combine), CombineMode::from_name(&restitution_combine)) else {
+            return false;
+        };
+        self.game_state.materials_mut().define(&name, PhysicsMaterial {
+            friction: friction.max(0.0),
+            restitution: restitution.clamp(0.0, 1.0),
+            density: density.max(0.0),
+            friction_combine,
+            restitution_combine,
+        });
+        true
+    }
+    
+    /// Defines every material in a scene file's `{ name: { friction, restitution,
+    /// density, frictionCombine, restitutionCombine } }` object; missing fields
+    /// take the default material's values. Returns how many were defined.
+    #[wasm_bindgen]
+    pub fn load_physics_materials(&mut self, materials: &JsValue) -> u32 {
+        if !materials.is_object() {
+            return 0;
         }
+        let defaults = PhysicsMaterial::default();
+        let number = |entry: &JsValue, key: &str, default: f32| {
+            js_sys::Reflect::get(entry, &key.into()).ok().and_then(|value| value.as_f64()).map_or(default, |value| value as f32)
+        };
+        let mode = |entry: &JsValue, key: &str, default: CombineMode| {
+            js_sys::Reflect::get(entry, &key.into()).ok()
+                .and_then(|value| value.as_string())
+                .and_then(|name| CombineMode::from_name(&name))
+                .unwrap_or(default)
+        };
         
-        // Update systems based on performance level
-        if self.performance.quality_level >= 1 {
-            if self.physics_enabled {
-                self.update_physics_system(delta_time);
+        let mut count = 0;
+        for name in js_sys::Object::keys(&js_sys::Object::from(materials.clone())).iter() {
+            let (Some(name), Ok(entry)) = (name.as_string(), js_sys::Reflect::get(materials, &name)) else {
+                continue;
+            };
+            if !entry.is_object() {
+                continue;
             }
             
-            let score_increment = self.collision_system.update(&mut self.entities);
-            self.score += score_increment;
-        }
-        
-        if self.performance.quality_level >= 2 {
-            self.particle_system.update(delta_time);
+            self.game_state.materials_mut().define(&name, PhysicsMaterial {
+                friction: number(&entry, "friction", defaults.friction).max(0.0),
+                restitution: number(&entry, "restitution", defaults.restitution).clamp(0.0, 1.0),
+                density: number(&entry, "density", defaults.density).max(0.0),
+                friction_combine: mode(&entry, "frictionCombine", defaults.friction_combine),
+                restitution_combine: mode(&entry, "restitutionCombine", defaults.restitution_combine),
+            });
+            count += 1;
         }
-        
-        // Update camera to follow player
-        if let Some(player) = self.entities.get(&1) {
-            let lerp_factor = 3.0 * delta_time;
-            let target = player.transform.position;
-            self.camera_target = self.camera_target.lerp(&target, lerp_factor);
+        count
+    }
+    
+    /// Applies a scene file's `{ nameOrTag: materialName }` object, giving each
+    /// material to every entity with that name or tag. Returns how many entities
+    /// got a material.
+    #[wasm_bindgen]
+    pub fn assign_physics_materials(&mut self, assignments: &JsValue) -> u32 {
+        if !assignments.is_object() {
+            return 0;
         }
         
-        // Cleanup dead entities
-        self.entities.retain(|_, entity| entity.is_alive());
-        
-        // Debug output
-        if self.debug_mode && self.performance.fps_counter % 60 == 0 {
-            console_log!("FPS: {:.1}, Entities: {}, Particles: {}, Quality: {}", 
-                        self.performance.current_fps, 
-                        self.entities.len(), 
-                        self.particle_system.particle_count(),
-                        self.performance.quality_level);
+        let mut count = 0;
+        for name_or_tag in js_sys::Object::keys(&js_sys::Object::from(assignments.clone())).iter() {
+            let material = js_sys::Reflect::get(assignments, &name_or_tag).ok().and_then(|value| value.as_string());
+            let (Some(name_or_tag), Some(material)) = (name_or_tag.as_string(), material) else {
+                continue;
+            };
+            count += self.game_state.assign_material(&name_or_tag, &material) as u32;
         }
+        count
     }
     
-    fn update_physics_system(&mut self, delta_time: f32) {
-        for entity in self.entities.values_mut() {
-            if !entity.active {
-                continue;
-            }
-            
-            if let Some(physics) = &entity.physics {
-                if !physics.is_kinematic && physics.use_gravity {
-                    entity.transform.acceleration += self.gravity;
-                }
-            }
-        }
+    /// Also sets the entity's mass from the material's density
+    #[wasm_bindgen]
+    pub fn set_entity_material(&mut self, handle: f64, name: String) -> bool {
+        EntityHandle::from_f64(handle).is_some_and(|handle| self.game_state.set_entity_material(handle, &name))
     }
     
-    // === WASM EXPORTS ===
+    /// Layer 0 (or mask 0) takes the entity out of collision entirely
+    #[wasm_bindgen]
+    pub fn set_collision_layers(&mut self, handle: f64, layer: u32, mask: u32) -> bool {
+        EntityHandle::from_f64(handle).is_some_and(|handle| self.game_state.set_collision_layers(handle, layer, mask))
+    }
     
+    /// For bullets and other bodies fast enough to pass through thin objects
     #[wasm_bindgen]
-    pub fn handle_key_event(&mut self, key_code: u32, pressed: bool) {
-        self.input.set_key(key_code, pressed);
-        
-        if pressed {
-            match key_code {
-                32 => { // Space
-                    self.paused = !self.paused;
-                    console_log!("Game {}", if self.paused { "paused" } else { "resumed" });
-                }
-                192 => { // Tilde (~)
-                    self.debug_mode = !self.debug_mode;
-                    console_log!("Debug mode {}", if self.debug_mode { "enabled" } else { "disabled" });
-                }
-                _ => {}
-            }
-        }
+    pub fn set_continuous_collision(&mut self, handle: f64, enabled: bool) -> bool {
+        EntityHandle::from_f64(handle).is_some_and(|handle| self.game_state.set_continuous_collision(handle, enabled))
     }
     
     #[wasm_bindgen]
-    pub fn handle_mouse_event(&mut self, x: f32, y: f32, delta_x: f32, delta_y: f32) {
-        self.input.set_mouse(x, y, delta_x, delta_y);
+    pub fn apply_torque(&mut self, handle: f64, torque: f32) -> bool {
+        EntityHandle::from_f64(handle).is_some_and(|handle| self.game_state.apply_torque(handle, torque))
     }
     
+    /// Spin in degrees per second; positive turns the same way as increasing rotation
     #[wasm_bindgen]
-    pub fn handle_touch_event(&mut self, touches: Vec<f32>) {
-        let touch_pairs: Vec<(f32, f32)> = touches
-            .chunks_exact(2)
-            .map(|chunk| (chunk[0], chunk[1]))
-            .collect();
-        self.input.set_touch(touch_pairs);
+    pub fn set_angular_velocity(&mut self, handle: f64, degrees_per_second: f32) -> bool {
+        EntityHandle::from_f64(handle).is_some_and(|handle| self.game_state.set_angular_velocity(handle, degrees_per_second))
     }
     
+    /// Angular drag and moment of inertia; pass inertia 0 to derive it from mass and collider
     #[wasm_bindgen]
-    pub fn get_score(&self) -> i32 {
-        self.score
+    pub fn set_angular_physics(&mut self, handle: f64, angular_drag: f32, inertia: f32) -> bool {
+        EntityHandle::from_f64(handle).is_some_and(|handle| self.game_state.set_angular_physics(handle, angular_drag, inertia))
     }
     
+    /// Rigid rod between two entities; a negative length keeps their current distance.
+    /// Returns the constraint id, or 0 if either handle is stale
     #[wasm_bindgen]
-    pub fn get_entity_count(&self) -> usize {
-        self.entities.len()
+    pub fn add_distance_constraint(&mut self, a: f64, b: f64, length: f32) -> u32 {
+        let (Some(a), Some(b)) = (EntityHandle::from_f64(a), EntityHandle::from_f64(b)) else {
+            return 0;
+        };
+        let length = if length < 0.0 { self.distance_between(a, b) } else { length };
+        self.add_constraint(Constraint::Distance { a, b, length })
     }
     
     #[wasm_bindgen]
-    pub fn get_particle_count(&self) -> usize {
-        self.particle_system.particle_count()
+    pub fn add_spring_constraint(&mut self, a: f64, b: f64, rest_length: f32, stiffness: f32, damping: f32) -> u32 {
+        let (Some(a), Some(b)) = (EntityHandle::from_f64(a), EntityHandle::from_f64(b)) else {
+            return 0;
+        };
+        let rest_length = if rest_length < 0.0 { self.distance_between(a, b) } else { rest_length };
+        self.add_constraint(Constraint::Spring { a, b, rest_length, stiffness: stiffness.max(0.0), damping: damping.max(0.0) })
     }
     
     #[wasm_bindgen]
-    pub fn get_fps(&self) -> f32 {
-        self.performance.current_fps
+    pub fn add_rope_constraint(&mut self, a: f64, b: f64, max_length: f32) -> u32 {
+        let (Some(a), Some(b)) = (EntityHandle::from_f64(a), EntityHandle::from_f64(b)) else {
+            return 0;
+        };
+        let max_length = if max_length < 0.0 { self.distance_between(a, b) } else { max_length };
+        self.add_constraint(Constraint::Rope { a, b, max_length })
     }
     
+    /// Hinges the entity on a world point at `length` away (0 holds it in place),
+    /// e.g. for a swinging hazard
     #[wasm_bindgen]
-    pub fn get_frame_time(&self) -> f32 {
-        self.performance.average_frame_time_ms
+    pub fn add_pin_constraint(&mut self, entity: f64, anchor_x: f32, anchor_y: f32, anchor_z: f32, length: f32) -> u32 {
+        let Some(entity) = EntityHandle::from_f64(entity) else {
+            return 0;
+        };
+        let anchor = Vector3::new(anchor_x, anchor_y, anchor_z);
+        self.add_constraint(Constraint::Pin { entity, anchor, length: length.max(0.0) })
     }
     
     #[wasm_bindgen]
-    pub fn get_quality_level(&self) -> u8 {
-        self.performance.quality_level
+    pub fn remove_constraint(&mut self, id: u32) -> bool {
+        self.game_state.remove_constraint(id)
     }
     
     #[wasm_bindgen]
-    pub fn set_quality_level(&mut self, quality: u8) {
-        self.performance.quality_level = quality.min(2);
-        self.performance.adaptive_quality = false;
-        console_log!("Quality manually set to {}", self.performance.quality_level);
+    pub fn wake_entity(&mut self, handle: f64) -> bool {
+        EntityHandle::from_f64(handle).is_some_and(|handle| self.game_state.wake_entity(handle))
     }
     
     #[wasm_bindgen]
-    pub fn enable_adaptive_quality(&mut self, enabled: bool) {
-        self.performance.adaptive_quality = enabled;
-        console_log!("Adaptive quality {}", if enabled { "enabled" } else { "disabled" });
+    pub fn set_can_sleep(&mut self, handle: f64, can_sleep: bool) -> bool {
+        EntityHandle::from_f64(handle).is_some_and(|handle| self.game_state.set_can_sleep(handle, can_sleep))
     }
     
     #[wasm_bindgen]
-    pub fn get_entity_render_data(&self) -> Vec<f32> {
-        let mut data = Vec::with_capacity(self.entities.len() * 16);
+    pub fn is_sleeping(&self, handle: f64) -> bool {
+        EntityHandle::from_f64(handle).is_some_and(|handle| self.game_state.is_sleeping(handle))
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_sleeping_enabled(&mut self, enabled: bool) {
+        self.game_state.set_sleeping_enabled(enabled);
+    }
+    
+    /// Off sends every entity and particle to the render data, on screen or not
+    #[wasm_bindgen]
+    pub fn set_view_culling(&mut self, enabled: bool) {
+        self.game_state.set_view_culling(enabled);
+    }
+    
+    /// Triggers report TriggerEnter/Stay/Exit events instead of colliding
+    #[wasm_bindgen]
+    pub fn set_trigger(&mut self, handle: f64, is_trigger: bool) -> bool {
+        EntityHandle::from_f64(handle).is_some_and(|handle| self.game_state.set_trigger(handle, is_trigger))
+    }
+    
+    /// First solid hit as `{ entity, point: [x, y, z], normal: [x, y, z], distance }`,
+    /// or null; `origin` and `direction` are `[x, y, z]`, and only colliders on
+    /// a layer in `mask` can be hit
+    #[wasm_bindgen]
+    pub fn raycast(&mut self, origin: Vec<f32>, direction: Vec<f32>, max_distance: f32, mask: u32) -> JsValue {
+        let (&[origin_x, origin_y, origin_z], &[direction_x, direction_y, direction_z]) = (origin.as_slice(), direction.as_slice()) else {
+            return JsValue::NULL;
+        };
+        let origin = Vector3::new(origin_x, origin_y, origin_z);
+        let direction = Vector3::new(direction_x, direction_y, direction_z);
         
-        for entity in self.entities.values() {
-            if !entity.active || !entity.renderer.as_ref().map_or(false, |r| r.visible) {
-                continue;
+        match self.game_state.raycast(origin, direction, max_distance, mask) {
+            Some(hit) => {
+                let object = js_sys::Object::new();
+                let point = js_sys::Array::of3(&hit.point.x.into(), &hit.point.y.into(), &hit.point.z.into());
+                let normal = js_sys::Array::of3(&hit.normal.x.into(), &hit.normal.y.into(), &hit.normal.z.into());
+                js_sys::Reflect::set(&object, &"entity".into(), &hit.entity.to_f64().into()).unwrap();
+                js_sys::Reflect::set(&object, &"point".into(), &point).unwrap();
+                js_sys::Reflect::set(&object, &"normal".into(), &normal).unwrap();
+                js_sys::Reflect::set(&object, &"distance".into(), &hit.distance.into()).unwrap();
+                object.into()
             }
-            
-            let transform = &entity.transform;
-            let renderer = entity.renderer.as_ref().unwrap();
-            
-            // Transform matrix (simplified 2D)
-            data.extend_from_slice(&[
-                transform.scale, 0.0, 0.0, transform.position.x,
-                0.0, transform.scale, 0.0, transform.position.y,
-                0.0, 0.0, transform.scale, transform.position.z,
-                renderer.color[0], renderer.color[1], renderer.color[2], 
-                renderer.color[3] * renderer.opacity,
-            ]);
+            None => JsValue::NULL,
         }
-        
-        data
     }
     
     #[wasm_bindgen]
-    pub fn get_particle_render_data(&self) -> Vec<f32> {
-        self.particle_system.get_render_data()
+    pub fn overlap_circle(&mut self, x: f32, y: f32, z: f32, radius: f32) -> Vec<f64> {
+        self.game_state.overlap_circle(Vector3::new(x, y, z), radius)
+            .into_iter()
+            .map(|handle| handle.to_f64())
+            .collect()
     }
     
+    /// Handle of the closest entity with `tag` (any entity if empty), or 0 if none
     #[wasm_bindgen]
-    pub fn get_camera_data(&self) -> Vec<f32> {
-        vec![
-            self.camera_position.x, self.camera_position.y, self.camera_position.z,
-            self.camera_target.x, self.camera_target.y, self.camera_target.z,
-            self.camera_fov,
-        ]
+    pub fn nearest_entity(&mut self, x: f32, y: f32, z: f32, tag: String) -> f64 {
+        self.game_state.nearest_entity(Vector3::new(x, y, z), &tag).map_or(0.0, |handle| handle.to_f64())
     }
     
+    /// World rectangle from its top-left corner and size; wrap and destroy
+    /// trigger `margin` past its edges
     #[wasm_bindgen]
-    pub fn create_explosion(&mut self, x: f32, y: f32, z: f32, intensity: f32) {
-        let position = Vector3::new(x, y, z);
-        self.particle_system.create_explosion(position, intensity);
+    pub fn set_world_bounds(&mut self, x: f32, y: f32, width: f32, height: f32, margin: f32) {
+        self.game_state.set_world_bounds(Vector2::new(x, y), Vector2::new(x + width, y + height), margin);
     }
     
+    /// "wrap", "clamp", "bounce", "destroy" or "unbounded"
     #[wasm_bindgen]
-    pub fn add_entity(&mut self, x: f32, y: f32, z: f32, name: String, tag: String) -> u32 {
-        let position = Vector3::new(x, y, z);
-        let entity_id = self.create_entity(name, position);
-        
-        if let Some(entity) = self.entities.get_mut(&entity_id) {
-            entity.tag = tag;
-            
-            entity.add_physics(WebPhysics::default());
-            entity.add_renderer(WebRenderer::default());
+    pub fn set_boundary_mode(&mut self, mode: String) -> bool {
+        match BoundaryMode::from_name(&mode) {
+            Some(mode) => {
+                self.game_state.set_boundary_mode(mode);
+                true
+            }
+            None => false,
         }
-        
-        entity_id
     }
     
+    /// Same modes as `set_boundary_mode`, or "world" to follow the world's mode again
     #[wasm_bindgen]
-    pub fn set_browser_capabilities(&mut self, 
-                                   webgl2: bool, 
-                                   hardware_accel: bool, 
-                                   is_mobile: bool, 
-                                   cpu_cores: u32) {
-        self.capabilities.webgl2_available = webgl2;
-        self.capabilities.hardware_acceleration = hardware_accel;
-        self.capabilities.is_mobile = is_mobile;
-        self.capabilities.cpu_cores = cpu_cores;
-        
-        // Adjust performance based on capabilities
-        if is_mobile || !hardware_accel {
-            self.performance.quality_level = 1; // Start with medium quality on mobile/slow devices
+    pub fn set_entity_boundary_mode(&mut self, handle: f64, mode: String) -> bool {
+        let mode = match mode.as_str() {
+            "world" => None,
+            name => match BoundaryMode::from_name(name) {
+                Some(mode) => Some(mode),
+                None => return false,
+            },
+        };
+        EntityHandle::from_f64(handle).is_some_and(|handle| self.game_state.set_entity_boundary_mode(handle, mode))
+    }
+    
+    /// Pass 0 as `parent` to detach
+    #[wasm_bindgen]
+    pub fn set_entity_parent(&mut self, handle: f64, parent: f64) -> bool {
+        let Some(handle) = EntityHandle::from_f64(handle) else {
+            return false;
+        };
+        if parent == 0.0 {
+            return self.game_state.set_entity_parent(handle, None);
         }
-        
-        console_log!("Browser capabilities updated: WebGL2={}, HW Accel={}, Mobile={}, Cores={}", 
-                    webgl2, hardware_accel, is_mobile, cpu_cores);
+        EntityHandle::from_f64(parent).is_some_and(|parent| self.game_state.set_entity_parent(handle, Some(parent)))
     }
     
+    /// Blend modes are "normal", "additive", "multiply" or "screen"
     #[wasm_bindgen]
-    pub fn get_performance_info(&self) -> JsValue {
-        let info = js_sys::Object::new();
-        
-        js_sys::Reflect::set(&info, &"fps".into(), &self.performance.current_fps.into()).unwrap();
-        js_sys::Reflect::set(&info, &"frameTime".into(), &self.performance.average_frame_time_ms.into()).unwrap();
-        js_sys::Reflect::set(&info, &"qualityLevel".into(), &self.performance.quality_level.into()).unwrap();
-        js_sys::Reflect::set(&info, &"adaptiveQuality".into(), &self.performance.adaptive_quality.into()).unwrap();
-        js_sys::Reflect::set(&info, &"entityCount".into(), &self.entities.len().into()).unwrap();
-        js_sys::Reflect::set(&info, &"particleCount".into(), &self.particle_system.particle_count().into()).unwrap();
-        js_sys::Reflect::set(&info, &"droppedFrames".into(), &self.performance.dropped_frames.into()).unwrap();
-        
-        info.into()
+    pub fn set_entity_rendering(&mut self, handle: f64, render_layer: u8, blend_mode: String, texture_id: u32) -> bool {
+        let Some(blend_mode) = BlendMode::from_name(&blend_mode) else {
+            return false;
+        };
+        EntityHandle::from_f64(handle).is_some_and(|handle| {
+            self.game_state.set_entity_rendering(handle, render_layer, blend_mode, texture_id)
+        })
     }
     
     #[wasm_bindgen]
-    pub fn reset_game(&mut self) {
-        console_log!("Resetting web game state");
-        
-        self.entities.clear();
-        self.next_entity_id = 1;
-        self.score = 0;
-        self.level = 1;
-        self.paused = false;
-        
-        self.particle_system = WebParticleSystem::new();
-        self.collision_system = WebCollisionSystem::new();
-        
-        self.initialize_scene();
+    pub fn set_entity_scale(&mut self, handle: f64, x: f32, y: f32, z: f32) -> bool {
+        EntityHandle::from_f64(handle).is_some_and(|handle| self.game_state.set_entity_scale(handle, Vector3::new(x, y, z)))
     }
     
     #[wasm_bindgen]
-    pub fn cleanup(&mut self) {
-        console_log!("Cleaning up Web Game Engine v1.12");
-        self.entities.clear();
-        self.particle_system = WebParticleSystem::new();
+    pub fn set_collider_circle(&mut self, handle: f64, radius: f32) -> bool {
+        self.set_collider(handle, ColliderShape::Circle { radius })
     }
-}
-
-// === WASM ENGINE WRAPPER ===
-
-#[wasm_bindgen]
-pub struct WebGameEngine {
-    game_state: WebGameState,
-}
-
-#[wasm_bindgen]
-impl WebGameEngine {
-    #[wasm_bindgen(constructor)]
-    pub fn new() -> WebGameEngine {
-        console_log!("Initializing WASM Web Game Engine v1.12");
-        std::panic::set_hook(Box::new(console_error_panic_hook::hook));
+    
+    /// Axis-aligned box, or one that turns with the entity when `oriented`
+    #[wasm_bindgen]
+    pub fn set_collider_box(&mut self, handle: f64, half_width: f32, half_height: f32, oriented: bool) -> bool {
+        let half_extents = Vector2::new(half_width, half_height);
+        if oriented {
+            self.set_collider(handle, ColliderShape::Obb { half_extents })
+        } else {
+            self.set_collider(handle, ColliderShape::Aabb { half_extents })
+        }
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_collider_capsule(&mut self, handle: f64, half_length: f32, radius: f32) -> bool {
+        self.set_collider(handle, ColliderShape::Capsule { half_length, radius })
+    }
+    
+    /// Convex polygon from local [x0, y0, x1, y1, ...] points; false for
+    /// fewer than three points, collinear points or a concave outline
+    #[wasm_bindgen]
+    pub fn set_collider_polygon(&mut self, handle: f64, points: Vec<f32>) -> bool {
+        if points.len() < 6 || !points.len().is_multiple_of(2) {
+            return false;
+        }
         
-        WebGameEngine {
-            game_state: WebGameState::new(),
+        let points = points.chunks_exact(2).map(|chunk| Vector2::new(chunk[0], chunk[1])).collect();
+        self.set_collider(handle, ColliderShape::Polygon { points })
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_collider_sphere(&mut self, handle: f64, radius: f32) -> bool {
+        self.set_collider(handle, ColliderShape::Sphere { radius })
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_collider_cuboid(&mut self, handle: f64, half_x: f32, half_y: f32, half_z: f32) -> bool {
+        self.set_collider(handle, ColliderShape::Cuboid { half_extents: Vector3::new(half_x, half_y, half_z) })
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_system_enabled(&mut self, name: String, enabled: bool) -> bool {
+        self.game_state.set_system_enabled(&name, enabled)
+    }
+    
+    #[wasm_bindgen]
+    pub fn get_system_order(&self) -> Vec<String> {
+        self.game_state.scheduler().execution_order()
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_tick_rate(&mut self, tick_rate: f32) {
+        self.game_state.set_tick_rate(tick_rate);
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_max_catch_up_steps(&mut self, steps: u32) {
+        self.game_state.set_max_catch_up_steps(steps);
+    }
+    
+    #[wasm_bindgen]
+    pub fn get_interpolation_alpha(&self) -> f32 {
+        self.game_state.get_interpolation_alpha()
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_browser_capabilities(&mut self, webgl2: bool, hardware_accel: bool, is_mobile: bool, cpu_cores: u32) {
+        self.game_state.set_browser_capabilities(webgl2, hardware_accel, is_mobile, cpu_cores);
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_canvas_size(&mut self, width: f32, height: f32) {
+        self.game_state.set_canvas_size(width, height);
+    }
+    
+    /// "perspective" or "orthographic"
+    #[wasm_bindgen]
+    pub fn set_camera_projection(&mut self, projection: String) -> bool {
+        match Projection::from_name(&projection) {
+            Some(projection) => {
+                self.game_state.camera_mut().projection = projection;
+                true
+            }
+            None => false,
         }
     }
     
+    /// Above 1 magnifies; narrows the field of view in perspective mode
     #[wasm_bindgen]
-    pub fn update_frame(&mut self, current_time: f64) {
-        self.game_state.update(current_time);
+    pub fn set_camera_zoom(&mut self, zoom: f32) {
+        self.game_state.camera_mut().zoom = zoom.max(MIN_CAMERA_ZOOM);
     }
     
     #[wasm_bindgen]
-    pub fn handle_key(&mut self, key_code: u32, pressed: bool) {
-        self.game_state.handle_key_event(key_code, pressed);
+    pub fn set_camera_fov(&mut self, degrees: f32) {
+        self.game_state.camera_mut().fov = degrees.clamp(1.0, 179.0);
     }
     
+    /// Dead zone is the full width and height the player moves in before the
+    /// camera follows; look-ahead is in seconds of the player's velocity
     #[wasm_bindgen]
-    pub fn handle_mouse(&mut self, x: f32, y: f32, delta_x: f32, delta_y: f32) {
-        self.game_state.handle_mouse_event(x, y, delta_x, delta_y);
+    pub fn set_camera_follow(&mut self, speed: f32, dead_zone_width: f32, dead_zone_height: f32, look_ahead: f32) {
+        let camera = self.game_state.camera_mut();
+        camera.follow_speed = speed.max(0.0);
+        camera.dead_zone = Vector2::new(dead_zone_width.max(0.0), dead_zone_height.max(0.0)) * 0.5;
+        camera.look_ahead = look_ahead.max(0.0);
     }
     
     #[wasm_bindgen]
-    pub fn handle_touch(&mut self, touches: Vec<f32>) {
-        self.game_state.handle_touch_event(touches);
+    pub fn set_camera_clamp_to_bounds(&mut self, enabled: bool) {
+        self.game_state.camera_mut().clamp_to_bounds = enabled;
     }
     
+    /// Jumps the camera to look at (x, y) without easing
     #[wasm_bindgen]
-    pub fn get_render_data(&self) -> JsValue {
-        let data = js_sys::Object::new();
-        
-        let entities = self.game_state.get_entity_render_data();
-        js_sys::Reflect::set(&data, &"entities".into(), 
-                           &js_sys::Float32Array::from(&entities[..]).into()).unwrap();
-        
-        let particles = self.game_state.get_particle_render_data();
-        js_sys::Reflect::set(&data, &"particles".into(), 
-                           &js_sys::Float32Array::from(&particles[..]).into()).unwrap();
-        
-        let camera = self.game_state.get_camera_data();
-        js_sys::Reflect::set(&data, &"camera".into(), 
-                           &js_sys::Float32Array::from(&camera[..]).into()).unwrap();
-        
-        data.into()
+    pub fn set_camera_focus(&mut self, x: f32, y: f32) {
+        let camera = self.game_state.camera_mut();
+        camera.focus.x = x;
+        camera.focus.y = y;
     }
     
-    #[wasm_bindgen]
-    pub fn get_performance_info(&self) -> JsValue {
-        self.game_state.get_performance_info()
+    fn set_collider(&mut self, handle: f64, shape: ColliderShape) -> bool {
+        EntityHandle::from_f64(handle).is_some_and(|handle| self.game_state.set_collider(handle, shape))
     }
     
-    #[wasm_bindgen]
-    pub fn set_browser_capabilities(&mut self, webgl2: bool, hardware_accel: bool, is_mobile: bool, cpu_cores: u32) {
-        self.game_state.set_browser_capabilities(webgl2, hardware_accel, is_mobile, cpu_cores);
+    fn add_constraint(&mut self, constraint: Constraint) -> u32 {
+        self.game_state.add_constraint(constraint).unwrap_or(0)
+    }
+    
+    fn distance_between(&self, a: EntityHandle, b: EntityHandle) -> f32 {
+        let world = self.game_state.world();
+        match (world.get::<WebTransform>(a), world.get::<WebTransform>(b)) {
+            (Some(transform_a), Some(transform_b)) => (transform_b.position - transform_a.position).magnitude(),
+            _ => 0.0,
+        }
     }
     
     #[wasm_bindgen]
@@ -603,7 +5458,9 @@ impl WebGameEngine {
 pub fn main() {
     console_log!("WASM Web Game Engine v1.12 Enhanced Edition loaded successfully!");
     console_log!("Features: WebGL Optimized, Adaptive Quality, Mobile Support, Performance Monitoring");
-} WEB-SPECIFIC CONSTANTS ===
+}
+
+// === WEB-SPECIFIC CONSTANTS ===
 const MAX_ENTITIES: usize = 2000;        // Optimized for web
 const MAX_PARTICLES: usize = 5000;       // WebGL friendly
 const MAX_LIGHTS: usize = 25;            // WebGL shader limit
@@ -612,6 +5469,24 @@ const CANVAS_HEIGHT: f32 = 1080.0;
 const TARGET_FPS: f32 = 60.0;
 const MAX_FRAME_TIME_MS: f32 = 16.67;    // 60 FPS budget
 const QUALITY_ADJUSTMENT_COOLDOWN: u32 = 60; // Frames
+const DEFAULT_TICK_RATE: f32 = 60.0;     // Fixed simulation steps per second
+const MAX_CATCH_UP_STEPS: u32 = 5;       // Ticks per frame before dropping time
+const GENERATION_BITS: u32 = 21;         // Index (32) + generation (21) fits a JS number exactly
+const MAX_QUEUED_EVENTS: usize = 4096;   // Undrained events before the oldest are dropped
+const DEFAULT_COLLISION_RADIUS: f32 = 16.0; // Also used for entities without physics
+const DEFAULT_COLLISION_LAYER: u32 = 1;
+const ALL_COLLISION_LAYERS: u32 = u32::MAX;
+const CCD_CONTACT_SLOP: f32 = 0.5;       // Penetration left by sweeps so contacts still resolve
+const DEFAULT_CONSTRAINT_ITERATIONS: u32 = 8;
+const SLEEP_LINEAR_THRESHOLD: f32 = 10.0;  // Pixels per second
+const SLEEP_ANGULAR_THRESHOLD: f32 = 10.0; // Degrees per second
+const SLEEP_DELAY: f32 = 0.5;            // Seconds at rest before an island sleeps
+const ENVIRONMENT_ANGULAR_DRAG: f32 = 0.5; // Slows the idle spin below the sleep threshold in about 3 seconds
+const MAX_SUBSTEPS: u32 = 8;
+const MIN_CAMERA_ZOOM: f32 = 0.01;
+const CULL_MARGIN: f32 = 8.0;            // Slack for render interpolation between ticks
+const MAX_HIERARCHY_DEPTH: usize = 32;   // Parent links followed when building model matrices
+const MAX_POSITION_ITERATIONS: u32 = 16;
 
 // === WEB BROWSER DETECTION ===
 
@@ -651,8 +5526,33 @@ pub struct WebTransform {
     pub position: Vector3<f32>,
     pub velocity: Vector3<f32>,
     pub acceleration: Vector3<f32>,
-    pub rotation: f32,        // Single Y-axis rotation for web performance
-    pub scale: f32,          // Uniform scale
+    pub rotation: f32,        // Single in-plane (Z-axis) rotation in degrees for web performance
+    pub scale: Vector3<f32>, // Per axis; rendering only, colliders keep their own size
+    
+    // State at the start of the current fixed tick, for render interpolation
+    pub previous_position: Vector3<f32>,
+    pub previous_rotation: f32,
+}
+
+impl WebTransform {
+    pub fn at(position: Vector3<f32>) -> Self {
+        Self {
+            position,
+            previous_position: position,
+            ..Default::default()
+        }
+    }
+    
+    /// Scale, then rotate about Z, then translate; relative to the parent, if any
+    pub fn local_matrix(&self) -> Matrix4<f32> {
+        let rotation = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), self.rotation.to_radians());
+        Matrix4::new_translation(&self.position) * rotation.to_homogeneous() * Matrix4::new_nonuniform_scaling(&self.scale)
+    }
+    
+    /// `local_matrix` as of the start of the current tick
+    pub fn previous_local_matrix(&self) -> Matrix4<f32> {
+        Self { position: self.previous_position, rotation: self.previous_rotation, ..*self }.local_matrix()
+    }
 }
 
 impl Default for WebTransform {
@@ -662,7 +5562,9 @@ impl Default for WebTransform {
             velocity: Vector3::zeros(),
             acceleration: Vector3::zeros(),
             rotation: 0.0,
-            scale: 1.0,
+            scale: Vector3::new(1.0, 1.0, 1.0),
+            previous_position: Vector3::zeros(),
+            previous_rotation: 0.0,
         }
     }
 }
@@ -671,10 +5573,20 @@ impl Default for WebTransform {
 pub struct WebPhysics {
     pub mass: f32,
     pub drag: f32,
-    pub bounciness: f32,
+    pub material: PhysicsMaterial,
     pub is_kinematic: bool,
     pub use_gravity: bool,
     pub collision_radius: f32,
+    pub collision_layer: u32, // Layers this body is on
+    pub collision_mask: u32,  // Layers this body collides with
+    pub continuous_collision: bool, // Sweep fast motion so it can't tunnel through thin objects
+    pub angular_velocity: f32,      // Degrees per second about Z, like `WebTransform::rotation`
+    pub angular_drag: f32,
+    pub inertia: Option<f32>,       // Moment of inertia; derived from mass and collider when None
+    pub torque: f32,                // Accumulated until the next integration step
+    pub can_sleep: bool,
+    pub sleeping: bool,             // At rest: skipped until woken by a contact, force or velocity change
+    pub sleep_timer: f32,           // Seconds spent below the sleep thresholds
 }
 
 impl Default for WebPhysics {
@@ -682,10 +5594,51 @@ impl Default for WebPhysics {
         Self {
             mass: 1.0,
             drag: 0.02,
-            bounciness: 0.5,
+            material: PhysicsMaterial::default(),
             is_kinematic: false,
             use_gravity: true,
-            collision_radius: 16.0,
+            collision_radius: DEFAULT_COLLISION_RADIUS,
+            collision_layer: DEFAULT_COLLISION_LAYER,
+            collision_mask: ALL_COLLISION_LAYERS,
+            continuous_collision: false,
+            angular_velocity: 0.0,
+            angular_drag: 0.05,
+            inertia: None,
+            torque: 0.0,
+            can_sleep: true,
+            sleeping: false,
+            sleep_timer: 0.0,
+        }
+    }
+}
+
+impl WebPhysics {
+    /// Both bodies must accept each other; a zero layer or mask never collides
+    pub fn collides_with(&self, other: &WebPhysics) -> bool {
+        (self.collision_layer & other.collision_mask) != 0 && (other.collision_layer & self.collision_mask) != 0
+    }
+    
+    /// Zero for kinematic or massless bodies, which collisions cannot move
+    pub fn inverse_mass(&self) -> f32 {
+        if self.is_kinematic || self.mass <= 0.0 {
+            0.0
+        } else {
+            1.0 / self.mass
+        }
+    }
+    
+    pub fn wake(&mut self) {
+        self.sleeping = false;
+        self.sleep_timer = 0.0;
+    }
+    
+    /// Zero for bodies collisions cannot turn; `shape` is the body's collider
+    pub fn inverse_inertia(&self, shape: &ColliderShape) -> f32 {
+        let inertia = self.inertia.unwrap_or(self.mass * shape.unit_inertia());
+        if self.is_kinematic || self.mass <= 0.0 || inertia <= 0.0 {
+            0.0
+        } else {
+            1.0 / inertia
         }
     }
 }
@@ -700,12 +5653,25 @@ pub struct WebRenderer {
     pub blend_mode: BlendMode,
 }
 
-#[derive(Debug, Clone)]
+// Batch tables report the mode as its discriminant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum BlendMode {
-    Normal,
-    Additive,
-    Multiply,
-    Screen,
+    Normal = 0,
+    Additive = 1,
+    Multiply = 2,
+    Screen = 3,
+}
+
+impl BlendMode {
+    pub fn from_name(name: &str) -> Option<BlendMode> {
+        match name {
+            "normal" => Some(BlendMode::Normal),
+            "additive" => Some(BlendMode::Additive),
+            "multiply" => Some(BlendMode::Multiply),
+            "screen" => Some(BlendMode::Screen),
+            _ => None,
+        }
+    }
 }
 
 impl Default for WebRenderer {
@@ -740,109 +5706,429 @@ impl Default for WebHealth {
     }
 }
 
-// === WEB ENTITY SYSTEM ===
+impl WebHealth {
+    pub fn new(max_health: f32) -> Self {
+        Self {
+            current: max_health,
+            max: max_health,
+            ..Default::default()
+        }
+    }
+}
 
-#[wasm_bindgen]
-#[derive(Debug, Clone)]
-pub struct WebEntity {
-    pub id: u32,
-    pub name: String,
-    pub tag: String,
-    pub active: bool,
+/// Per-entity override of the world's boundary mode
+#[derive(Debug, Clone, Copy)]
+pub struct WebBoundary {
+    pub mode: BoundaryMode,
+}
+
+/// Renders the entity relative to `parent`'s transform. Rendering only: physics
+/// and collisions keep using the entity's own transform as world space, and a
+/// despawned parent leaves the child at its own transform.
+#[derive(Debug, Clone, Copy)]
+pub struct WebParent {
+    pub parent: EntityHandle,
+}
+
+// === ENTITY HANDLES ===
+
+// Slot index plus the slot's generation at allocation time. Freeing a slot bumps
+// its generation, so handles to removed entities never match a new occupant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EntityHandle {
+    pub index: u32,
+    pub generation: u32,
+}
+
+impl EntityHandle {
+    /// Packs the handle into a JS-safe integer: `generation * 2^32 + index`
+    pub fn to_f64(self) -> f64 {
+        ((self.generation as u64) << 32 | self.index as u64) as f64
+    }
+    
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if value.is_nan() || value < 0.0 || value.fract() != 0.0 || value >= (1u64 << (32 + GENERATION_BITS)) as f64 {
+            return None;
+        }
+        
+        let bits = value as u64;
+        Some(Self {
+            index: bits as u32,
+            generation: (bits >> 32) as u32,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct EntityAllocator {
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    free_list: Vec<u32>,
+}
+
+impl EntityAllocator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn allocate(&mut self) -> EntityHandle {
+        if let Some(index) = self.free_list.pop() {
+            self.alive[index as usize] = true;
+            return EntityHandle { index, generation: self.generations[index as usize] };
+        }
+        
+        // Generations start at 1 so a packed value of 0 is never a live handle
+        let index = self.generations.len() as u32;
+        self.generations.push(1);
+        self.alive.push(true);
+        EntityHandle { index, generation: 1 }
+    }
+    
+    pub fn free(&mut self, handle: EntityHandle) -> bool {
+        if !self.is_alive(handle) {
+            return false;
+        }
+        
+        let slot = handle.index as usize;
+        let next_generation = (self.generations[slot] + 1) & ((1 << GENERATION_BITS) - 1);
+        self.generations[slot] = next_generation.max(1);
+        self.alive[slot] = false;
+        self.free_list.push(handle.index);
+        true
+    }
     
-    // Core components
-    pub transform: WebTransform,
-    pub physics: Option<WebPhysics>,
-    pub renderer: Option<WebRenderer>,
-    pub health: Option<WebHealth>,
+    pub fn is_alive(&self, handle: EntityHandle) -> bool {
+        let slot = handle.index as usize;
+        slot < self.generations.len() && self.alive[slot] && self.generations[slot] == handle.generation
+    }
+}
+
+// === ECS WORLD ===
+
+// Components live in one sparse set per type: `sparse` maps an entity's slot
+// index to its position in the densely packed `handles`/`data` arrays, so
+// systems walk contiguous memory and any 'static type can be a component.
+
+const EMPTY_SLOT: u32 = u32::MAX;
+
+#[derive(Debug)]
+pub struct SparseSet<T> {
+    sparse: Vec<u32>,
+    handles: Vec<EntityHandle>,
+    data: Vec<T>,
 }
 
-impl WebEntity {
-    pub fn new(id: u32, name: String, position: Vector3<f32>) -> Self {
+impl<T> Default for SparseSet<T> {
+    fn default() -> Self {
         Self {
-            id,
-            name,
-            tag: "Default".to_string(),
-            active: true,
-            transform: WebTransform {
-                position,
-                ..Default::default()
-            },
-            physics: None,
-            renderer: None,
-            health: None,
+            sparse: Vec::new(),
+            handles: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+}
+
+impl<T> SparseSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            sparse: Vec::with_capacity(capacity),
+            handles: Vec::with_capacity(capacity),
+            data: Vec::with_capacity(capacity),
+        }
+    }
+    
+    fn dense_index(&self, handle: EntityHandle) -> Option<usize> {
+        let dense = *self.sparse.get(handle.index as usize)?;
+        if dense != EMPTY_SLOT && self.handles[dense as usize] == handle {
+            Some(dense as usize)
+        } else {
+            None
+        }
+    }
+    
+    pub fn insert(&mut self, handle: EntityHandle, value: T) {
+        let slot = handle.index as usize;
+        if slot >= self.sparse.len() {
+            self.sparse.resize(slot + 1, EMPTY_SLOT);
+        }
+        
+        // Replace in place, including a leftover from an older generation
+        let dense = self.sparse[slot];
+        if dense != EMPTY_SLOT {
+            self.handles[dense as usize] = handle;
+            self.data[dense as usize] = value;
+            return;
+        }
+        
+        self.sparse[slot] = self.data.len() as u32;
+        self.handles.push(handle);
+        self.data.push(value);
+    }
+    
+    pub fn remove(&mut self, handle: EntityHandle) -> Option<T> {
+        let dense = self.dense_index(handle)?;
+        
+        // Swap-remove keeps the arrays packed; patch the moved entity's slot
+        let value = self.data.swap_remove(dense);
+        self.handles.swap_remove(dense);
+        if let Some(moved) = self.handles.get(dense) {
+            self.sparse[moved.index as usize] = dense as u32;
         }
+        self.sparse[handle.index as usize] = EMPTY_SLOT;
+        
+        Some(value)
+    }
+    
+    pub fn get(&self, handle: EntityHandle) -> Option<&T> {
+        self.dense_index(handle).map(|dense| &self.data[dense])
+    }
+    
+    pub fn get_mut(&mut self, handle: EntityHandle) -> Option<&mut T> {
+        self.dense_index(handle).map(move |dense| &mut self.data[dense])
+    }
+    
+    pub fn contains(&self, handle: EntityHandle) -> bool {
+        self.dense_index(handle).is_some()
+    }
+    
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+    
+    pub fn handles(&self) -> &[EntityHandle] {
+        &self.handles
+    }
+    
+    pub fn iter(&self) -> impl Iterator<Item = (EntityHandle, &T)> {
+        self.handles.iter().copied().zip(self.data.iter())
+    }
+    
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (EntityHandle, &mut T)> {
+        self.handles.iter().copied().zip(self.data.iter_mut())
+    }
+    
+    pub fn clear(&mut self) {
+        self.sparse.clear();
+        self.handles.clear();
+        self.data.clear();
+    }
+}
+
+// Type-erased view so the world can despawn across every storage
+pub trait ComponentStorage {
+    fn remove_entity(&mut self, handle: EntityHandle);
+    fn clear(&mut self);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: 'static> ComponentStorage for SparseSet<T> {
+    fn remove_entity(&mut self, handle: EntityHandle) {
+        self.remove(handle);
+    }
+    
+    fn clear(&mut self) {
+        SparseSet::clear(self);
     }
     
-    pub fn add_physics(&mut self, physics: WebPhysics) -> &mut Self {
-        self.physics = Some(physics);
+    fn as_any(&self) -> &dyn Any {
         self
     }
     
-    pub fn add_renderer(&mut self, renderer: WebRenderer) -> &mut Self {
-        self.renderer = Some(renderer);
+    fn as_any_mut(&mut self) -> &mut dyn Any {
         self
     }
+}
+
+#[derive(Default)]
+pub struct World {
+    allocator: EntityAllocator,
+    live: SparseSet<()>,
+    storages: HashMap<TypeId, Box<dyn ComponentStorage>>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
     
-    pub fn add_health(&mut self, max_health: f32) -> &mut Self {
-        self.health = Some(WebHealth {
-            current: max_health,
-            max: max_health,
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            live: SparseSet::with_capacity(capacity),
             ..Default::default()
-        });
-        self
+        }
     }
     
-    pub fn is_alive(&self) -> bool {
-        self.active && self.health.as_ref().map_or(true, |h| h.current > 0.0)
+    pub fn spawn(&mut self) -> EntityBuilder<'_> {
+        let handle = self.allocator.allocate();
+        self.live.insert(handle, ());
+        EntityBuilder { world: self, handle }
     }
     
-    pub fn update(&mut self, delta_time: f32) {
-        if !self.active {
-            return;
+    /// Removes the entity and all its components; the handle becomes stale
+    pub fn despawn(&mut self, handle: EntityHandle) -> bool {
+        if !self.allocator.free(handle) {
+            return false;
         }
         
-        // Update physics
-        if let Some(physics) = &self.physics {
-            if !physics.is_kinematic {
-                // Apply drag
-                self.transform.velocity *= 1.0 - (physics.drag * delta_time);
-                
-                // Integration
-                self.transform.velocity += self.transform.acceleration * delta_time;
-                self.transform.position += self.transform.velocity * delta_time;
-                
-                // Reset acceleration
-                self.transform.acceleration = Vector3::zeros();
-            }
+        for storage in self.storages.values_mut() {
+            storage.remove_entity(handle);
         }
-        
-        // Update rotation (simple spinning for visual effect)
-        self.transform.rotation += delta_time * 45.0; // 45 degrees per second
-        if self.transform.rotation > 360.0 {
-            self.transform.rotation -= 360.0;
+        self.live.remove(handle);
+        true
+    }
+    
+    /// Despawns everything; existing handles stay invalid afterwards
+    pub fn clear(&mut self) {
+        for &handle in self.live.handles() {
+            self.allocator.free(handle);
         }
-        
-        // Boundary wrapping for web canvas
-        if self.transform.position.x < 0.0 {
-            self.transform.position.x = CANVAS_WIDTH;
-        } else if self.transform.position.x > CANVAS_WIDTH {
-            self.transform.position.x = 0.0;
+        self.live.clear();
+        for storage in self.storages.values_mut() {
+            storage.clear();
         }
-        
-        if self.transform.position.y < 0.0 {
-            self.transform.position.y = CANVAS_HEIGHT;
-        } else if self.transform.position.y > CANVAS_HEIGHT {
-            self.transform.position.y = 0.0;
+    }
+    
+    pub fn is_alive(&self, handle: EntityHandle) -> bool {
+        self.allocator.is_alive(handle)
+    }
+    
+    pub fn len(&self) -> usize {
+        self.live.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.live.is_empty()
+    }
+    
+    pub fn entities(&self) -> &[EntityHandle] {
+        self.live.handles()
+    }
+    
+    /// Adds or replaces a component; fails for stale handles
+    pub fn insert<T: 'static>(&mut self, handle: EntityHandle, component: T) -> bool {
+        if !self.is_alive(handle) {
+            return false;
         }
         
-        // Update health regeneration
-        if let Some(health) = &mut self.health {
-            if health.regeneration != 0.0 {
-                health.current += health.regeneration * delta_time;
-                health.current = health.current.min(health.max).max(0.0);
+        self.storage_mut::<T>().insert(handle, component);
+        true
+    }
+    
+    pub fn remove<T: 'static>(&mut self, handle: EntityHandle) -> Option<T> {
+        self.storages.get_mut(&TypeId::of::<T>())
+            .and_then(|storage| storage.as_any_mut().downcast_mut::<SparseSet<T>>())
+            .and_then(|storage| storage.remove(handle))
+    }
+    
+    pub fn get<T: 'static>(&self, handle: EntityHandle) -> Option<&T> {
+        self.storage::<T>().and_then(|storage| storage.get(handle))
+    }
+    
+    pub fn get_mut<T: 'static>(&mut self, handle: EntityHandle) -> Option<&mut T> {
+        self.storages.get_mut(&TypeId::of::<T>())
+            .and_then(|storage| storage.as_any_mut().downcast_mut::<SparseSet<T>>())
+            .and_then(|storage| storage.get_mut(handle))
+    }
+    
+    pub fn has<T: 'static>(&self, handle: EntityHandle) -> bool {
+        self.storage::<T>().is_some_and(|storage| storage.contains(handle))
+    }
+    
+    pub fn storage<T: 'static>(&self) -> Option<&SparseSet<T>> {
+        self.storages.get(&TypeId::of::<T>())
+            .and_then(|storage| storage.as_any().downcast_ref::<SparseSet<T>>())
+    }
+    
+    pub fn storage_mut<T: 'static>(&mut self) -> &mut SparseSet<T> {
+        self.storages.entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(SparseSet::<T>::new()))
+            .as_any_mut()
+            .downcast_mut::<SparseSet<T>>()
+            .expect("storage registered under the wrong TypeId")
+    }
+    
+    // === QUERIES ===
+    // Multi-component queries walk the first component's dense array and look
+    // the others up, so put the rarer component first.
+    
+    pub fn query<A: 'static>(&self) -> impl Iterator<Item = (EntityHandle, &A)> {
+        self.storage::<A>().into_iter().flat_map(|storage| storage.iter())
+    }
+    
+    pub fn query_mut<A: 'static>(&mut self) -> impl Iterator<Item = (EntityHandle, &mut A)> {
+        self.storages.get_mut(&TypeId::of::<A>())
+            .and_then(|storage| storage.as_any_mut().downcast_mut::<SparseSet<A>>())
+            .into_iter()
+            .flat_map(|storage| storage.iter_mut())
+    }
+    
+    pub fn query2<A: 'static, B: 'static>(&self) -> impl Iterator<Item = (EntityHandle, &A, &B)> {
+        self.storage::<A>().zip(self.storage::<B>()).into_iter().flat_map(|(storage_a, storage_b)| {
+            storage_a.iter().filter_map(move |(handle, a)| storage_b.get(handle).map(|b| (handle, a, b)))
+        })
+    }
+    
+    pub fn for_each2_mut<A: 'static, B: 'static>(&mut self, mut f: impl FnMut(EntityHandle, &mut A, &mut B)) {
+        assert_ne!(TypeId::of::<A>(), TypeId::of::<B>(), "query components must be distinct");
+        
+        // Lift B out of the map so both storages can be borrowed mutably
+        let Some(mut boxed_b) = self.storages.remove(&TypeId::of::<B>()) else {
+            return;
+        };
+        
+        if let Some(boxed_a) = self.storages.get_mut(&TypeId::of::<A>()) {
+            let storage_a = boxed_a.as_any_mut().downcast_mut::<SparseSet<A>>().unwrap();
+            let storage_b = boxed_b.as_any_mut().downcast_mut::<SparseSet<B>>().unwrap();
+            
+            for (handle, a) in storage_a.iter_mut() {
+                if let Some(b) = storage_b.get_mut(handle) {
+                    f(handle, a, b);
+                }
             }
         }
+        
+        self.storages.insert(TypeId::of::<B>(), boxed_b);
+    }
+}
+
+pub struct EntityBuilder<'w> {
+    world: &'w mut World,
+    handle: EntityHandle,
+}
+
+impl<'w> EntityBuilder<'w> {
+    pub fn with<T: 'static>(self, component: T) -> Self {
+        self.world.storage_mut::<T>().insert(self.handle, component);
+        self
+    }
+    
+    pub fn build(self) -> EntityHandle {
+        self.handle
+    }
+}
+
+// Name and tag for every entity spawned by the game state
+#[derive(Debug, Clone)]
+pub struct WebEntityInfo {
+    pub name: String,
+    pub tag: String,
+}
+
+impl WebEntityInfo {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            tag: "Default".to_string(),
+        }
     }
 }
 
@@ -859,6 +6145,7 @@ pub struct WebParticle {
     pub rotation: f32,
     pub angular_velocity: f32,
     pub active: bool,
+    pub visible: bool, // False while outside the view; skipped by the render data
 }
 
 #[derive(Debug)]
@@ -909,14 +6196,14 @@ impl WebParticleSystem {
         }
     }
     
-    pub fn create_explosion(&mut self, position: Vector3<f32>, intensity: f32) {
+    pub fn create_explosion(&mut self, position: Vector3<f32>, intensity: f32, rng: &mut dyn RandomSource) {
         let particle_count = (intensity * 30.0) as usize;
         let max_new_particles = (MAX_PARTICLES - self.particles.len()).min(particle_count).min(50);
         
         for _ in 0..max_new_particles {
-            let angle = Math::random() * 2.0 * std::f64::consts::PI;
-            let elevation = Math::random() * std::f64::consts::PI - std::f64::consts::PI * 0.5;
-            let speed = 80.0 + Math::random() * 120.0;
+            let angle = rng.next_f64() * 2.0 * std::f64::consts::PI;
+            let elevation = rng.next_f64() * std::f64::consts::PI - std::f64::consts::PI * 0.5;
+            let speed = 80.0 + rng.next_f64() * 120.0;
             
             let velocity = Vector3::new(
                 (angle.cos() * elevation.cos() * speed) as f32,
@@ -928,12 +6215,13 @@ impl WebParticleSystem {
                 position,
                 velocity,
                 color: [1.0, 0.7, 0.2, 1.0], // Orange fire
-                life: 1.0 + Math::random() as f32,
+                life: 1.0 + rng.next_f64() as f32,
                 max_life: 2.0,
-                size: 3.0 + Math::random() as f32 * 4.0,
+                size: 3.0 + rng.next_f64() as f32 * 4.0,
                 rotation: 0.0,
-                angular_velocity: (Math::random() as f32 - 0.5) * 10.0,
+                angular_velocity: (rng.next_f64() as f32 - 0.5) * 10.0,
                 active: true,
+                visible: true,
             };
             
             self.particles.push(particle);
@@ -945,10 +6233,27 @@ impl WebParticleSystem {
     }
     
     pub fn get_render_data(&self) -> Vec<f32> {
-        let active_particles: Vec<&WebParticle> = self.particles.iter().filter(|p| p.active).collect();
-        let mut data = Vec::with_capacity(active_particles.len() * 8);
-        
-        for particle in active_particles {
+        let mut data = Vec::with_capacity(self.particle_count() * 8);
+        self.write_render_data(&mut data);
+        data
+    }
+    
+    /// Marks each active particle visible or not by `sees(position, size)`;
+    /// returns how many were hidden
+    pub fn cull(&mut self, sees: impl Fn(Vector3<f32>, f32) -> bool) -> usize {
+        let mut culled = 0;
+        for particle in self.particles.iter_mut().filter(|p| p.active) {
+            particle.visible = sees(particle.position, particle.size);
+            if !particle.visible {
+                culled += 1;
+            }
+        }
+        culled
+    }
+    
+    /// Appends [x, y, z, size, r, g, b, a] per active, visible particle
+    pub fn write_render_data(&self, data: &mut Vec<f32>) {
+        for particle in self.particles.iter().filter(|p| p.active && p.visible) {
             data.extend_from_slice(&[
                 particle.position.x,
                 particle.position.y,
@@ -960,8 +6265,6 @@ impl WebParticleSystem {
                 particle.color[3],
             ]);
         }
-        
-        data
     }
 }
 
@@ -970,6 +6273,7 @@ impl WebParticleSystem {
 #[derive(Debug)]
 pub struct WebPerformanceMonitor {
     pub last_frame_time: f64,
+    pub frame_delta: f32, // Uncapped seconds since the previous frame
     pub frame_times: Vec<f32>,
     pub fps_counter: u32,
     pub fps_timer: f64,
@@ -982,9 +6286,10 @@ pub struct WebPerformanceMonitor {
 }
 
 impl WebPerformanceMonitor {
-    pub fn new() -> Self {
+    pub fn new(start_time: f64) -> Self {
         Self {
-            last_frame_time: now(),
+            last_frame_time: start_time,
+            frame_delta: 0.0,
             frame_times: Vec::with_capacity(60),
             fps_counter: 0,
             fps_timer: 0.0,
@@ -997,9 +6302,10 @@ impl WebPerformanceMonitor {
         }
     }
     
-    pub fn update(&mut self, current_time: f64) -> f32 {
+    pub fn update(&mut self, current_time: f64, logger: &dyn Logger) -> f32 {
         let frame_time = ((current_time - self.last_frame_time) / 1000.0) as f32;
         self.last_frame_time = current_time;
+        self.frame_delta = frame_time.max(0.0);
         
         // Cap delta time
         let capped_frame_time = frame_time.min(0.033); // Max 33ms
@@ -1030,14 +6336,14 @@ impl WebPerformanceMonitor {
                 if self.quality_level > 0 {
                     self.quality_level -= 1;
                     self.quality_cooldown = QUALITY_ADJUSTMENT_COOLDOWN;
-                    console_log!("Quality reduced to {} (frame time: {:.2}ms)", 
+                    platform_log!(logger, "Quality reduced to {} (frame time: {:.2}ms)", 
                                 self.quality_level, self.average_frame_time_ms);
                 }
             } else if self.average_frame_time_ms < MAX_FRAME_TIME_MS * 0.7 {
                 if self.quality_level < 2 {
                     self.quality_level += 1;
                     self.quality_cooldown = QUALITY_ADJUSTMENT_COOLDOWN * 3; // Longer cooldown for increase
-                    console_log!("Quality increased to {} (frame time: {:.2}ms)", 
+                    platform_log!(logger, "Quality increased to {} (frame time: {:.2}ms)", 
                                 self.quality_level, self.average_frame_time_ms);
                 }
             }
@@ -1113,4 +6419,3 @@ impl WebInputSystem {
         movement
     }
 }
-// ===
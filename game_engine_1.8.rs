@@ -1,7 +1,8 @@
 use wasm_bindgen::prelude::*;
 use js_sys::*;
 use web_sys::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::iter::Iterator; // js_sys::* also exports an `Iterator`
 
 // Web-Optimized Game Engine for Deplauncher 1.8 - Classic Edition (Rust)
 // Lightweight, memory-safe engine specifically optimized for web browsers
@@ -31,6 +32,17 @@ const MAX_PARTICLES: usize = 200;        // Reduced for consistent performance
 const CANVAS_WIDTH: f32 = 800.0;
 const CANVAS_HEIGHT: f32 = 600.0;
 const COLLISION_RADIUS: f32 = 28.0;      // Slightly reduced for responsiveness
+const DEFAULT_COLLIDER_RADIUS: f32 = COLLISION_RADIUS * 0.5; // Sprites are drawn at this radius before scaling
+const BOUNDARY_MARGIN: f32 = 32.0;       // How far sprites leave the screen before wrapping
+const DEFAULT_COLLISION_LAYER: u32 = 1;
+const ALL_COLLISION_LAYERS: u32 = u32::MAX;
+const MAX_TRIGGER_EVENTS: usize = 1024;  // Undrained trigger events before the oldest are dropped
+const CCD_CONTACT_SLOP: f32 = 0.5;       // Penetration left by sweeps so contacts still resolve
+const DEFAULT_FRICTION: f32 = 0.3;       // Default material's friction
+const CLASSIC_DAMPING: f32 = 0.08;       // Share of velocity lost per update at the default friction
+const MIN_CAMERA_ZOOM: f32 = 0.01;
+const CULL_MARGIN: f32 = 8.0;            // Slack so sprites don't pop at the view's edge
+const ENTITY_RENDER_FLOATS: usize = 14;  // Per entity in `get_entity_render_data`
 const TARGET_FPS: f32 = 60.0;
 const MAX_FRAME_TIME_MS: f32 = 16.67;    // 60 FPS budget
 
@@ -129,6 +141,15 @@ impl Vector2 {
     pub fn lerp(&self, target: &Vector2, t: f32) -> Self {
         *self + (*target - *self) * t
     }
+    
+    pub fn dot(&self, other: &Vector2) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+    
+    /// Z component of the 3D cross product
+    pub fn cross(&self, other: &Vector2) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
 }
 
 impl std::ops::Add for Vector2 {
@@ -166,12 +187,271 @@ impl std::ops::MulAssign<f32> for Vector2 {
     }
 }
 
+// === DETERMINISTIC RANDOM ===
+
+// Engine-owned random stream; the same seed always replays the same sequence
+#[derive(Debug, Clone)]
+pub struct XorShiftRandom {
+    seed: u32,
+    state: u64,
+}
+
+impl XorShiftRandom {
+    pub fn new(seed: u32) -> Self {
+        let mut rng = Self { seed, state: 0 };
+        rng.set_seed(seed);
+        rng
+    }
+    
+    // Uniform value in [0, 1), drop-in for Math::random()
+    pub fn next_f64(&mut self) -> f64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 11) as f64 / (1u64 << 53) as f64
+    }
+    
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+    
+    pub fn set_seed(&mut self, seed: u32) {
+        // Seeds 1, 2, 3... would start xorshift in near-identical states; mix them first (SplitMix64)
+        let mut z = (seed as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        
+        self.seed = seed;
+        self.state = z.max(1); // Zero state would stay zero forever
+    }
+}
+
+// === WORLD BOUNDS ===
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BoundaryMode {
+    Wrap,      // Leave one side, come back on the other
+    Clamp,     // Stop at the edge
+    Bounce,    // Reflect off the edge, losing speed by the entity's restitution
+    Destroy,   // Deactivated once fully outside
+    Unbounded, // Bounds are ignored
+}
+
+impl BoundaryMode {
+    pub fn from_name(name: &str) -> Option<BoundaryMode> {
+        match name {
+            "wrap" => Some(BoundaryMode::Wrap),
+            "clamp" => Some(BoundaryMode::Clamp),
+            "bounce" => Some(BoundaryMode::Bounce),
+            "destroy" => Some(BoundaryMode::Destroy),
+            "unbounded" => Some(BoundaryMode::Unbounded),
+            _ => None,
+        }
+    }
+}
+
+// The playable rectangle. Wrap and destroy act `margin` past the edges so
+// sprites can leave the screen first, while clamp and bounce keep the
+// entity's bounding radius inside them.
+#[derive(Debug, Clone, Copy)]
+pub struct WorldBounds {
+    pub min: Vector2,
+    pub max: Vector2,
+    pub margin: f32,
+    pub mode: BoundaryMode, // For entities without their own `boundary_mode`
+}
+
+impl WorldBounds {
+    pub fn new() -> Self {
+        Self {
+            min: Vector2::zero(),
+            max: Vector2::new(CANVAS_WIDTH, CANVAS_HEIGHT),
+            margin: BOUNDARY_MARGIN,
+            mode: BoundaryMode::Wrap,
+        }
+    }
+    
+    // One axis of `WebEntity::update`'s boundary step; false once the entity has left for good
+    fn apply_axis(&self, mode: BoundaryMode, position: &mut f32, velocity: &mut f32, (min, max): (f32, f32), radius: f32, restitution: f32) -> bool {
+        match mode {
+            BoundaryMode::Unbounded => {}
+            BoundaryMode::Wrap => {
+                if *position < min - self.margin {
+                    *position = max + self.margin;
+                } else if *position > max + self.margin {
+                    *position = min - self.margin;
+                }
+            }
+            BoundaryMode::Destroy => {
+                if *position < min - self.margin || *position > max + self.margin {
+                    return false;
+                }
+            }
+            BoundaryMode::Clamp | BoundaryMode::Bounce => {
+                // An entity wider than the world is held at its center
+                let (low, high) = if max - min > radius * 2.0 {
+                    (min + radius, max - radius)
+                } else {
+                    ((min + max) * 0.5, (min + max) * 0.5)
+                };
+                let rebound = if mode == BoundaryMode::Bounce { restitution } else { 0.0 };
+                
+                if *position < low {
+                    *position = low;
+                    *velocity = velocity.abs() * rebound;
+                } else if *position > high {
+                    *position = high;
+                    *velocity = -velocity.abs() * rebound;
+                }
+            }
+        }
+        true
+    }
+}
+
+impl Default for WorldBounds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// === PHYSICS MATERIALS ===
+
+// How the two materials in a collision mix their friction or restitution.
+// Mismatched modes resolve to the one declared last, so e.g. a `Max` pad
+// bounces everything that lands on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CombineMode {
+    Average,
+    Min,
+    Multiply,
+    Max,
+}
+
+impl CombineMode {
+    pub fn from_name(name: &str) -> Option<CombineMode> {
+        match name {
+            "average" => Some(CombineMode::Average),
+            "min" => Some(CombineMode::Min),
+            "multiply" => Some(CombineMode::Multiply),
+            "max" => Some(CombineMode::Max),
+            _ => None,
+        }
+    }
+    
+    pub fn combine(self, a: f32, b: f32) -> f32 {
+        match self {
+            CombineMode::Average => (a + b) * 0.5,
+            CombineMode::Min => a.min(b),
+            CombineMode::Multiply => a * b,
+            CombineMode::Max => a.max(b),
+        }
+    }
+}
+
+// Surface properties of an entity. Friction also sets how quickly it slows
+// down on its own; density is relative to a default-sized entity, so at
+// density 1 the default collider weighs 1 and mass grows with collider area.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhysicsMaterial {
+    pub friction: f32,    // Largest friction impulse as a share of the normal one
+    pub restitution: f32, // Share of the approach speed kept after a bounce
+    pub density: f32,
+    pub friction_combine: CombineMode,
+    pub restitution_combine: CombineMode,
+}
+
+impl Default for PhysicsMaterial {
+    fn default() -> Self {
+        Self {
+            friction: DEFAULT_FRICTION,
+            restitution: 0.8,
+            density: 1.0,
+            friction_combine: CombineMode::Average,
+            restitution_combine: CombineMode::Average,
+        }
+    }
+}
+
+impl PhysicsMaterial {
+    pub fn ice() -> Self {
+        Self { friction: 0.02, restitution: 0.1, density: 0.9, friction_combine: CombineMode::Min, ..Self::default() }
+    }
+    
+    pub fn rubber() -> Self {
+        Self { friction: 0.9, restitution: 0.9, density: 1.2, restitution_combine: CombineMode::Max, ..Self::default() }
+    }
+    
+    pub fn mud() -> Self {
+        Self { friction: 1.0, restitution: 0.0, density: 1.6, friction_combine: CombineMode::Max, restitution_combine: CombineMode::Min }
+    }
+    
+    /// `(friction, restitution)` to use when this material hits `other`
+    pub fn combine(&self, other: &PhysicsMaterial) -> (f32, f32) {
+        let friction_mode = self.friction_combine.max(other.friction_combine);
+        let restitution_mode = self.restitution_combine.max(other.restitution_combine);
+        (
+            friction_mode.combine(self.friction, other.friction),
+            restitution_mode.combine(self.restitution, other.restitution).clamp(0.0, 1.0),
+        )
+    }
+    
+    /// Mass of an entity of this material with the given collider
+    pub fn mass_for(&self, collider: &ColliderShape) -> f32 {
+        let reference_area = ColliderShape::Circle { radius: DEFAULT_COLLIDER_RADIUS }.area();
+        self.density * collider.area() / reference_area
+    }
+    
+    /// Share of its velocity an entity keeps each update
+    fn velocity_retention(&self) -> f32 {
+        (1.0 - CLASSIC_DAMPING * self.friction / DEFAULT_FRICTION).max(0.0)
+    }
+}
+
+// Material library looked up by name when JS or a scene file assigns one to
+// an entity. Entities copy the material, so redefining a name later only
+// affects assignments made after it. Ships with "default", "ice", "rubber"
+// and "mud".
+#[derive(Debug, Clone)]
+pub struct PhysicsMaterials {
+    materials: HashMap<String, PhysicsMaterial>,
+}
+
+impl PhysicsMaterials {
+    pub fn new() -> Self {
+        let mut materials = HashMap::new();
+        materials.insert("default".to_string(), PhysicsMaterial::default());
+        materials.insert("ice".to_string(), PhysicsMaterial::ice());
+        materials.insert("rubber".to_string(), PhysicsMaterial::rubber());
+        materials.insert("mud".to_string(), PhysicsMaterial::mud());
+        Self { materials }
+    }
+    
+    pub fn define(&mut self, name: &str, material: PhysicsMaterial) {
+        self.materials.insert(name.to_string(), material);
+    }
+    
+    pub fn get(&self, name: &str) -> Option<PhysicsMaterial> {
+        self.materials.get(name).copied()
+    }
+}
+
+impl Default for PhysicsMaterials {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // === WEB ENTITY SYSTEM ===
 
 #[wasm_bindgen]
 #[derive(Debug, Clone)]
 pub struct WebEntity {
+    pub id: u32, // Stable across removals, unlike the entity's index
     pub position: Vector2,
+    #[wasm_bindgen(skip)]
+    pub previous_position: Vector2, // Where the last update started; swept by continuous collision
     pub velocity: Vector2,
     pub rotation: f32,
     pub texture_id: u32,
@@ -180,6 +460,22 @@ pub struct WebEntity {
     pub max_health: i32,
     pub name: String,
     pub entity_type: EntityType,
+    pub mass: f32,
+    #[wasm_bindgen(skip)]
+    pub material: PhysicsMaterial,
+    pub is_kinematic: bool, // Moves on its own velocity but is never pushed by collisions
+    #[wasm_bindgen(skip)]
+    pub collider: ColliderShape,
+    pub collision_layer: u32, // Layers this entity is on
+    pub collision_mask: u32,  // Layers this entity collides with
+    pub is_trigger: bool,     // Reports overlaps as trigger events but is never pushed apart
+    pub continuous_collision: bool, // Sweep fast motion so it can't tunnel through thin objects
+    #[wasm_bindgen(skip)]
+    pub boundary_mode: Option<BoundaryMode>, // Overrides the world's mode when set
+    pub angular_velocity: f32, // Degrees per second, like `rotation`
+    pub angular_drag: f32,
+    pub inertia: Option<f32>,  // Moment of inertia; derived from mass and collider when None
+    pub on_screen: bool,       // Cleared by view culling; off-screen entities are left out of the render data
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -190,10 +486,24 @@ pub enum EntityType {
     Projectile,
 }
 
+impl EntityType {
+    pub fn from_name(name: &str) -> Option<EntityType> {
+        match name {
+            "Player" => Some(EntityType::Player),
+            "Environment" => Some(EntityType::Environment),
+            "Pickup" => Some(EntityType::Pickup),
+            "Projectile" => Some(EntityType::Projectile),
+            _ => None,
+        }
+    }
+}
+
 impl WebEntity {
     pub fn new(position: Vector2, texture_id: u32, name: String, entity_type: EntityType) -> Self {
         Self {
+            id: 0, // Assigned when the game state adds the entity
             position,
+            previous_position: position,
             velocity: Vector2::zero(),
             rotation: 0.0,
             texture_id,
@@ -202,48 +512,107 @@ impl WebEntity {
             max_health: 100,
             name,
             entity_type,
+            mass: 1.0,
+            material: PhysicsMaterial::default(),
+            is_kinematic: false,
+            collider: ColliderShape::Circle { radius: DEFAULT_COLLIDER_RADIUS },
+            collision_layer: DEFAULT_COLLISION_LAYER,
+            collision_mask: ALL_COLLISION_LAYERS,
+            is_trigger: false,
+            continuous_collision: false,
+            boundary_mode: None,
+            angular_velocity: 0.0,
+            angular_drag: 0.5,
+            inertia: None,
+            on_screen: true,
         }
     }
     
-    pub fn update(&mut self, delta_time: f32) {
+    pub fn update(&mut self, delta_time: f32, bounds: &WorldBounds) {
         if !self.active {
             return;
         }
         
         // Apply velocity
+        self.previous_position = self.position;
         self.position += self.velocity * delta_time;
+        let moved_to = self.position;
         
         // Apply rotation
-        self.rotation += delta_time * 60.0; // 60 degrees per second
-        if self.rotation > 360.0 {
-            self.rotation -= 360.0;
-        }
-        
-        // Boundary wrapping for classic arcade feel
-        if self.position.x < -32.0 {
-            self.position.x = CANVAS_WIDTH + 32.0;
-        } else if self.position.x > CANVAS_WIDTH + 32.0 {
-            self.position.x = -32.0;
+        self.angular_velocity *= (1.0 - self.angular_drag * delta_time).max(0.0);
+        self.rotation = (self.rotation + self.angular_velocity * delta_time).rem_euclid(360.0);
+        
+        // Boundary wrapping (by default) for classic arcade feel
+        let mode = self.boundary_mode.unwrap_or(bounds.mode);
+        let radius = self.collider.bounding_radius();
+        let restitution = self.material.restitution;
+        let inside_x = bounds.apply_axis(mode, &mut self.position.x, &mut self.velocity.x, (bounds.min.x, bounds.max.x), radius, restitution);
+        let inside_y = bounds.apply_axis(mode, &mut self.position.y, &mut self.velocity.y, (bounds.min.y, bounds.max.y), radius, restitution);
+        if !(inside_x && inside_y) {
+            self.active = false; // Left the world; removed with the dead
+            return;
         }
         
-        if self.position.y < -32.0 {
-            self.position.y = CANVAS_HEIGHT + 32.0;
-        } else if self.position.y > CANVAS_HEIGHT + 32.0 {
-            self.position.y = -32.0;
+        // Wrapped: nothing was crossed on the way, so there is nothing to sweep
+        if mode == BoundaryMode::Wrap && (self.position.x != moved_to.x || self.position.y != moved_to.y) {
+            self.previous_position = self.position;
         }
         
-        // Apply friction for classic physics
-        self.velocity *= 0.92; // More responsive than original
+        // Surface friction for classic physics; the default material keeps the classic 0.92
+        self.velocity *= self.material.velocity_retention();
     }
     
     pub fn apply_force(&mut self, force: Vector2) {
         self.velocity += force;
     }
     
+    /// Angular counterpart of `apply_force`; scale by the frame time for a steady torque
+    pub fn apply_torque(&mut self, torque: f32) {
+        self.angular_velocity += (torque * self.inverse_inertia()).to_degrees();
+    }
+    
     pub fn is_alive(&self) -> bool {
         self.active && self.health > 0
     }
     
+    /// Scale, rotation then translation as a column-major 3x3 matrix. Entities
+    /// have no size of their own, so the per-axis scale stretches a sprite
+    /// drawn for the default collider over this entity's collider; there are
+    /// no parents to apply in this edition.
+    pub fn model_matrix(&self) -> [f32; 9] {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let scale = self.collider.half_extents() * (1.0 / DEFAULT_COLLIDER_RADIUS);
+        [
+            cos * scale.x, sin * scale.x, 0.0,
+            -sin * scale.y, cos * scale.y, 0.0,
+            self.position.x, self.position.y, 1.0,
+        ]
+    }
+    
+    /// Both entities must accept each other; a zero layer or mask never collides
+    pub fn collides_with(&self, other: &WebEntity) -> bool {
+        (self.collision_layer & other.collision_mask) != 0 && (other.collision_layer & self.collision_mask) != 0
+    }
+    
+    /// Zero for kinematic or massless entities, which collisions cannot move
+    pub fn inverse_mass(&self) -> f32 {
+        if self.is_kinematic || self.mass <= 0.0 {
+            0.0
+        } else {
+            1.0 / self.mass
+        }
+    }
+    
+    /// Zero for entities collisions cannot turn
+    pub fn inverse_inertia(&self) -> f32 {
+        let inertia = self.inertia.unwrap_or(self.mass * self.collider.unit_inertia());
+        if self.is_kinematic || self.mass <= 0.0 || inertia <= 0.0 {
+            0.0
+        } else {
+            1.0 / inertia
+        }
+    }
+    
     pub fn take_damage(&mut self, damage: i32) {
         self.health = (self.health - damage).max(0);
         if self.health <= 0 {
@@ -263,6 +632,7 @@ pub struct WebParticle {
     pub size: f32,
     pub color: [u8; 3],
     pub active: bool,
+    pub visible: bool, // False while outside the view; skipped by the render data
 }
 
 impl WebParticle {
@@ -275,6 +645,7 @@ impl WebParticle {
             size,
             color,
             active: true,
+            visible: true,
         }
     }
     
@@ -343,20 +714,20 @@ impl WebParticleSystem {
         }
     }
     
-    pub fn create_explosion(&mut self, position: Vector2, count: usize) {
+    pub fn create_explosion(&mut self, position: Vector2, count: usize, rng: &mut XorShiftRandom) {
         let actual_count = count.min(15).min(self.max_particles - self.particles.len());
         
         for _ in 0..actual_count {
-            let angle = Math::random() * 2.0 * std::f64::consts::PI;
-            let speed = 60.0 + Math::random() * 80.0;
+            let angle = rng.next_f64() * 2.0 * std::f64::consts::PI;
+            let speed = 60.0 + rng.next_f64() * 80.0;
             
             let velocity = Vector2::new(
                 (angle.cos() * speed) as f32,
                 (angle.sin() * speed) as f32,
             );
             
-            let life = 1.0 + Math::random() as f32 * 1.0;
-            let size = 2.5 + Math::random() as f32 * 2.5;
+            let life = 1.0 + rng.next_f64() as f32 * 1.0;
+            let size = 2.5 + rng.next_f64() as f32 * 2.5;
             let color = [255, 180, 60]; // Orange explosion
             
             let particle = WebParticle::new(position, velocity, life, size, color);
@@ -369,10 +740,27 @@ impl WebParticleSystem {
     }
     
     pub fn get_render_data(&self) -> Vec<f32> {
-        let active_particles: Vec<&WebParticle> = self.particles.iter().filter(|p| p.active).collect();
-        let mut data = Vec::with_capacity(active_particles.len() * 6);
-        
-        for particle in active_particles {
+        let mut data = Vec::with_capacity(self.active_particle_count() * 6);
+        self.write_render_data(&mut data);
+        data
+    }
+    
+    /// Sets each live particle's `visible` flag from `sees(position, size)` and
+    /// counts the ones that failed
+    pub fn cull(&mut self, sees: impl Fn(Vector2, f32) -> bool) -> usize {
+        let mut culled = 0;
+        for particle in self.particles.iter_mut().filter(|p| p.active) {
+            particle.visible = sees(particle.position, particle.size);
+            if !particle.visible {
+                culled += 1;
+            }
+        }
+        culled
+    }
+    
+    /// Appends [x, y, size, r, g, b] per active, visible particle
+    pub fn write_render_data(&self, data: &mut Vec<f32>) {
+        for particle in self.particles.iter().filter(|p| p.active && p.visible) {
             data.extend_from_slice(&[
                 particle.position.x,
                 particle.position.y,
@@ -382,8 +770,6 @@ impl WebParticleSystem {
                 particle.color[2] as f32 / 255.0,
             ]);
         }
-        
-        data
     }
     
     pub fn clear(&mut self) {
@@ -536,109 +922,1128 @@ impl WebInputSystem {
     }
 }
 
-// === WEB COLLISION SYSTEM ===
+// === COLLIDERS ===
 
-#[derive(Debug)]
-pub struct WebCollisionSystem {
-    spatial_grid: HashMap<(i32, i32), Vec<usize>>,
-    cell_size: f32,
+// Shapes are in local space around the entity position and turn with
+// `WebEntity::rotation` (degrees), except the axis-aligned box
+#[derive(Debug, Clone)]
+pub enum ColliderShape {
+    Circle { radius: f32 },
+    Aabb { half_width: f32, half_height: f32 },
+    Obb { half_width: f32, half_height: f32 },
+    Capsule { half_length: f32, radius: f32 }, // Segment along local X
+    Polygon { points: Vec<Vector2> },          // Convex, either winding
 }
 
-impl WebCollisionSystem {
-    pub fn new() -> Self {
-        Self {
-            spatial_grid: HashMap::new(),
-            cell_size: 64.0,
+#[derive(Debug, Clone, Copy)]
+pub struct Contact {
+    pub normal: Vector2, // Unit vector from the second shape towards the first
+    pub depth: f32,
+    pub point: Vector2,  // Middle of the touching region, where impulses act
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RaycastHit {
+    pub index: usize,
+    pub point: Vector2,
+    pub normal: Vector2, // Unit surface normal, facing back along the ray
+    pub distance: f32,
+}
+
+// A shape placed in the world: a rounded segment (a point for circles) or a
+// convex hull with its edge normals
+enum WorldShape {
+    Round { core: [Vector2; 2], radius: f32 },
+    Hull { vertices: Vec<Vector2>, axes: Vec<Vector2> },
+}
+
+// Strictly convex outline: three or more finite points whose consecutive
+// edges always bend the same way (no straight or doubled-back corners) and
+// wind around exactly once (so a pentagram fails)
+fn is_convex_polygon(points: &[Vector2]) -> bool {
+    if points.len() < 3 || points.iter().any(|point| !point.x.is_finite() || !point.y.is_finite()) {
+        return false;
+    }
+    
+    let mut turn_sign = 0.0;
+    let mut total_turn = 0.0;
+    for (i, &a) in points.iter().enumerate() {
+        let (b, c) = (points[(i + 1) % points.len()], points[(i + 2) % points.len()]);
+        let (edge, next_edge) = (b - a, c - b);
+        let cross = edge.cross(&next_edge);
+        if cross.abs() <= f32::EPSILON * edge.magnitude() * next_edge.magnitude() || (turn_sign != 0.0 && cross.signum() != turn_sign) {
+            return false;
+        }
+        turn_sign = cross.signum();
+        total_turn += cross.atan2(edge.dot(&next_edge));
+    }
+    (total_turn.abs() - std::f32::consts::TAU).abs() < 1e-3
+}
+
+impl ColliderShape {
+    /// Radius of a circle around the entity position that contains the shape
+    pub fn bounding_radius(&self) -> f32 {
+        match self {
+            ColliderShape::Circle { radius } => *radius,
+            ColliderShape::Aabb { half_width, half_height } | ColliderShape::Obb { half_width, half_height } => {
+                Vector2::new(*half_width, *half_height).magnitude()
+            }
+            ColliderShape::Capsule { half_length, radius } => half_length + radius,
+            ColliderShape::Polygon { points } => points.iter().map(|point| point.magnitude()).fold(0.0, f32::max),
+        }
+    }
+    
+    /// Half the width and height of the unrotated shape's bounding box
+    pub fn half_extents(&self) -> Vector2 {
+        match self {
+            ColliderShape::Circle { radius } => Vector2::new(*radius, *radius),
+            ColliderShape::Aabb { half_width, half_height } | ColliderShape::Obb { half_width, half_height } => {
+                Vector2::new(*half_width, *half_height)
+            }
+            ColliderShape::Capsule { half_length, radius } => Vector2::new(half_length + radius, *radius),
+            ColliderShape::Polygon { points } => points.iter().fold(Vector2::zero(), |extents, point| {
+                Vector2::new(extents.x.max(point.x.abs()), extents.y.max(point.y.abs()))
+            }),
+        }
+    }
+    
+    /// False for NaN, infinite or negative sizes and for polygons that aren't
+    /// strictly convex, none of which SAT, `area` or `unit_inertia` can handle
+    pub fn is_valid(&self) -> bool {
+        let valid_size = |size: f32| size.is_finite() && size >= 0.0;
+        match self {
+            ColliderShape::Circle { radius } => valid_size(*radius),
+            ColliderShape::Aabb { half_width, half_height } | ColliderShape::Obb { half_width, half_height } => {
+                valid_size(*half_width) && valid_size(*half_height)
+            }
+            ColliderShape::Capsule { half_length, radius } => valid_size(*half_length) && valid_size(*radius),
+            ColliderShape::Polygon { points } => is_convex_polygon(points),
         }
     }
     
-    pub fn update(&mut self, entities: &mut [WebEntity], particle_system: &mut WebParticleSystem) -> i32 {
-        // Clear spatial grid
-        self.spatial_grid.clear();
+    /// Minimum translation pushing this shape out of `other` (SAT), if they overlap
+    pub fn contact(&self, position: Vector2, rotation: f32, other: &ColliderShape, other_position: Vector2, other_rotation: f32) -> Option<Contact> {
+        let shape_a = self.place(position, rotation);
+        let shape_b = other.place(other_position, other_rotation);
         
-        // Populate spatial grid
-        for (index, entity) in entities.iter().enumerate() {
-            if !entity.active {
+        let mut axes = Vec::new();
+        shape_a.collect_axes(&shape_b, &mut axes);
+        shape_b.collect_axes(&shape_a, &mut axes);
+        axes.push(Vector2::new(1.0, 0.0)); // Concentric circles have no other usable axis
+        
+        let mut best: Option<(Vector2, f32)> = None;
+        for axis in axes {
+            if axis.magnitude_squared() < 1e-12 {
                 continue;
             }
+            let axis = axis.normalized();
             
-            let grid_x = (entity.position.x / self.cell_size) as i32;
-            let grid_y = (entity.position.y / self.cell_size) as i32;
+            let (min_a, max_a) = shape_a.project(&axis);
+            let (min_b, max_b) = shape_b.project(&axis);
+            let depth = max_a.min(max_b) - min_a.max(min_b);
+            if depth <= 0.0 {
+                return None;
+            }
             
-            self.spatial_grid.entry((grid_x, grid_y))
-                .or_insert_with(Vec::new)
-                .push(index);
+            if best.is_none_or(|(_, best_depth)| depth < best_depth) {
+                best = Some((axis, depth));
+            }
         }
         
-        let mut score_increment = 0;
-        let mut collisions = Vec::new();
+        // SAT axes have no direction; flip so the normal points from `other` to us
+        let (mut normal, depth) = best?;
+        if (position - other_position).dot(&normal) < 0.0 {
+            normal *= -1.0;
+        }
+        let point = contact_point(&shape_a, &shape_b, &normal);
+        Some(Contact { normal, depth, point })
+    }
+    
+    /// Area of the shape; mass scales with it through the material's density
+    pub fn area(&self) -> f32 {
+        match self {
+            ColliderShape::Circle { radius } => std::f32::consts::PI * radius * radius,
+            ColliderShape::Aabb { half_width, half_height } | ColliderShape::Obb { half_width, half_height } => 4.0 * half_width * half_height,
+            ColliderShape::Capsule { half_length, radius } => 4.0 * half_length * radius + std::f32::consts::PI * radius * radius,
+            ColliderShape::Polygon { points } => {
+                let twice_area: f32 = points.iter().enumerate()
+                    .map(|(i, a)| a.cross(&points[(i + 1) % points.len()]))
+                    .sum();
+                twice_area.abs() * 0.5
+            }
+        }
+    }
+    
+    /// Moment of inertia about the entity position for a mass of 1
+    pub fn unit_inertia(&self) -> f32 {
+        match self {
+            ColliderShape::Circle { radius } => radius * radius * 0.5,
+            ColliderShape::Aabb { half_width, half_height } | ColliderShape::Obb { half_width, half_height } => {
+                (half_width * half_width + half_height * half_height) / 3.0
+            }
+            // Approximated by the box around the capsule
+            ColliderShape::Capsule { half_length, radius } => ((half_length + radius).powi(2) + radius * radius) / 3.0,
+            ColliderShape::Polygon { points } => {
+                // Sum over triangles from the origin to each edge; dividing by the signed area makes winding irrelevant
+                let (mut weighted, mut area) = (0.0, 0.0);
+                for (i, a) in points.iter().enumerate() {
+                    let b = &points[(i + 1) % points.len()];
+                    let cross = a.cross(b);
+                    weighted += cross * (a.dot(a) + a.dot(b) + b.dot(b));
+                    area += cross;
+                }
+                if area.abs() > 1e-6 {
+                    weighted / (6.0 * area)
+                } else {
+                    self.bounding_radius().powi(2) * 0.5
+                }
+            }
+        }
+    }
+    
+    /// `(distance, normal)` where a ray with unit `direction` first touches the
+    /// shape; origins already inside report distance 0
+    pub fn raycast(&self, position: Vector2, rotation: f32, origin: Vector2, direction: Vector2, max_distance: f32) -> Option<(f32, Vector2)> {
+        self.place(position, rotation).raycast(&origin, &direction, max_distance)
+    }
+    
+    fn place(&self, center: Vector2, rotation: f32) -> WorldShape {
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        let local = |x: f32, y: f32| center + Vector2::new(x * cos - y * sin, x * sin + y * cos);
         
-        // Check collisions within grid cells
-        for entity_indices in self.spatial_grid.values() {
-            for i in 0..entity_indices.len() {
-                for j in (i + 1)..entity_indices.len() {
-                    let idx_a = entity_indices[i];
-                    let idx_b = entity_indices[j];
-                    
-                    let distance_sq = entities[idx_a].position.distance_squared_to(&entities[idx_b].position);
-                    let collision_radius_sq = COLLISION_RADIUS * COLLISION_RADIUS;
-                    
-                    if distance_sq < collision_radius_sq {
-                        collisions.push((idx_a, idx_b, distance_sq.sqrt()));
-                        
-                        // Score for player collisions
-                        if entities[idx_a].entity_type == EntityType::Player || 
-                           entities[idx_b].entity_type == EntityType::Player {
-                            score_increment += 10;
+        match self {
+            ColliderShape::Circle { radius } => WorldShape::Round { core: [center, center], radius: *radius },
+            ColliderShape::Capsule { half_length, radius } => {
+                WorldShape::Round { core: [local(-half_length, 0.0), local(*half_length, 0.0)], radius: *radius }
+            }
+            ColliderShape::Aabb { half_width, half_height } => {
+                let (x, y) = (*half_width, *half_height);
+                WorldShape::Hull {
+                    vertices: vec![
+                        center + Vector2::new(-x, -y),
+                        center + Vector2::new(x, -y),
+                        center + Vector2::new(x, y),
+                        center + Vector2::new(-x, y),
+                    ],
+                    axes: vec![Vector2::new(1.0, 0.0), Vector2::new(0.0, 1.0)],
+                }
+            }
+            ColliderShape::Obb { half_width, half_height } => {
+                let (x, y) = (*half_width, *half_height);
+                WorldShape::Hull {
+                    vertices: vec![local(-x, -y), local(x, -y), local(x, y), local(-x, y)],
+                    axes: vec![Vector2::new(cos, sin), Vector2::new(-sin, cos)],
+                }
+            }
+            ColliderShape::Polygon { points } => {
+                let vertices: Vec<Vector2> = points.iter().map(|point| local(point.x, point.y)).collect();
+                let axes = (0..vertices.len())
+                    .map(|i| {
+                        let edge = vertices[(i + 1) % vertices.len()] - vertices[i];
+                        Vector2::new(-edge.y, edge.x)
+                    })
+                    .collect();
+                WorldShape::Hull { vertices, axes }
+            }
+        }
+    }
+}
+
+impl WorldShape {
+    // The extreme point along `direction`, or both ends when a flat side
+    // (a hull edge or the straight part of a capsule) faces that way
+    fn support_feature(&self, direction: &Vector2) -> Vec<Vector2> {
+        let (points, offset): (&[Vector2], Vector2) = match self {
+            WorldShape::Round { core, radius } => (core, *direction * *radius),
+            WorldShape::Hull { vertices, .. } => (vertices, Vector2::zero()),
+        };
+        
+        let reach = points.iter().map(|point| point.dot(direction)).fold(f32::MIN, f32::max);
+        points.iter()
+            .filter(|point| point.dot(direction) >= reach - 1e-2)
+            .map(|point| *point + offset)
+            .collect()
+    }
+    
+    fn raycast(&self, origin: &Vector2, direction: &Vector2, max_distance: f32) -> Option<(f32, Vector2)> {
+        match self {
+            WorldShape::Hull { axes, .. } => {
+                let slabs = axes.iter().map(|axis| {
+                    let (min, max) = self.project(axis);
+                    (*axis, min, max)
+                });
+                clip_ray_to_slabs(slabs, origin, direction, max_distance)
+            }
+            WorldShape::Round { core, radius } => {
+                // Projection of the core segment's box, widened by the rounding
+                let along = core[1] - core[0];
+                let side = Vector2::new(-along.y, along.x);
+                let body = if along.magnitude_squared() > 1e-12 {
+                    let slabs = [
+                        (along, core[0].dot(&along), core[1].dot(&along)),
+                        (side, core[0].dot(&side) - radius * side.magnitude(), core[0].dot(&side) + radius * side.magnitude()),
+                    ];
+                    clip_ray_to_slabs(slabs.into_iter(), origin, direction, max_distance)
+                } else {
+                    None
+                };
+                
+                core.iter()
+                    .filter_map(|center| ray_circle(origin, direction, center, *radius, max_distance))
+                    .chain(body)
+                    .min_by(|a, b| a.0.total_cmp(&b.0))
+            }
+        }
+    }
+    
+    fn project(&self, axis: &Vector2) -> (f32, f32) {
+        match self {
+            WorldShape::Round { core, radius } => {
+                let (start, end) = (core[0].dot(axis), core[1].dot(axis));
+                (start.min(end) - radius, start.max(end) + radius)
+            }
+            WorldShape::Hull { vertices, .. } => vertices.iter()
+                .map(|vertex| vertex.dot(axis))
+                .fold((f32::MAX, f32::MIN), |(min, max), value| (min.min(value), max.max(value))),
+        }
+    }
+    
+    // Candidate separating axes this shape contributes: a hull's edge normals,
+    // or for circles and capsules the line from the core to the closest point
+    // of `other`, plus the capsule's own side normal
+    fn collect_axes(&self, other: &WorldShape, axes: &mut Vec<Vector2>) {
+        match self {
+            WorldShape::Hull { axes: edge_axes, .. } => axes.extend_from_slice(edge_axes),
+            WorldShape::Round { core, .. } => {
+                let direction = core[1] - core[0];
+                axes.push(Vector2::new(-direction.y, direction.x));
+                
+                match other {
+                    WorldShape::Hull { vertices, .. } => {
+                        for vertex in vertices {
+                            axes.push(*vertex - closest_point_on_segment(core, vertex));
                         }
                     }
+                    WorldShape::Round { core: other_core, .. } => {
+                        let (point, other_point) = closest_points_between_segments(core, other_core);
+                        axes.push(other_point - point);
+                    }
                 }
             }
         }
-        
-        // Resolve collisions
-        for (idx_a, idx_b, distance) in collisions {
-            let direction = (entities[idx_a].position - entities[idx_b].position).normalized();
-            let overlap = COLLISION_RADIUS - distance;
-            
-            // Separate entities
-            entities[idx_a].position += direction * overlap * 0.5;
-            entities[idx_b].position -= direction * overlap * 0.5;
-            
-            // Apply collision response
-            let bounce_force = 120.0;
-            entities[idx_a].apply_force(direction * bounce_force);
-            entities[idx_b].apply_force(direction * -bounce_force);
-            
-            // Create particle effect
-            let collision_point = (entities[idx_a].position + entities[idx_b].position) * 0.5;
-            particle_system.create_explosion(collision_point, 3);
+    }
+}
+
+fn closest_point_on_segment(segment: &[Vector2; 2], point: &Vector2) -> Vector2 {
+    let direction = segment[1] - segment[0];
+    let length_squared = direction.magnitude_squared();
+    if length_squared < 1e-12 {
+        return segment[0];
+    }
+    
+    let t = ((*point - segment[0]).dot(&direction) / length_squared).clamp(0.0, 1.0);
+    segment[0] + direction * t
+}
+
+// Non-crossing segments are closest at one of their four endpoints; crossing ones touch
+fn closest_points_between_segments(a: &[Vector2; 2], b: &[Vector2; 2]) -> (Vector2, Vector2) {
+    let candidates = [
+        (a[0], closest_point_on_segment(b, &a[0])),
+        (a[1], closest_point_on_segment(b, &a[1])),
+        (closest_point_on_segment(a, &b[0]), b[0]),
+        (closest_point_on_segment(a, &b[1]), b[1]),
+    ];
+    candidates.into_iter()
+        .min_by(|(a0, b0), (a1, b1)| a0.distance_squared_to(b0).total_cmp(&a1.distance_squared_to(b1)))
+        .unwrap_or((a[0], b[0]))
+}
+
+// Representative contact point: the midpoint of the overlap between the two
+// touching features measured across the normal, so a box sitting on a ledge
+// is pushed from the middle of what it actually rests on
+fn contact_point(shape_a: &WorldShape, shape_b: &WorldShape, normal: &Vector2) -> Vector2 {
+    let feature_a = shape_a.support_feature(&(*normal * -1.0));
+    let feature_b = shape_b.support_feature(normal);
+    let centroid = |points: &[Vector2]| {
+        points.iter().fold(Vector2::zero(), |sum, point| sum + *point) * (1.0 / points.len().max(1) as f32)
+    };
+    let middle = (centroid(&feature_a) + centroid(&feature_b)) * 0.5;
+    
+    let tangent = Vector2::new(normal.y, -normal.x);
+    let extent = |points: &[Vector2]| points.iter()
+        .map(|point| point.dot(&tangent))
+        .fold((f32::MAX, f32::MIN), |(min, max), value| (min.min(value), max.max(value)));
+    let (min_a, max_a) = extent(&feature_a);
+    let (min_b, max_b) = extent(&feature_b);
+    let (low, high) = (min_a.max(min_b), max_a.min(max_b));
+    if low > high {
+        return middle;
+    }
+    middle + tangent * ((low + high) * 0.5 - middle.dot(&tangent))
+}
+
+// Slab test: each (axis, min, max) cuts the ray's [0, max_distance] interval,
+// and the hit normal comes from whichever slab the ray entered last
+fn clip_ray_to_slabs(slabs: impl Iterator<Item = (Vector2, f32, f32)>,
+                     origin: &Vector2, direction: &Vector2, max_distance: f32) -> Option<(f32, Vector2)> {
+    let (mut enter, mut exit) = (0.0, max_distance);
+    let mut normal = *direction * -1.0;
+    
+    for (axis, min, max) in slabs {
+        let start = origin.dot(&axis);
+        let speed = direction.dot(&axis);
+        if speed.abs() < 1e-9 {
+            if start < min || start > max {
+                return None; // Parallel and outside
+            }
+            continue;
         }
         
-        score_increment
+        let (t_min, t_max) = ((min - start) / speed, (max - start) / speed);
+        let (near, far, face) = if t_min < t_max { (t_min, t_max, axis * -1.0) } else { (t_max, t_min, axis) };
+        if near > enter {
+            enter = near;
+            normal = face;
+        }
+        exit = exit.min(far);
+        if enter > exit {
+            return None;
+        }
     }
+    
+    Some((enter, normal.normalized()))
 }
 
-// === MAIN WEB GAME STATE ===
-
-#[wasm_bindgen]
-pub struct WebGameState {
-    entities: Vec<WebEntity>,
-    particle_system: WebParticleSystem,
-    collision_system: WebCollisionSystem,
-    input_system: WebInputSystem,
-    performance: WebPerformanceMonitor,
+fn ray_circle(origin: &Vector2, direction: &Vector2, center: &Vector2, radius: f32, max_distance: f32) -> Option<(f32, Vector2)> {
+    let offset = *origin - *center;
+    let b = offset.dot(direction);
+    let c = offset.magnitude_squared() - radius * radius;
+    if c <= 0.0 {
+        return Some((0.0, *direction * -1.0));
+    }
     
-    // Camera
-    camera_x: f32,
-    camera_y: f32,
-    camera_shake: f32,
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let distance = -b - discriminant.sqrt();
+    if !(0.0..=max_distance).contains(&distance) {
+        return None;
+    }
+    Some((distance, (offset + *direction * distance).normalized()))
+}
+
+// Fraction (0..=1) of this frame's step from `previous_position` at which
+// `mover` first touches `other`, treating `other` as fixed. Bounding circles
+// give the first moment contact is possible (exact for two circles); other
+// shapes are then probed with `contact` at small steps.
+fn time_of_impact(mover: &WebEntity, other: &WebEntity) -> Option<f32> {
+    let offset = mover.previous_position - other.position;
+    let motion = mover.position - mover.previous_position;
+    
+    let exact = matches!(mover.collider, ColliderShape::Circle { .. }) && matches!(other.collider, ColliderShape::Circle { .. });
+    
+    // Circle pairs aim slightly inside contact so the discrete pass resolves them
+    let mut reach = mover.collider.bounding_radius() + other.collider.bounding_radius();
+    if exact {
+        if offset.magnitude() < reach {
+            return None; // Already overlapping; the discrete pass has it
+        }
+        reach = (reach - CCD_CONTACT_SLOP).max(reach * 0.5);
+    }
     
-    // Game state
-    score: i32,
-    level: i32,
-    paused: bool,
-    debug_mode: bool,
+    // Solve |offset + motion * t| = reach for t
+    let a = motion.magnitude_squared();
+    let b = 2.0 * offset.dot(&motion);
+    let c = offset.magnitude_squared() - reach * reach;
+    let entry = if c <= 0.0 {
+        0.0
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if a < 1e-12 || discriminant < 0.0 {
+            return None;
+        }
+        (-b - discriminant.sqrt()) / (2.0 * a)
+    };
+    if !(0.0..=1.0).contains(&entry) {
+        return None;
+    }
+    if exact {
+        return Some(entry);
+    }
+    
+    // Probe every half bounding radius of travel, fine enough not to skip a contact
+    let step = (mover.collider.bounding_radius() * 0.5 / a.sqrt()).max(1e-3);
+    let mut time = entry;
+    while time <= 1.0 {
+        let position = mover.previous_position + motion * time;
+        if mover.collider.contact(position, mover.rotation, &other.collider, other.position, other.rotation).is_some() {
+            return Some(time);
+        }
+        time += step;
+    }
+    None
+}
+
+// === WEB COLLISION SYSTEM ===
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriggerPhase {
+    Enter,
+    Stay,
+    Exit,
+}
+
+impl TriggerPhase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TriggerPhase::Enter => "enter",
+            TriggerPhase::Stay => "stay",
+            TriggerPhase::Exit => "exit",
+        }
+    }
+}
+
+// Entity ids rather than indices, so exits still make sense after removals
+#[derive(Debug, Clone, Copy)]
+pub struct TriggerEvent {
+    pub phase: TriggerPhase,
+    pub trigger: u32,
+    pub other: u32,
+}
+
+// The grid persists between frames and is keyed by entity index: each frame
+// only entities that crossed a cell boundary (or changed slot) move.
+#[derive(Debug)]
+pub struct WebCollisionSystem {
+    spatial_grid: HashMap<(i32, i32), Vec<usize>>,
+    entity_cells: Vec<Option<(i32, i32)>>,
+    cell_size: f32,
+    trigger_contacts: Vec<(u32, u32)>, // Sorted (trigger id, other id) overlaps
+    trigger_events: VecDeque<TriggerEvent>,
+}
+
+impl WebCollisionSystem {
+    pub fn new() -> Self {
+        Self {
+            spatial_grid: HashMap::new(),
+            entity_cells: Vec::new(),
+            cell_size: COLLISION_RADIUS,
+            trigger_contacts: Vec::new(),
+            trigger_events: VecDeque::new(),
+        }
+    }
+    
+    /// Trigger events since the last drain, oldest first
+    pub fn drain_trigger_events(&mut self) -> Vec<TriggerEvent> {
+        std::mem::take(&mut self.trigger_events).into()
+    }
+    
+    pub fn update(&mut self, entities: &mut [WebEntity], particle_system: &mut WebParticleSystem, rng: &mut XorShiftRandom) -> i32 {
+        Self::sweep_continuous(entities);
+        self.update_grid(entities);
+        
+        let mut score_increment = 0;
+        let mut collisions = Vec::new();
+        let mut trigger_contacts = Vec::new();
+        
+        // Check each entity against its own and the eight neighboring cells
+        for (idx_a, cell) in self.entity_cells.iter().enumerate() {
+            let Some((grid_x, grid_y)) = *cell else {
+                continue;
+            };
+            
+            for neighbor_x in (grid_x - 1)..=(grid_x + 1) {
+                for neighbor_y in (grid_y - 1)..=(grid_y + 1) {
+                    let Some(entity_indices) = self.spatial_grid.get(&(neighbor_x, neighbor_y)) else {
+                        continue;
+                    };
+                    
+                    // Each pair once, always as (lower, higher)
+                    for &idx_b in entity_indices.iter().filter(|&&idx_b| idx_b > idx_a) {
+                        let (entity_a, entity_b) = (&entities[idx_a], &entities[idx_b]);
+                        if !entity_a.collides_with(entity_b) {
+                            continue;
+                        }
+                        
+                        // Cheap bounding-circle reject before the separating-axis test
+                        let reach = entity_a.collider.bounding_radius() + entity_b.collider.bounding_radius();
+                        if entity_a.position.distance_squared_to(&entity_b.position) >= reach * reach {
+                            continue;
+                        }
+                        
+                        let contact = entity_a.collider.contact(entity_a.position, entity_a.rotation,
+                                                                &entity_b.collider, entity_b.position, entity_b.rotation);
+                        if let Some(contact) = contact {
+                            // Triggers just record the overlap by id; no push, bounce or score
+                            if entity_a.is_trigger || entity_b.is_trigger {
+                                if entity_a.is_trigger {
+                                    trigger_contacts.push((entity_a.id, entity_b.id));
+                                }
+                                if entity_b.is_trigger {
+                                    trigger_contacts.push((entity_b.id, entity_a.id));
+                                }
+                                continue;
+                            }
+                            
+                            collisions.push((idx_a, idx_b, contact));
+                            
+                            // Score for player collisions
+                            if entities[idx_a].entity_type == EntityType::Player || 
+                               entities[idx_b].entity_type == EntityType::Player {
+                                score_increment += 10;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        
+        // Resolve in index order so results don't depend on HashMap iteration order
+        collisions.sort_by_key(|&(idx_a, idx_b, _)| (idx_a, idx_b));
+        
+        trigger_contacts.sort();
+        self.report_trigger_changes(trigger_contacts);
+        
+        // Resolve collisions
+        for (idx_a, idx_b, contact) in collisions {
+            let inverse_mass_a = entities[idx_a].inverse_mass();
+            let inverse_mass_b = entities[idx_b].inverse_mass();
+            let inverse_mass_sum = inverse_mass_a + inverse_mass_b;
+            if inverse_mass_sum <= 0.0 {
+                continue; // Two immovable bodies
+            }
+            
+            // Contact normal points from b to a
+            let (normal, overlap) = (contact.normal, contact.depth);
+            
+            // Lighter entities take more of the push; kinematic ones none
+            entities[idx_a].position += normal * (overlap * inverse_mass_a / inverse_mass_sum);
+            entities[idx_b].position -= normal * (overlap * inverse_mass_b / inverse_mass_sum);
+            
+            // Velocities of the two surfaces where they touch, spin included
+            let offset_a = contact.point - entities[idx_a].position;
+            let offset_b = contact.point - entities[idx_b].position;
+            let surface_velocity = |entity: &WebEntity, offset: &Vector2| {
+                let spin = entity.angular_velocity.to_radians();
+                entity.velocity + Vector2::new(-spin * offset.y, spin * offset.x)
+            };
+            let (inverse_inertia_a, inverse_inertia_b) = (entities[idx_a].inverse_inertia(), entities[idx_b].inverse_inertia());
+            
+            // How much the contact's speed along `direction` changes per unit impulse
+            let inverse_effective_mass = |direction: &Vector2| {
+                let (lever_a, lever_b) = (offset_a.cross(direction), offset_b.cross(direction));
+                inverse_mass_sum + lever_a * lever_a * inverse_inertia_a + lever_b * lever_b * inverse_inertia_b
+            };
+            
+            // Separating entities need no bounce
+            let relative_velocity = surface_velocity(&entities[idx_a], &offset_a) - surface_velocity(&entities[idx_b], &offset_b);
+            let closing_speed = relative_velocity.dot(&normal);
+            if closing_speed < 0.0 {
+                let (friction, restitution) = entities[idx_a].material.combine(&entities[idx_b].material);
+                let normal_impulse = -(1.0 + restitution) * closing_speed / inverse_effective_mass(&normal);
+                let mut impulse = normal * normal_impulse;
+                
+                // Friction opposes sliding at the contact, which is what sets entities spinning
+                let sliding = relative_velocity - normal * closing_speed;
+                let sliding_speed = sliding.magnitude();
+                if sliding_speed > 1e-4 {
+                    let tangent = sliding * (1.0 / sliding_speed);
+                    let friction_impulse = (sliding_speed / inverse_effective_mass(&tangent)).min(friction * normal_impulse);
+                    impulse = impulse - tangent * friction_impulse;
+                }
+                
+                entities[idx_a].apply_force(impulse * inverse_mass_a);
+                entities[idx_a].angular_velocity += (offset_a.cross(&impulse) * inverse_inertia_a).to_degrees();
+                entities[idx_b].apply_force(impulse * -inverse_mass_b);
+                entities[idx_b].angular_velocity -= (offset_b.cross(&impulse) * inverse_inertia_b).to_degrees();
+            }
+            
+            // Create particle effect
+            particle_system.create_explosion(contact.point, 3, rng);
+        }
+        
+        score_increment
+    }
+    
+    /// Closest active, non-trigger entity along the ray whose layer is in `mask`
+    pub fn raycast(&mut self, entities: &[WebEntity], origin: Vector2, direction: Vector2, max_distance: f32, mask: u32) -> Option<RaycastHit> {
+        let length = direction.magnitude();
+        if length < 1e-6 || max_distance.is_nan() || max_distance < 0.0 {
+            return None;
+        }
+        let direction = direction * (1.0 / length);
+        
+        self.update_grid(entities);
+        let mut tested = vec![false; entities.len()];
+        let mut closest: Option<RaycastHit> = None;
+        
+        for (entry, (grid_x, grid_y)) in self.cells_along_ray(origin, direction, max_distance) {
+            // Cells come in ray order, so once one starts beyond the best hit
+            // no later cell's entities can be closer
+            if closest.is_some_and(|hit| entry > hit.distance) {
+                break;
+            }
+            
+            for neighbor_x in (grid_x - 1)..=(grid_x + 1) {
+                for neighbor_y in (grid_y - 1)..=(grid_y + 1) {
+                    let Some(entity_indices) = self.spatial_grid.get(&(neighbor_x, neighbor_y)) else {
+                        continue;
+                    };
+                    
+                    for &index in entity_indices {
+                        let entity = &entities[index];
+                        if tested[index] || entity.is_trigger || entity.collision_layer & mask == 0 {
+                            continue;
+                        }
+                        tested[index] = true;
+                        
+                        let Some((distance, normal)) = entity.collider.raycast(entity.position, entity.rotation, origin, direction, max_distance) else {
+                            continue;
+                        };
+                        // Ties go to the lower index so results are deterministic
+                        if closest.is_none_or(|hit| (distance, index) < (hit.distance, hit.index)) {
+                            closest = Some(RaycastHit { index, point: origin + direction * distance, normal, distance });
+                        }
+                    }
+                }
+            }
+        }
+        closest
+    }
+    
+    /// Indices of active entities (triggers included) touching the circle, in order
+    pub fn overlap_circle(&mut self, entities: &[WebEntity], center: Vector2, radius: f32) -> Vec<usize> {
+        let circle = ColliderShape::Circle { radius: radius.max(0.0) };
+        let reach = Vector2::new(radius, radius);
+        
+        self.update_grid(entities);
+        let mut indices: Vec<usize> = self.query_box(center - reach, center + reach)
+            .into_iter()
+            .filter(|&index| {
+                let entity = &entities[index];
+                circle.contact(center, 0.0, &entity.collider, entity.position, entity.rotation).is_some()
+            })
+            .collect();
+        indices.sort_unstable();
+        indices
+    }
+    
+    /// Index of the active entity closest to `point`, optionally only of one type
+    pub fn nearest_entity(&mut self, entities: &[WebEntity], point: Vector2, entity_type: Option<&EntityType>) -> Option<usize> {
+        if !point.x.is_finite() || !point.y.is_finite() {
+            return None;
+        }
+        
+        self.update_grid(entities);
+        let (occupied_min, occupied_max) = self.occupied_cells()?;
+        
+        // A box this wide around `point` contains every occupied cell
+        let full_reach = [
+            point.x - occupied_min.0 as f32 * self.cell_size,
+            (occupied_max.0 as f32 + 1.0) * self.cell_size - point.x,
+            point.y - occupied_min.1 as f32 * self.cell_size,
+            (occupied_max.1 as f32 + 1.0) * self.cell_size - point.y,
+        ].into_iter().fold(0.0f32, |widest, reach| widest.max(reach.abs()));
+        
+        // Double the box around `point` until the best match inside is within
+        // `reach`; anything outside the box is at least that far
+        let mut reach = self.cell_size;
+        loop {
+            let extent = Vector2::new(reach, reach);
+            let closest = self.query_box(point - extent, point + extent)
+                .into_iter()
+                .filter(|&index| entity_type.is_none_or(|entity_type| entities[index].entity_type == *entity_type))
+                .map(|index| (entities[index].position.distance_to(&point), index))
+                .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+            
+            let covers_grid = reach >= full_reach;
+            match closest {
+                Some((distance, index)) if distance <= reach || covers_grid => return Some(index),
+                None if covers_grid => return None,
+                _ => reach *= 2.0,
+            }
+        }
+    }
+    
+    // Moves each fast `continuous_collision` entity back along its step to
+    // where it first touched something, so the overlap test below sees the
+    // hit. Other entities are taken as stationary, and since few entities
+    // opt in a plain scan of the list is enough.
+    fn sweep_continuous(entities: &mut [WebEntity]) {
+        for index in 0..entities.len() {
+            let mover = &entities[index];
+            if !mover.active || !mover.continuous_collision || mover.is_kinematic {
+                continue;
+            }
+            
+            let motion = mover.position - mover.previous_position;
+            if motion.magnitude() <= mover.collider.bounding_radius() {
+                continue; // Too slow to skip past anything this frame
+            }
+            
+            let earliest = entities.iter()
+                .enumerate()
+                .filter(|&(other_index, other)| {
+                    other_index != index && other.active && !other.is_trigger && mover.collides_with(other)
+                })
+                .filter_map(|(_, other)| time_of_impact(mover, other))
+                .fold(None, |earliest: Option<f32>, time| Some(earliest.map_or(time, |earliest| earliest.min(time))));
+            
+            if let Some(time) = earliest {
+                let start = entities[index].previous_position;
+                entities[index].position = start + motion * time;
+            }
+        }
+    }
+    
+    // Both lists are sorted, so one merge pass finds new, ongoing and broken overlaps
+    fn report_trigger_changes(&mut self, trigger_contacts: Vec<(u32, u32)>) {
+        let (mut old, mut new) = (0, 0);
+        while old < self.trigger_contacts.len() || new < trigger_contacts.len() {
+            let (phase, (trigger, other)) = match (self.trigger_contacts.get(old), trigger_contacts.get(new)) {
+                (Some(&before), Some(&now)) if before == now => {
+                    old += 1;
+                    new += 1;
+                    (TriggerPhase::Stay, now)
+                }
+                (Some(&before), Some(&now)) if before < now => {
+                    old += 1;
+                    (TriggerPhase::Exit, before)
+                }
+                (Some(&before), None) => {
+                    old += 1;
+                    (TriggerPhase::Exit, before)
+                }
+                (_, Some(&now)) => {
+                    new += 1;
+                    (TriggerPhase::Enter, now)
+                }
+                (None, None) => break,
+            };
+            
+            // Cap the backlog for pages that never call drain_trigger_events
+            if self.trigger_events.len() >= MAX_TRIGGER_EVENTS {
+                self.trigger_events.pop_front();
+            }
+            self.trigger_events.push_back(TriggerEvent { phase, trigger, other });
+        }
+        
+        self.trigger_contacts = trigger_contacts;
+    }
+    
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+    
+    /// Re-buckets the grid to the entities' current positions
+    pub fn update_grid(&mut self, entities: &[WebEntity]) {
+        // Cells one bounding diameter wide keep every overlap within the 3x3
+        // block `update` checks; the size follows the biggest collider
+        let largest_radius = entities.iter()
+            .filter(|entity| entity.active)
+            .map(|entity| entity.collider.bounding_radius())
+            .fold(DEFAULT_COLLIDER_RADIUS, f32::max);
+        if largest_radius * 2.0 != self.cell_size {
+            self.cell_size = largest_radius * 2.0;
+            self.spatial_grid.clear();
+            self.entity_cells.clear();
+        }
+        
+        // Slots past the end belong to entities removed since last frame
+        for index in entities.len()..self.entity_cells.len() {
+            self.move_entity(index, None);
+        }
+        self.entity_cells.resize(entities.len(), None);
+        
+        for (index, entity) in entities.iter().enumerate() {
+            let cell = if entity.active {
+                Some((
+                    (entity.position.x / self.cell_size).floor() as i32,
+                    (entity.position.y / self.cell_size).floor() as i32,
+                ))
+            } else {
+                None
+            };
+            self.move_entity(index, cell);
+        }
+    }
+    
+    fn move_entity(&mut self, index: usize, cell: Option<(i32, i32)>) {
+        let previous = self.entity_cells.get(index).copied().flatten();
+        if previous == cell {
+            return;
+        }
+        
+        if let Some(previous) = previous {
+            if let Some(entity_indices) = self.spatial_grid.get_mut(&previous) {
+                entity_indices.retain(|&other| other != index);
+                if entity_indices.is_empty() {
+                    self.spatial_grid.remove(&previous);
+                }
+            }
+        }
+        if let Some(cell) = cell {
+            self.spatial_grid.entry(cell).or_default().push(index);
+        }
+        if let Some(slot) = self.entity_cells.get_mut(index) {
+            *slot = cell;
+        }
+    }
+    
+    fn cell_of(&self, position: Vector2) -> (i32, i32) {
+        ((position.x / self.cell_size).floor() as i32, (position.y / self.cell_size).floor() as i32)
+    }
+    
+    // Lowest and highest occupied cell coordinates on each axis
+    fn occupied_cells(&self) -> Option<((i32, i32), (i32, i32))> {
+        self.spatial_grid.keys().fold(None, |bounds, &(x, y)| match bounds {
+            None => Some(((x, y), (x, y))),
+            Some((min, max)) => Some(((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y)))),
+        })
+    }
+    
+    /// Indices bucketed in or next to the cells under `min..max`; an entity's
+    /// collider can reach into the neighbouring cell, so callers still test shapes
+    pub fn query_box(&self, min: Vector2, max: Vector2) -> Vec<usize> {
+        let Some((occupied_min, occupied_max)) = self.occupied_cells() else {
+            return Vec::new();
+        };
+        
+        // Clip to cells that hold entities so a screen-sized box doesn't walk empty
+        // space. `cell_of` saturates far-off corners to the i32 limits, so the margin must too
+        let (low, high) = (self.cell_of(min), self.cell_of(max));
+        let mut indices = Vec::new();
+        for x in low.0.saturating_sub(1).max(occupied_min.0)..=high.0.saturating_add(1).min(occupied_max.0) {
+            for y in low.1.saturating_sub(1).max(occupied_min.1)..=high.1.saturating_add(1).min(occupied_max.1) {
+                if let Some(entity_indices) = self.spatial_grid.get(&(x, y)) {
+                    indices.extend_from_slice(entity_indices);
+                }
+            }
+        }
+        indices
+    }
+    
+    // Cells a unit-direction ray passes through, in order, with the distance at
+    // which it enters each. Walks only the occupied part of the grid (plus the
+    // one-cell margin shapes can spill into), so long rays stay cheap
+    fn cells_along_ray(&self, origin: Vector2, direction: Vector2, max_distance: f32) -> Vec<(f32, (i32, i32))> {
+        let Some((low, high)) = self.occupied_cells() else {
+            return Vec::new();
+        };
+        
+        let region_min = [(low.0 as f32 - 1.0) * self.cell_size, (low.1 as f32 - 1.0) * self.cell_size];
+        let region_max = [(high.0 as f32 + 2.0) * self.cell_size, (high.1 as f32 + 2.0) * self.cell_size];
+        let slabs = [
+            (Vector2::new(1.0, 0.0), region_min[0], region_max[0]),
+            (Vector2::new(0.0, 1.0), region_min[1], region_max[1]),
+        ];
+        let Some((start, _)) = clip_ray_to_slabs(slabs.into_iter(), &origin, &direction, max_distance) else {
+            return Vec::new();
+        };
+        
+        let first = self.cell_of(origin + direction * start);
+        let (origin, direction) = ([origin.x, origin.y], [direction.x, direction.y]);
+        let mut cell = [first.0, first.1];
+        let mut step = [0; 2];
+        let mut next_boundary = [f32::INFINITY; 2];
+        let mut boundary_spacing = [f32::INFINITY; 2];
+        for axis in 0..2 {
+            if direction[axis] > 0.0 {
+                step[axis] = 1;
+                next_boundary[axis] = ((cell[axis] + 1) as f32 * self.cell_size - origin[axis]) / direction[axis];
+            } else if direction[axis] < 0.0 {
+                step[axis] = -1;
+                next_boundary[axis] = (cell[axis] as f32 * self.cell_size - origin[axis]) / direction[axis];
+            }
+            if step[axis] != 0 {
+                boundary_spacing[axis] = self.cell_size / direction[axis].abs();
+            }
+        }
+        
+        let mut cells = Vec::new();
+        let mut distance = start;
+        loop {
+            cells.push((distance, (cell[0], cell[1])));
+            
+            let axis = if next_boundary[0] <= next_boundary[1] { 0 } else { 1 };
+            distance = next_boundary[axis];
+            if distance > max_distance || !(region_min[axis]..=region_max[axis]).contains(&(origin[axis] + direction[axis] * distance)) {
+                break;
+            }
+            cell[axis] += step[axis];
+            next_boundary[axis] += boundary_spacing[axis];
+        }
+        cells
+    }
+}
+
+// === CAMERA ===
+
+// Orthographic 2D camera centered on `position`. Follows a target through a
+// dead zone, leading it by its velocity, can be held inside the world bounds,
+// and shakes without drifting from where it is following.
+#[derive(Debug, Clone)]
+pub struct WebCamera {
+    pub position: Vector2,
+    pub zoom: f32,
+    pub viewport: Vector2,     // Canvas size in pixels
+    pub follow_speed: f32,     // Share of the gap to the target closed per second
+    pub dead_zone: Vector2,    // Half-size of the box the target moves in freely
+    pub look_ahead: f32,       // Seconds of target velocity to lead by
+    pub clamp_to_bounds: bool, // Keep the view inside the world bounds
+    pub shake: f32,
+    shake_offset: Vector2,
+}
+
+impl WebCamera {
+    pub fn new() -> Self {
+        Self {
+            position: Vector2::new(CANVAS_WIDTH / 2.0, CANVAS_HEIGHT / 2.0),
+            zoom: 1.0,
+            viewport: Vector2::new(CANVAS_WIDTH, CANVAS_HEIGHT),
+            follow_speed: 3.0,
+            dead_zone: Vector2::zero(),
+            look_ahead: 0.0,
+            clamp_to_bounds: false,
+            shake: 0.0,
+            shake_offset: Vector2::zero(),
+        }
+    }
+    
+    /// Where the view is centered this frame, shake included
+    pub fn view_center(&self) -> Vector2 {
+        self.position + self.shake_offset
+    }
+    
+    /// Half the visible width and height in world units
+    pub fn half_extents(&self) -> Vector2 {
+        self.viewport * (0.5 / self.zoom.max(MIN_CAMERA_ZOOM))
+    }
+    
+    /// Whether any of the circle lies inside the view
+    pub fn sees(&self, center: Vector2, radius: f32) -> bool {
+        let (half, view_center) = (self.half_extents(), self.view_center());
+        (center.x - view_center.x).abs() <= half.x + radius && (center.y - view_center.y).abs() <= half.y + radius
+    }
+    
+    /// The view rectangle grown by `margin` on every side, as (min, max)
+    pub fn view_box(&self, margin: f32) -> (Vector2, Vector2) {
+        let reach = self.half_extents() + Vector2::new(margin, margin);
+        let view_center = self.view_center();
+        (view_center - reach, view_center + reach)
+    }
+    
+    /// Eases towards `target` (led by `velocity`) once it leaves the dead zone
+    pub fn follow(&mut self, target: Vector2, velocity: Vector2, delta_time: f32) {
+        let goal = target + velocity * self.look_ahead;
+        let step = (self.follow_speed * delta_time).min(1.0);
+        self.position.x += Self::dead_zone_excess(goal.x - self.position.x, self.dead_zone.x) * step;
+        self.position.y += Self::dead_zone_excess(goal.y - self.position.y, self.dead_zone.y) * step;
+    }
+    
+    fn dead_zone_excess(offset: f32, half_size: f32) -> f32 {
+        (offset.abs() - half_size).max(0.0).copysign(offset)
+    }
+    
+    /// Keeps the view inside `bounds`; a view larger than the world is centered on it
+    pub fn clamp_to(&mut self, bounds: &WorldBounds) {
+        let half = self.half_extents();
+        self.position.x = Self::clamp_axis(self.position.x, bounds.min.x, bounds.max.x, half.x);
+        self.position.y = Self::clamp_axis(self.position.y, bounds.min.y, bounds.max.y, half.y);
+    }
+    
+    fn clamp_axis(center: f32, min: f32, max: f32, half: f32) -> f32 {
+        if max - min > half * 2.0 {
+            center.clamp(min + half, max - half)
+        } else {
+            (min + max) * 0.5
+        }
+    }
+    
+    /// Rolls a fresh shake offset and decays the shake
+    pub fn update_shake(&mut self, rng: &mut XorShiftRandom) {
+        if self.shake > 0.01 {
+            self.shake_offset = Vector2::new(
+                (rng.next_f64() as f32 - 0.5) * self.shake,
+                (rng.next_f64() as f32 - 0.5) * self.shake,
+            );
+            self.shake *= 0.9; // Decay
+        } else {
+            self.shake = 0.0;
+            self.shake_offset = Vector2::zero();
+        }
+    }
+    
+    /// World to clip space as a column-major 3x3 matrix, flipping y so the
+    /// canvas's downward y ends up pointing down on screen
+    pub fn view_projection_matrix(&self) -> [f32; 9] {
+        let zoom = self.zoom.max(MIN_CAMERA_ZOOM);
+        let scale_x = 2.0 * zoom / self.viewport.x.max(1.0);
+        let scale_y = -2.0 * zoom / self.viewport.y.max(1.0);
+        let center = self.view_center();
+        [
+            scale_x, 0.0, 0.0,
+            0.0, scale_y, 0.0,
+            -scale_x * center.x, -scale_y * center.y, 1.0,
+        ]
+    }
+    
+    pub fn write_data(&self, data: &mut Vec<f32>) {
+        let center = self.view_center();
+        data.extend_from_slice(&self.view_projection_matrix());
+        data.extend_from_slice(&[center.x, center.y, self.zoom]);
+    }
+}
+
+impl Default for WebCamera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// === RENDER BUFFERS ===
+
+// Render data kept alive between frames for zero-copy reads: JS wraps each
+// buffer in a `Float32Array` over wasm memory. Buffers are cleared, not freed,
+// so their pointers stay put unless they grow or wasm memory does; check the
+// pointer each frame and rewrap when it changes.
+#[derive(Debug, Default)]
+pub struct RenderBuffers {
+    entities: Vec<f32>,  // Same layout as `get_entity_render_data`
+    particles: Vec<f32>,
+    camera: Vec<f32>,
+}
+
+// === MAIN WEB GAME STATE ===
+
+#[wasm_bindgen]
+pub struct WebGameState {
+    entities: Vec<WebEntity>,
+    next_entity_id: u32,
+    particle_system: WebParticleSystem,
+    collision_system: WebCollisionSystem,
+    input_system: WebInputSystem,
+    performance: WebPerformanceMonitor,
+    rng: XorShiftRandom,
+    world_bounds: WorldBounds,
+    materials: PhysicsMaterials,
+    render_buffers: RenderBuffers,
+    
+    // Camera
+    camera: WebCamera,
+    
+    // View culling, refreshed every update
+    view_culling: bool,
+    visible_entities: usize,
+    culled_entities: usize,
+    culled_particles: usize,
+    
+    // Game state
+    score: i32,
+    level: i32,
+    paused: bool,
+    debug_mode: bool,
 }
 
 #[wasm_bindgen]
@@ -655,14 +2060,22 @@ impl WebGameState {
         
         let mut game_state = WebGameState {
             entities: Vec::with_capacity(MAX_ENTITIES),
+            next_entity_id: 1,
             particle_system: WebParticleSystem::new(MAX_PARTICLES),
             collision_system: WebCollisionSystem::new(),
             input_system: WebInputSystem::new(),
             performance,
+            rng: XorShiftRandom::new((Math::random() * u32::MAX as f64) as u32), // Only the initial seed is browser-random
+            world_bounds: WorldBounds::new(),
+            materials: PhysicsMaterials::new(),
+            render_buffers: RenderBuffers::default(),
             
-            camera_x: CANVAS_WIDTH / 2.0,
-            camera_y: CANVAS_HEIGHT / 2.0,
-            camera_shake: 0.0,
+            camera: WebCamera::new(),
+            
+            view_culling: true,
+            visible_entities: 0,
+            culled_entities: 0,
+            culled_particles: 0,
             
             score: 0,
             level: 1,
@@ -682,34 +2095,44 @@ impl WebGameState {
             "Player".to_string(),
             EntityType::Player,
         );
-        self.entities.push(player);
+        self.push_entity(player);
         
         // Create environment entities (fewer for mobile)
         let entity_count = if self.performance.browser_info.is_mobile { 12 } else { 18 };
         
         for i in 0..entity_count {
             let position = Vector2::new(
-                50.0 + (CANVAS_WIDTH - 100.0) * Math::random() as f32,
-                50.0 + (CANVAS_HEIGHT - 100.0) * Math::random() as f32,
+                50.0 + (CANVAS_WIDTH - 100.0) * self.rng.next_f64() as f32,
+                50.0 + (CANVAS_HEIGHT - 100.0) * self.rng.next_f64() as f32,
             );
             
-            let texture_id = 1 + (Math::random() * 3.0) as u32;
+            let texture_id = 1 + (self.rng.next_f64() * 3.0) as u32;
             let name = format!("Object_{}", i);
             
             let mut env_entity = WebEntity::new(position, texture_id, name, EntityType::Environment);
             
             // Give some initial velocity for dynamic gameplay
             env_entity.velocity = Vector2::new(
-                (Math::random() as f32 - 0.5) * 40.0,
-                (Math::random() as f32 - 0.5) * 40.0,
+                (self.rng.next_f64() as f32 - 0.5) * 40.0,
+                (self.rng.next_f64() as f32 - 0.5) * 40.0,
             );
             
-            self.entities.push(env_entity);
+            // Classic idle spin until something knocks them
+            env_entity.angular_velocity = 60.0;
+            env_entity.angular_drag = 0.0;
+            
+            self.push_entity(env_entity);
         }
         
         console_log!("Initialized {} entities for web", self.entities.len());
     }
     
+    fn push_entity(&mut self, mut entity: WebEntity) {
+        entity.id = self.next_entity_id;
+        self.next_entity_id += 1;
+        self.entities.push(entity);
+    }
+    
     #[wasm_bindgen]
     pub fn update(&mut self, current_time: f64) {
         if self.paused {
@@ -742,7 +2165,7 @@ impl WebGameState {
         
         // Update entities
         for entity in &mut self.entities {
-            entity.update(delta_time);
+            entity.update(delta_time, &self.world_bounds);
             
             // Simple AI for non-player entities
             if entity.entity_type != EntityType::Player && entity.active {
@@ -758,11 +2181,11 @@ impl WebGameState {
         
         // Update systems based on quality level
         if self.performance.quality_level >= 1 {
-            let score_increment = self.collision_system.update(&mut self.entities, &mut self.particle_system);
+            let score_increment = self.collision_system.update(&mut self.entities, &mut self.particle_system, &mut self.rng);
             self.score += score_increment;
             
             if score_increment > 0 {
-                self.camera_shake = 5.0; // Screen shake on collision
+                self.camera.shake = 5.0; // Screen shake on collision
             }
         }
         
@@ -772,24 +2195,18 @@ impl WebGameState {
         
         // Update camera with smooth following and shake
         if let Some(player) = self.entities.first() {
-            let target_x = player.position.x;
-            let target_y = player.position.y;
-            
-            let lerp_speed = 3.0 * delta_time;
-            self.camera_x += (target_x - self.camera_x) * lerp_speed;
-            self.camera_y += (target_y - self.camera_y) * lerp_speed;
-            
-            // Camera shake
-            if self.camera_shake > 0.0 {
-                self.camera_x += (Math::random() as f32 - 0.5) * self.camera_shake;
-                self.camera_y += (Math::random() as f32 - 0.5) * self.camera_shake;
-                self.camera_shake *= 0.9; // Decay
-            }
+            self.camera.follow(player.position, player.velocity, delta_time);
         }
+        if self.camera.clamp_to_bounds {
+            self.camera.clamp_to(&self.world_bounds);
+        }
+        self.camera.update_shake(&mut self.rng);
         
         // Cleanup dead entities
         self.entities.retain(|e| e.is_alive());
         
+        self.cull_to_view();
+        
         // Debug output
         if self.debug_mode && self.performance.fps_counter % 60 == 0 {
             console_log!("FPS: {:.1}, Entities: {}, Particles: {}, Quality: {}", 
@@ -891,34 +2308,90 @@ impl WebGameState {
         self.paused = !self.paused;
     }
     
+    // Flags entities and particles the camera can't see so the render data
+    // skips them. Only entities the grid finds near the view get the exact
+    // test; the box is grown by a cell, so anything up to that big is caught.
+    fn cull_to_view(&mut self) {
+        if !self.view_culling {
+            for entity in &mut self.entities {
+                entity.on_screen = true;
+            }
+            self.visible_entities = self.entities.iter().filter(|entity| entity.active).count();
+            self.culled_entities = 0;
+            self.culled_particles = self.particle_system.cull(|_, _| true);
+            return;
+        }
+        
+        self.collision_system.update_grid(&self.entities);
+        let margin = self.collision_system.cell_size();
+        let (min, max) = self.camera.view_box(margin);
+        let mut near_view = vec![false; self.entities.len()];
+        for index in self.collision_system.query_box(min, max) {
+            near_view[index] = true;
+        }
+        
+        self.visible_entities = 0;
+        self.culled_entities = 0;
+        for (entity, near_view) in self.entities.iter_mut().zip(near_view) {
+            let radius = entity.collider.bounding_radius() + CULL_MARGIN;
+            entity.on_screen = (near_view || radius > margin) && self.camera.sees(entity.position, radius);
+            if entity.active {
+                if entity.on_screen {
+                    self.visible_entities += 1;
+                } else {
+                    self.culled_entities += 1;
+                }
+            }
+        }
+        
+        let camera = &self.camera;
+        self.culled_particles = self.particle_system.cull(|position, size| camera.sees(position, size));
+    }
+    
     #[wasm_bindgen]
     pub fn get_camera_position(&self) -> Vec<f32> {
-        vec![self.camera_x, self.camera_y]
+        let center = self.camera.view_center();
+        vec![center.x, center.y]
+    }
+    
+    /// 12 floats: the 3x3 view-projection matrix (column-major, for
+    /// `uniformMatrix3fv`), then the view center x, y and zoom
+    #[wasm_bindgen]
+    pub fn get_camera_data(&self) -> Vec<f32> {
+        let mut data = Vec::with_capacity(12);
+        self.camera.write_data(&mut data);
+        data
     }
     
+    /// Per active, on-screen entity, 14 floats:
+    /// - 0..9: the 3x3 model matrix in column-major order (as `uniformMatrix3fv`
+    ///   expects), see `WebEntity::model_matrix`
+    /// - 9, 10: vel_x, vel_y
+    /// - 11: texture_id
+    /// - 12: health_ratio
+    /// - 13: is_player, 1.0 or 0.0
     #[wasm_bindgen]
     pub fn get_entity_render_data(&self) -> Vec<f32> {
-        let mut data = Vec::with_capacity(self.entities.len() * 8);
-        
+        let mut data = Vec::with_capacity(self.entities.len() * ENTITY_RENDER_FLOATS);
+        self.write_entity_render_data(&mut data);
+        data
+    }
+    
+    fn write_entity_render_data(&self, data: &mut Vec<f32>) {
         for entity in &self.entities {
-            if !entity.active {
+            if !entity.active || !entity.on_screen {
                 continue;
             }
             
-            // Entity render data: pos_x, pos_y, vel_x, vel_y, rotation, texture_id, health_ratio, is_player
+            data.extend_from_slice(&entity.model_matrix());
             data.extend_from_slice(&[
-                entity.position.x,
-                entity.position.y,
                 entity.velocity.x,
                 entity.velocity.y,
-                entity.rotation,
                 entity.texture_id as f32,
                 entity.health as f32 / entity.max_health as f32,
                 if entity.entity_type == EntityType::Player { 1.0 } else { 0.0 },
             ]);
         }
-        
-        data
     }
     
     #[wasm_bindgen]
@@ -926,46 +2399,453 @@ impl WebGameState {
         self.particle_system.get_render_data()
     }
     
+    /// Refills the entity, particle and camera buffers in place; allocates only
+    /// when a frame needs more room than any before it
     #[wasm_bindgen]
-    pub fn create_explosion(&mut self, x: f32, y: f32, count: usize) {
-        let position = Vector2::new(x, y);
-        self.particle_system.create_explosion(position, count);
-        self.camera_shake = 8.0; // Add screen shake
+    pub fn update_render_buffers(&mut self) {
+        let mut buffers = std::mem::take(&mut self.render_buffers);
+        buffers.entities.clear();
+        self.write_entity_render_data(&mut buffers.entities);
+        buffers.particles.clear();
+        self.particle_system.write_render_data(&mut buffers.particles);
+        buffers.camera.clear();
+        self.camera.write_data(&mut buffers.camera);
+        self.render_buffers = buffers;
     }
     
     #[wasm_bindgen]
-    pub fn add_entity(&mut self, x: f32, y: f32, texture_id: u32, name: String, entity_type: String) -> bool {
-        if self.entities.len() >= MAX_ENTITIES {
+    pub fn entity_buffer_ptr(&self) -> *const f32 {
+        self.render_buffers.entities.as_ptr()
+    }
+    
+    #[wasm_bindgen]
+    pub fn entity_buffer_len(&self) -> usize {
+        self.render_buffers.entities.len()
+    }
+    
+    #[wasm_bindgen]
+    pub fn particle_buffer_ptr(&self) -> *const f32 {
+        self.render_buffers.particles.as_ptr()
+    }
+    
+    #[wasm_bindgen]
+    pub fn particle_buffer_len(&self) -> usize {
+        self.render_buffers.particles.len()
+    }
+    
+    #[wasm_bindgen]
+    pub fn camera_buffer_ptr(&self) -> *const f32 {
+        self.render_buffers.camera.as_ptr()
+    }
+    
+    #[wasm_bindgen]
+    pub fn camera_buffer_len(&self) -> usize {
+        self.render_buffers.camera.len()
+    }
+    
+    #[wasm_bindgen]
+    pub fn create_explosion(&mut self, x: f32, y: f32, count: usize) {
+        let position = Vector2::new(x, y);
+        self.particle_system.create_explosion(position, count, &mut self.rng);
+        self.camera.shake = 8.0; // Add screen shake
+    }
+    
+    #[wasm_bindgen]
+    pub fn add_entity(&mut self, x: f32, y: f32, texture_id: u32, name: String, entity_type: String) -> bool {
+        if self.entities.len() >= MAX_ENTITIES {
             return false;
         }
         
-        let etype = match entity_type.as_str() {
-            "Player" => EntityType::Player,
-            "Environment" => EntityType::Environment,
-            "Pickup" => EntityType::Pickup,
-            "Projectile" => EntityType::Projectile,
-            _ => EntityType::Environment,
-        };
+        let etype = EntityType::from_name(&entity_type).unwrap_or(EntityType::Environment);
         
         let entity = WebEntity::new(Vector2::new(x, y), texture_id, name, etype);
-        self.entities.push(entity);
+        self.push_entity(entity);
         true
     }
     
+    /// Stable id of the entity at `index`, or 0 if there is none
+    #[wasm_bindgen]
+    pub fn get_entity_id(&self, index: usize) -> u32 {
+        self.entities.get(index).map_or(0, |entity| entity.id)
+    }
+    
+    /// Current index of the entity with `id`, or -1 once it is gone
+    #[wasm_bindgen]
+    pub fn find_entity_index(&self, id: u32) -> i32 {
+        self.entities.iter().position(|entity| entity.id == id).map_or(-1, |index| index as i32)
+    }
+    
+    /// Opts the entity at `index` in or out of swept (continuous) collision, for
+    /// projectiles and anything else fast enough to pass through thin objects
+    #[wasm_bindgen]
+    pub fn set_entity_continuous_collision(&mut self, index: usize, enabled: bool) -> bool {
+        match self.entities.get_mut(index) {
+            Some(entity) => {
+                entity.continuous_collision = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Spin of the entity at `index` in degrees per second
+    #[wasm_bindgen]
+    pub fn set_entity_angular_velocity(&mut self, index: usize, degrees_per_second: f32) -> bool {
+        match self.entities.get_mut(index) {
+            Some(entity) => {
+                entity.angular_velocity = degrees_per_second;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Angular impulse on the entity at `index`; scale by the frame time for a steady torque
+    #[wasm_bindgen]
+    pub fn apply_entity_torque(&mut self, index: usize, torque: f32) -> bool {
+        match self.entities.get_mut(index) {
+            Some(entity) => {
+                entity.apply_torque(torque);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Angular drag and moment of inertia; a non-positive inertia derives it from mass and collider
+    #[wasm_bindgen]
+    pub fn set_entity_angular_physics(&mut self, index: usize, angular_drag: f32, inertia: f32) -> bool {
+        match self.entities.get_mut(index) {
+            Some(entity) => {
+                entity.angular_drag = angular_drag.max(0.0);
+                entity.inertia = (inertia > 0.0).then_some(inertia);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Makes the entity at `index` a sensor that reports overlaps instead of colliding
+    #[wasm_bindgen]
+    pub fn set_entity_trigger(&mut self, index: usize, is_trigger: bool) -> bool {
+        match self.entities.get_mut(index) {
+            Some(entity) => {
+                entity.is_trigger = is_trigger;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Trigger events since the last call as `[{ type, trigger, other }]`, where
+    /// type is "enter", "stay" or "exit" and trigger/other are entity ids
+    #[wasm_bindgen]
+    pub fn drain_trigger_events(&mut self) -> JsValue {
+        let events = js_sys::Array::new();
+        
+        for event in self.collision_system.drain_trigger_events() {
+            let object = js_sys::Object::new();
+            js_sys::Reflect::set(&object, &"type".into(), &event.phase.name().into()).unwrap();
+            js_sys::Reflect::set(&object, &"trigger".into(), &event.trigger.into()).unwrap();
+            js_sys::Reflect::set(&object, &"other".into(), &event.other.into()).unwrap();
+            events.push(&object);
+        }
+        
+        events.into()
+    }
+    
+    /// First solid hit as `{ entity, index, point: [x, y], normal: [x, y], distance }`
+    /// (entity is the id), or null; only entities on a layer in `mask` can be hit
+    #[wasm_bindgen]
+    pub fn raycast(&mut self, origin_x: f32, origin_y: f32, direction_x: f32, direction_y: f32, max_distance: f32, mask: u32) -> JsValue {
+        let origin = Vector2::new(origin_x, origin_y);
+        let direction = Vector2::new(direction_x, direction_y);
+        
+        match self.collision_system.raycast(&self.entities, origin, direction, max_distance, mask) {
+            Some(hit) => {
+                let object = js_sys::Object::new();
+                let point = js_sys::Array::of2(&hit.point.x.into(), &hit.point.y.into());
+                let normal = js_sys::Array::of2(&hit.normal.x.into(), &hit.normal.y.into());
+                js_sys::Reflect::set(&object, &"entity".into(), &self.entities[hit.index].id.into()).unwrap();
+                js_sys::Reflect::set(&object, &"index".into(), &(hit.index as u32).into()).unwrap();
+                js_sys::Reflect::set(&object, &"point".into(), &point).unwrap();
+                js_sys::Reflect::set(&object, &"normal".into(), &normal).unwrap();
+                js_sys::Reflect::set(&object, &"distance".into(), &hit.distance.into()).unwrap();
+                object.into()
+            }
+            None => JsValue::NULL,
+        }
+    }
+    
+    /// Ids of the entities touching the circle
+    #[wasm_bindgen]
+    pub fn overlap_circle(&mut self, x: f32, y: f32, radius: f32) -> Vec<u32> {
+        self.collision_system.overlap_circle(&self.entities, Vector2::new(x, y), radius)
+            .into_iter()
+            .map(|index| self.entities[index].id)
+            .collect()
+    }
+    
+    /// Id of the closest entity of `entity_type` (any type if empty), or 0 if none
+    #[wasm_bindgen]
+    pub fn nearest_entity(&mut self, x: f32, y: f32, entity_type: String) -> u32 {
+        let filter = if entity_type.is_empty() {
+            None
+        } else {
+            match EntityType::from_name(&entity_type) {
+                Some(entity_type) => Some(entity_type),
+                None => return 0,
+            }
+        };
+        
+        self.collision_system.nearest_entity(&self.entities, Vector2::new(x, y), filter.as_ref())
+            .map_or(0, |index| self.entities[index].id)
+    }
+    
+    /// Playfield from `(x, y)` with the given size; `margin` is how far past an
+    /// edge wrapping and destroying entities may travel first
+    #[wasm_bindgen]
+    pub fn set_world_bounds(&mut self, x: f32, y: f32, width: f32, height: f32, margin: f32) {
+        let (x_end, y_end) = (x + width, y + height);
+        self.world_bounds.min = Vector2::new(x.min(x_end), y.min(y_end));
+        self.world_bounds.max = Vector2::new(x.max(x_end), y.max(y_end));
+        self.world_bounds.margin = margin.max(0.0);
+    }
+    
+    /// World-wide edge behaviour: "wrap" (the classic default), "clamp",
+    /// "bounce", "destroy" or "unbounded"; false for anything else
+    #[wasm_bindgen]
+    pub fn set_boundary_mode(&mut self, mode: String) -> bool {
+        match BoundaryMode::from_name(&mode) {
+            Some(mode) => {
+                self.world_bounds.mode = mode;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// The real canvas size, which sets how much of the world the camera shows
+    #[wasm_bindgen]
+    pub fn set_canvas_size(&mut self, width: f32, height: f32) {
+        self.camera.viewport = Vector2::new(width.max(1.0), height.max(1.0));
+    }
+    
+    /// Above 1 magnifies
+    #[wasm_bindgen]
+    pub fn set_camera_zoom(&mut self, zoom: f32) {
+        self.camera.zoom = zoom.max(MIN_CAMERA_ZOOM);
+    }
+    
+    /// The camera stays still while the player is inside a
+    /// `dead_zone_width` x `dead_zone_height` box around the view centre, and
+    /// leads by `look_ahead` seconds of the player's velocity
+    #[wasm_bindgen]
+    pub fn set_camera_follow(&mut self, speed: f32, dead_zone_width: f32, dead_zone_height: f32, look_ahead: f32) {
+        self.camera.follow_speed = speed.max(0.0);
+        self.camera.dead_zone = Vector2::new(dead_zone_width.max(0.0) * 0.5, dead_zone_height.max(0.0) * 0.5);
+        self.camera.look_ahead = look_ahead.max(0.0);
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_camera_clamp_to_bounds(&mut self, enabled: bool) {
+        self.camera.clamp_to_bounds = enabled;
+    }
+    
+    /// Jumps the camera to center on (x, y) without easing
+    #[wasm_bindgen]
+    pub fn set_camera_position(&mut self, x: f32, y: f32) {
+        self.camera.position = Vector2::new(x, y);
+    }
+    
+    /// Off sends every entity and particle to the render data, on screen or
+    /// not; takes effect at the next update
+    #[wasm_bindgen]
+    pub fn set_view_culling(&mut self, enabled: bool) {
+        self.view_culling = enabled;
+    }
+    
+    /// Adds or replaces a named material; both combine modes must be one of
+    /// "average", "min", "multiply" or "max"
+    #[wasm_bindgen]
+    pub fn define_physics_material(&mut self, name: String, friction: f32, restitution: f32, density: f32,
+                                   friction_combine: String, restitution_combine: String) -> bool {
+        let (Some(friction_combine), Some(restitution_combine)) = (CombineMode::from_name(&friction_combine), CombineMode::from_name(&restitution_combine)) else {
+            return false;
+        };
+        self.materials.define(&name, PhysicsMaterial {
+            friction: friction.max(0.0),
+            restitution: restitution.clamp(0.0, 1.0),
+            density: density.max(0.0),
+            friction_combine,
+            restitution_combine,
+        });
+        true
+    }
+    
+    /// Reads a scene file's materials table, `{ name: { friction, restitution,
+    /// density, frictionCombine, restitutionCombine } }`, filling gaps from the
+    /// default material; returns the number of entries accepted
+    #[wasm_bindgen]
+    pub fn load_physics_materials(&mut self, materials: &JsValue) -> u32 {
+        if !materials.is_object() {
+            return 0;
+        }
+        let defaults = PhysicsMaterial::default();
+        let number = |entry: &JsValue, key: &str, default: f32| {
+            js_sys::Reflect::get(entry, &key.into()).ok().and_then(|value| value.as_f64()).map_or(default, |value| value as f32)
+        };
+        let mode = |entry: &JsValue, key: &str, default: CombineMode| {
+            js_sys::Reflect::get(entry, &key.into()).ok()
+                .and_then(|value| value.as_string())
+                .and_then(|name| CombineMode::from_name(&name))
+                .unwrap_or(default)
+        };
+        
+        let mut count = 0;
+        for name in js_sys::Object::keys(&js_sys::Object::from(materials.clone())).iter() {
+            let (Some(name), Ok(entry)) = (name.as_string(), js_sys::Reflect::get(materials, &name)) else {
+                continue;
+            };
+            if !entry.is_object() {
+                continue;
+            }
+            
+            self.materials.define(&name, PhysicsMaterial {
+                friction: number(&entry, "friction", defaults.friction).max(0.0),
+                restitution: number(&entry, "restitution", defaults.restitution).clamp(0.0, 1.0),
+                density: number(&entry, "density", defaults.density).max(0.0),
+                friction_combine: mode(&entry, "frictionCombine", defaults.friction_combine),
+                restitution_combine: mode(&entry, "restitutionCombine", defaults.restitution_combine),
+            });
+            count += 1;
+        }
+        count
+    }
+    
+    /// Gives the entity at `index` a named material and sets its mass from the
+    /// material's density; false for unknown names
+    #[wasm_bindgen]
+    pub fn set_entity_material(&mut self, index: usize, name: String) -> bool {
+        match (self.materials.get(&name), self.entities.get_mut(index)) {
+            (Some(material), Some(entity)) => {
+                entity.mass = material.mass_for(&entity.collider);
+                entity.material = material;
+                true
+            }
+            _ => false,
+        }
+    }
+    
+    /// Overrides the world's edge behaviour for one entity; "world" drops the override
+    #[wasm_bindgen]
+    pub fn set_entity_boundary_mode(&mut self, index: usize, mode: String) -> bool {
+        let mode = match mode.as_str() {
+            "world" => None,
+            name => match BoundaryMode::from_name(name) {
+                Some(mode) => Some(mode),
+                None => return false,
+            },
+        };
+        
+        match self.entities.get_mut(index) {
+            Some(entity) => {
+                entity.boundary_mode = mode;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    #[wasm_bindgen]
+    pub fn get_seed(&self) -> u32 {
+        self.rng.seed()
+    }
+    
+    /// Gives the entity at `index` a collision shape; `kind` is "circle", "box",
+    /// "obb", "capsule" or "polygon". Circles and capsules read the radius from
+    /// `params[0]` (capsules also the half length from `params[1]`), boxes the
+    /// half width and height, and polygons local [x0, y0, x1, y1, ...] points.
+    /// False for negative sizes or a polygon that isn't strictly convex.
+    #[wasm_bindgen]
+    pub fn set_entity_collider(&mut self, index: usize, kind: String, params: Vec<f32>) -> bool {
+        let param = |i: usize| params.get(i).copied();
+        let collider = match (kind.as_str(), param(0), param(1)) {
+            ("circle", Some(radius), _) => ColliderShape::Circle { radius },
+            ("box", Some(half_width), Some(half_height)) => ColliderShape::Aabb { half_width, half_height },
+            ("obb", Some(half_width), Some(half_height)) => ColliderShape::Obb { half_width, half_height },
+            ("capsule", Some(radius), Some(half_length)) => ColliderShape::Capsule { half_length, radius },
+            ("polygon", _, _) if params.len() >= 6 && params.len().is_multiple_of(2) => ColliderShape::Polygon {
+                points: params.chunks_exact(2).map(|chunk| Vector2::new(chunk[0], chunk[1])).collect(),
+            },
+            _ => return false,
+        };
+        if !collider.is_valid() {
+            return false;
+        }
+        
+        match self.entities.get_mut(index) {
+            Some(entity) => {
+                entity.collider = collider;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Sets the layer bits the entity at `index` is on and the layer bits it
+    /// collides with; layer 0 (or mask 0) takes it out of collision entirely
+    #[wasm_bindgen]
+    pub fn set_entity_collision_layers(&mut self, index: usize, layer: u32, mask: u32) -> bool {
+        match self.entities.get_mut(index) {
+            Some(entity) => {
+                entity.collision_layer = layer;
+                entity.collision_mask = mask;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Sets collision mass, restitution and kinematic flag for the entity at `index`
+    #[wasm_bindgen]
+    pub fn set_entity_physics(&mut self, index: usize, mass: f32, restitution: f32, is_kinematic: bool) -> bool {
+        match self.entities.get_mut(index) {
+            Some(entity) => {
+                entity.mass = mass.max(0.0);
+                entity.material.restitution = restitution.clamp(0.0, 1.0);
+                entity.is_kinematic = is_kinematic;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_seed(&mut self, seed: u32) {
+        self.rng.set_seed(seed);
+        console_log!("Seed set to {}", seed);
+        self.reset_game();
+    }
+    
     #[wasm_bindgen]
     pub fn reset_game(&mut self) {
         console_log!("Resetting web game state");
         
+        // Rewind the generator so the rebuilt scene matches the first one
+        let seed = self.rng.seed();
+        self.rng.set_seed(seed);
+        
         self.entities.clear();
         self.particle_system.clear();
         self.score = 0;
         self.level = 1;
         self.paused = false;
-        self.camera_shake = 0.0;
         
-        // Reset camera
-        self.camera_x = CANVAS_WIDTH / 2.0;
-        self.camera_y = CANVAS_HEIGHT / 2.0;
+        // Reset camera position, keeping its settings
+        self.camera.position = Vector2::new(CANVAS_WIDTH / 2.0, CANVAS_HEIGHT / 2.0);
+        self.camera.shake = 0.0;
+        self.camera.update_shake(&mut self.rng); // Clears the offset
         
         // Reset performance metrics
         self.performance.fps_counter = 0;
@@ -998,6 +2878,9 @@ impl WebGameState {
         js_sys::Reflect::set(&info, &"adaptiveQuality".into(), &self.performance.adaptive_quality.into()).unwrap();
         js_sys::Reflect::set(&info, &"entityCount".into(), &self.entities.len().into()).unwrap();
         js_sys::Reflect::set(&info, &"particleCount".into(), &self.particle_system.active_particle_count().into()).unwrap();
+        js_sys::Reflect::set(&info, &"visibleEntities".into(), &self.visible_entities.into()).unwrap();
+        js_sys::Reflect::set(&info, &"culledEntities".into(), &self.culled_entities.into()).unwrap();
+        js_sys::Reflect::set(&info, &"culledParticles".into(), &self.culled_particles.into()).unwrap();
         js_sys::Reflect::set(&info, &"isPerformanceGood".into(), &self.performance.is_performance_good().into()).unwrap();
         
         info.into()
@@ -1011,6 +2894,206 @@ impl WebGameState {
     }
 }
 
+// Natively testable parts only: `WebGameState::new` needs the browser
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn sat_contacts_between_shapes() {
+        let square = ColliderShape::Aabb { half_width: 10.0, half_height: 10.0 };
+        let circle = ColliderShape::Circle { radius: 5.0 };
+        let origin = Vector2::zero();
+        let contact = square.contact(origin, 0.0, &circle, Vector2::new(13.0, 0.0), 0.0).unwrap();
+        assert!((contact.depth - 2.0).abs() < 1e-4);
+        assert!((contact.normal.x + 1.0).abs() < 1e-4 && contact.normal.y.abs() < 1e-4);
+        assert!(square.contact(origin, 0.0, &circle, Vector2::new(14.0, 14.0), 0.0).is_none());
+        
+        // Rotation only matters for Obb: turned 45 degrees its corner reaches x = 14.1
+        let oriented = ColliderShape::Obb { half_width: 10.0, half_height: 10.0 };
+        assert!(oriented.contact(origin, 45.0, &circle, Vector2::new(18.0, 0.0), 0.0).is_some());
+        assert!(square.contact(origin, 45.0, &circle, Vector2::new(18.0, 0.0), 0.0).is_none());
+        
+        let triangle = ColliderShape::Polygon { points: vec![Vector2::new(0.0, 0.0), Vector2::new(10.0, 0.0), Vector2::new(0.0, 10.0)] };
+        assert!(triangle.contact(origin, 0.0, &circle, Vector2::new(9.0, 9.0), 0.0).is_none());
+        assert!(triangle.contact(origin, 0.0, &circle, Vector2::new(6.0, 6.0), 0.0).is_some());
+    }
+    
+    #[test]
+    fn invalid_colliders_are_rejected() {
+        let polygon = |points: &[(f32, f32)]| ColliderShape::Polygon {
+            points: points.iter().map(|&(x, y)| Vector2::new(x, y)).collect(),
+        };
+        assert!(polygon(&[(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)]).is_valid());
+        assert!(polygon(&[(0.0, 0.0), (0.0, 10.0), (10.0, 0.0)]).is_valid());
+        assert!(!polygon(&[(0.0, 0.0), (10.0, 0.0)]).is_valid());
+        assert!(!polygon(&[(0.0, 0.0), (5.0, 0.0), (10.0, 0.0)]).is_valid());
+        assert!(!polygon(&[(0.0, 0.0), (10.0, 0.0), (2.0, 2.0), (0.0, 10.0)]).is_valid());
+        let star: Vec<(f32, f32)> = (0..5)
+            .map(|i| (i as f32 * 4.0 * std::f32::consts::PI / 5.0).sin_cos())
+            .collect();
+        assert!(!polygon(&star).is_valid());
+        assert!(!ColliderShape::Circle { radius: -1.0 }.is_valid());
+        assert!(!ColliderShape::Aabb { half_width: 1.0, half_height: -1.0 }.is_valid());
+        assert!(ColliderShape::Capsule { half_length: 0.0, radius: 1.0 }.is_valid());
+    }
+    
+    // A trigger and a crate touching at (100, 100), with ids 1 and 2
+    fn trigger_pair() -> Vec<WebEntity> {
+        let mut entities = vec![
+            WebEntity::new(Vector2::new(100.0, 100.0), 0, "Zone".to_string(), EntityType::Environment),
+            WebEntity::new(Vector2::new(105.0, 100.0), 0, "Crate".to_string(), EntityType::Environment),
+        ];
+        entities[0].is_trigger = true;
+        for (index, entity) in entities.iter_mut().enumerate() {
+            entity.id = index as u32 + 1;
+        }
+        entities
+    }
+    
+    #[test]
+    fn triggers_report_without_pushing() {
+        let mut collisions = WebCollisionSystem::new();
+        let mut particles = WebParticleSystem::new(MAX_PARTICLES);
+        let mut rng = XorShiftRandom::new(1);
+        let mut entities = trigger_pair();
+        
+        assert_eq!(collisions.update(&mut entities, &mut particles, &mut rng), 0);
+        collisions.update(&mut entities, &mut particles, &mut rng);
+        entities[1].position = Vector2::new(500.0, 100.0);
+        collisions.update(&mut entities, &mut particles, &mut rng);
+        
+        let events: Vec<_> = collisions.drain_trigger_events().iter()
+            .map(|event| (event.phase, event.trigger, event.other))
+            .collect();
+        assert_eq!(events, vec![(TriggerPhase::Enter, 1, 2), (TriggerPhase::Stay, 1, 2), (TriggerPhase::Exit, 1, 2)]);
+        assert!(collisions.drain_trigger_events().is_empty());
+    }
+    
+    #[test]
+    fn undrained_trigger_events_drop_oldest() {
+        let mut collisions = WebCollisionSystem::new();
+        let mut particles = WebParticleSystem::new(MAX_PARTICLES);
+        let mut rng = XorShiftRandom::new(1);
+        let mut entities = trigger_pair();
+        
+        // Alternating enter and exit, one event per update
+        for frame in 0..MAX_TRIGGER_EVENTS + 1 {
+            entities[1].position.x = if frame % 2 == 0 { 105.0 } else { 500.0 };
+            collisions.update(&mut entities, &mut particles, &mut rng);
+        }
+        let events = collisions.drain_trigger_events();
+        assert_eq!(events.len(), MAX_TRIGGER_EVENTS);
+        assert_eq!(events[0].phase, TriggerPhase::Exit);
+        assert_eq!(events[MAX_TRIGGER_EVENTS - 1].phase, TriggerPhase::Enter);
+    }
+    
+    #[test]
+    fn continuous_collision_stops_tunneling() {
+        // One 200 unit step across a wall 2 units thick
+        let final_x = |continuous: bool| {
+            let mut wall = WebEntity::new(Vector2::new(500.0, 100.0), 0, "Wall".to_string(), EntityType::Environment);
+            wall.collider = ColliderShape::Aabb { half_width: 1.0, half_height: 100.0 };
+            wall.is_kinematic = true;
+            let mut bullet = WebEntity::new(Vector2::new(600.0, 100.0), 0, "Bullet".to_string(), EntityType::Projectile);
+            bullet.previous_position = Vector2::new(400.0, 100.0);
+            bullet.collider = ColliderShape::Circle { radius: 2.0 };
+            bullet.continuous_collision = continuous;
+            
+            let mut entities = vec![wall, bullet];
+            WebCollisionSystem::new().update(&mut entities, &mut WebParticleSystem::new(MAX_PARTICLES), &mut XorShiftRandom::new(1));
+            entities[1].position.x
+        };
+        
+        assert_eq!(final_x(false), 600.0);
+        assert!(final_x(true) < 500.0, "{}", final_x(true));
+    }
+    
+    #[test]
+    fn queries_find_closest_hits_and_overlaps() {
+        let mut entities = vec![
+            WebEntity::new(Vector2::new(100.0, 100.0), 0, "Wall".to_string(), EntityType::Environment),
+            WebEntity::new(Vector2::new(300.0, 100.0), 0, "Near".to_string(), EntityType::Pickup),
+            WebEntity::new(Vector2::new(900.0, 700.0), 0, "Far".to_string(), EntityType::Pickup),
+        ];
+        entities[0].collider = ColliderShape::Aabb { half_width: 10.0, half_height: 20.0 };
+        let mut collisions = WebCollisionSystem::new();
+        
+        let hit = collisions.raycast(&entities, Vector2::new(0.0, 105.0), Vector2::new(1.0, 0.0), 1000.0, u32::MAX).unwrap();
+        assert_eq!(hit.index, 0);
+        assert!((hit.distance - 90.0).abs() < 1e-3 && (hit.normal.x + 1.0).abs() < 1e-4, "{:?}", hit);
+        
+        // Unnormalized directions are fine; too short a range or a zero mask misses
+        let hit = collisions.raycast(&entities, Vector2::new(1000.0, 100.0), Vector2::new(-2.0, 0.0), 1e9, u32::MAX).unwrap();
+        assert_eq!(hit.index, 1);
+        assert!(collisions.raycast(&entities, Vector2::new(1000.0, 100.0), Vector2::new(-1.0, 0.0), 600.0, u32::MAX).is_none());
+        assert!(collisions.raycast(&entities, Vector2::new(0.0, 105.0), Vector2::new(1.0, 0.0), 1000.0, 0).is_none());
+        
+        // A trigger wall lets the ray through but is still found by overlaps
+        entities[0].is_trigger = true;
+        assert_eq!(collisions.raycast(&entities, Vector2::new(0.0, 105.0), Vector2::new(1.0, 0.0), 1000.0, u32::MAX).unwrap().index, 1);
+        assert_eq!(collisions.overlap_circle(&entities, Vector2::new(200.0, 100.0), 95.0), vec![0, 1]);
+        assert!(collisions.overlap_circle(&entities, Vector2::new(200.0, 400.0), 5.0).is_empty());
+        
+        assert_eq!(collisions.nearest_entity(&entities, Vector2::zero(), None), Some(0));
+        assert_eq!(collisions.nearest_entity(&entities, Vector2::zero(), Some(&EntityType::Pickup)), Some(1));
+        assert_eq!(collisions.nearest_entity(&entities, Vector2::new(5000.0, 5000.0), Some(&EntityType::Pickup)), Some(2));
+        assert_eq!(collisions.nearest_entity(&entities, Vector2::zero(), Some(&EntityType::Player)), None);
+    }
+    
+    #[test]
+    fn boundary_modes_at_the_right_edge() {
+        let bounds = WorldBounds::default();
+        let leave = |mode: BoundaryMode| {
+            let mut entity = WebEntity::new(Vector2::new(CANVAS_WIDTH - 20.0, 100.0), 0, "Crate".to_string(), EntityType::Environment);
+            entity.boundary_mode = Some(mode);
+            entity.velocity = Vector2::new(1000.0, 0.0);
+            entity.update(1.0, &bounds);
+            entity
+        };
+        let radius = DEFAULT_COLLIDER_RADIUS;
+        
+        assert_eq!(leave(BoundaryMode::Wrap).position.x, -BOUNDARY_MARGIN);
+        let clamped = leave(BoundaryMode::Clamp);
+        assert_eq!((clamped.position.x, clamped.velocity.x), (CANVAS_WIDTH - radius, 0.0));
+        let bounced = leave(BoundaryMode::Bounce);
+        assert_eq!(bounced.position.x, CANVAS_WIDTH - radius);
+        assert!(bounced.velocity.x < 0.0);
+        assert!(!leave(BoundaryMode::Destroy).active);
+        assert_eq!(leave(BoundaryMode::Unbounded).position.x, CANVAS_WIDTH + 980.0);
+    }
+    
+    #[test]
+    fn model_matrix_scales_to_the_collider() {
+        let mut entity = WebEntity::new(Vector2::new(10.0, 20.0), 0, "Crate".to_string(), EntityType::Environment);
+        assert_eq!(entity.model_matrix(), [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 10.0, 20.0, 1.0]);
+        
+        entity.collider = ColliderShape::Obb { half_width: DEFAULT_COLLIDER_RADIUS * 2.0, half_height: DEFAULT_COLLIDER_RADIUS * 0.5 };
+        entity.rotation = 90.0;
+        let expected = [0.0, 2.0, 0.0, -0.5, 0.0, 0.0, 10.0, 20.0, 1.0];
+        for (value, expected) in entity.model_matrix().iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-5, "{:?}", entity.model_matrix());
+        }
+    }
+    
+    #[test]
+    fn bad_query_points_neither_hang_nor_overflow() {
+        let mut collisions = WebCollisionSystem::new();
+        let entities = trigger_pair();
+        
+        assert_eq!(collisions.nearest_entity(&entities, Vector2::new(f32::NAN, 0.0), None), None);
+        assert_eq!(collisions.nearest_entity(&entities, Vector2::new(0.0, f32::INFINITY), None), None);
+        assert!(collisions.nearest_entity(&entities, Vector2::new(1e12, -1e12), None).is_some());
+        assert_eq!(collisions.nearest_entity(&entities, Vector2::new(1e12, 0.0), Some(&EntityType::Player)), None);
+        
+        // Corners past the i32 cell range saturate instead of wrapping
+        let mut everything = collisions.query_box(Vector2::new(-1e12, -1e12), Vector2::new(1e12, 1e12));
+        everything.sort_unstable();
+        assert_eq!(everything, vec![0, 1]);
+        assert!(collisions.query_box(Vector2::new(1e12, 1e12), Vector2::new(2e12, 2e12)).is_empty());
+    }
+}
+
 // === WEB ENGINE WRAPPER ===
 
 #[wasm_bindgen]
@@ -1068,6 +3151,9 @@ impl WebGameEngine {
         let camera = self.game_state.get_camera_position();
         let camera_array = js_sys::Float32Array::from(&camera[..]);
         js_sys::Reflect::set(&data, &"camera".into(), &camera_array.into()).unwrap();
+        let view_projection = self.game_state.get_camera_data();
+        js_sys::Reflect::set(&data, &"cameraData".into(), 
+                           &js_sys::Float32Array::from(&view_projection[..]).into()).unwrap();
         
         // Canvas dimensions
         js_sys::Reflect::set(&data, &"canvasWidth".into(), &CANVAS_WIDTH.into()).unwrap();
@@ -1076,6 +3162,44 @@ impl WebGameEngine {
         data.into()
     }
     
+    /// Refills the game state's render buffers for zero-copy reads through
+    /// `entity_buffer_ptr`/`entity_buffer_len` and the particle and camera pairs;
+    /// layouts match `get_render_data`
+    #[wasm_bindgen]
+    pub fn update_render_buffers(&mut self) {
+        self.game_state.update_render_buffers();
+    }
+    
+    #[wasm_bindgen]
+    pub fn entity_buffer_ptr(&self) -> *const f32 {
+        self.game_state.entity_buffer_ptr()
+    }
+    
+    #[wasm_bindgen]
+    pub fn entity_buffer_len(&self) -> usize {
+        self.game_state.entity_buffer_len()
+    }
+    
+    #[wasm_bindgen]
+    pub fn particle_buffer_ptr(&self) -> *const f32 {
+        self.game_state.particle_buffer_ptr()
+    }
+    
+    #[wasm_bindgen]
+    pub fn particle_buffer_len(&self) -> usize {
+        self.game_state.particle_buffer_len()
+    }
+    
+    #[wasm_bindgen]
+    pub fn camera_buffer_ptr(&self) -> *const f32 {
+        self.game_state.camera_buffer_ptr()
+    }
+    
+    #[wasm_bindgen]
+    pub fn camera_buffer_len(&self) -> usize {
+        self.game_state.camera_buffer_len()
+    }
+    
     #[wasm_bindgen]
     pub fn get_game_info(&self) -> JsValue {
         let info = js_sys::Object::new();
@@ -1118,6 +3242,147 @@ impl WebGameEngine {
         self.game_state.add_entity(x, y, texture_id, name, entity_type)
     }
     
+    #[wasm_bindgen]
+    pub fn set_seed(&mut self, seed: u32) {
+        self.game_state.set_seed(seed);
+    }
+    
+    #[wasm_bindgen]
+    pub fn get_seed(&self) -> u32 {
+        self.game_state.get_seed()
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_entity_physics(&mut self, index: usize, mass: f32, restitution: f32, is_kinematic: bool) -> bool {
+        self.game_state.set_entity_physics(index, mass, restitution, is_kinematic)
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_entity_collider(&mut self, index: usize, kind: String, params: Vec<f32>) -> bool {
+        self.game_state.set_entity_collider(index, kind, params)
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_entity_collision_layers(&mut self, index: usize, layer: u32, mask: u32) -> bool {
+        self.game_state.set_entity_collision_layers(index, layer, mask)
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_entity_trigger(&mut self, index: usize, is_trigger: bool) -> bool {
+        self.game_state.set_entity_trigger(index, is_trigger)
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_entity_continuous_collision(&mut self, index: usize, enabled: bool) -> bool {
+        self.game_state.set_entity_continuous_collision(index, enabled)
+    }
+    
+    #[wasm_bindgen]
+    pub fn get_entity_id(&self, index: usize) -> u32 {
+        self.game_state.get_entity_id(index)
+    }
+    
+    #[wasm_bindgen]
+    pub fn find_entity_index(&self, id: u32) -> i32 {
+        self.game_state.find_entity_index(id)
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_entity_angular_velocity(&mut self, index: usize, degrees_per_second: f32) -> bool {
+        self.game_state.set_entity_angular_velocity(index, degrees_per_second)
+    }
+    
+    #[wasm_bindgen]
+    pub fn apply_entity_torque(&mut self, index: usize, torque: f32) -> bool {
+        self.game_state.apply_entity_torque(index, torque)
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_entity_angular_physics(&mut self, index: usize, angular_drag: f32, inertia: f32) -> bool {
+        self.game_state.set_entity_angular_physics(index, angular_drag, inertia)
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_world_bounds(&mut self, x: f32, y: f32, width: f32, height: f32, margin: f32) {
+        self.game_state.set_world_bounds(x, y, width, height, margin);
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_boundary_mode(&mut self, mode: String) -> bool {
+        self.game_state.set_boundary_mode(mode)
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_entity_boundary_mode(&mut self, index: usize, mode: String) -> bool {
+        self.game_state.set_entity_boundary_mode(index, mode)
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_canvas_size(&mut self, width: f32, height: f32) {
+        self.game_state.set_canvas_size(width, height);
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_camera_zoom(&mut self, zoom: f32) {
+        self.game_state.set_camera_zoom(zoom);
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_camera_follow(&mut self, speed: f32, dead_zone_width: f32, dead_zone_height: f32, look_ahead: f32) {
+        self.game_state.set_camera_follow(speed, dead_zone_width, dead_zone_height, look_ahead);
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_camera_clamp_to_bounds(&mut self, enabled: bool) {
+        self.game_state.set_camera_clamp_to_bounds(enabled);
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_camera_position(&mut self, x: f32, y: f32) {
+        self.game_state.set_camera_position(x, y);
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_view_culling(&mut self, enabled: bool) {
+        self.game_state.set_view_culling(enabled);
+    }
+    
+    #[wasm_bindgen]
+    pub fn define_physics_material(&mut self, name: String, friction: f32, restitution: f32, density: f32,
+                                   friction_combine: String, restitution_combine: String) -> bool {
+        self.game_state.define_physics_material(name, friction, restitution, density, friction_combine, restitution_combine)
+    }
+    
+    #[wasm_bindgen]
+    pub fn load_physics_materials(&mut self, materials: &JsValue) -> u32 {
+        self.game_state.load_physics_materials(materials)
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_entity_material(&mut self, index: usize, name: String) -> bool {
+        self.game_state.set_entity_material(index, name)
+    }
+    
+    #[wasm_bindgen]
+    pub fn raycast(&mut self, origin_x: f32, origin_y: f32, direction_x: f32, direction_y: f32, max_distance: f32, mask: u32) -> JsValue {
+        self.game_state.raycast(origin_x, origin_y, direction_x, direction_y, max_distance, mask)
+    }
+    
+    #[wasm_bindgen]
+    pub fn overlap_circle(&mut self, x: f32, y: f32, radius: f32) -> Vec<u32> {
+        self.game_state.overlap_circle(x, y, radius)
+    }
+    
+    #[wasm_bindgen]
+    pub fn nearest_entity(&mut self, x: f32, y: f32, entity_type: String) -> u32 {
+        self.game_state.nearest_entity(x, y, entity_type)
+    }
+    
+    #[wasm_bindgen]
+    pub fn drain_trigger_events(&mut self) -> JsValue {
+        self.game_state.drain_trigger_events()
+    }
+    
     #[wasm_bindgen]
     pub fn reset(&mut self) {
         self.game_state.reset_game();